@@ -0,0 +1,31 @@
+-- Per-store inventory: current on-hand quantity plus an append-only movement ledger.
+-- store_stock.qty is always the running sum of stock_movements.qty for the same store/product.
+
+CREATE TABLE IF NOT EXISTS store_stock (
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    product_id INTEGER NOT NULL REFERENCES products(id),
+    qty INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (store_id, product_id)
+);
+
+CREATE TABLE IF NOT EXISTS stock_movements (
+    id SERIAL PRIMARY KEY,
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    product_id INTEGER NOT NULL REFERENCES products(id),
+    -- sale, adjustment, transfer, receipt, return
+    movement_type VARCHAR(20) NOT NULL,
+    -- Signed quantity: negative for stock leaving the store, positive for stock entering it
+    qty INTEGER NOT NULL,
+    balance_after INTEGER NOT NULL,
+    reference_type VARCHAR(50),
+    reference_id INTEGER,
+    note TEXT,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_movements_store_product
+    ON stock_movements (store_id, product_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_stock_movements_reference
+    ON stock_movements (reference_type, reference_id);
@@ -0,0 +1,22 @@
+-- Company-level POS configuration. Companies without a row use the defaults below.
+CREATE TABLE IF NOT EXISTS company_settings (
+    company_id INTEGER PRIMARY KEY REFERENCES companies(id),
+    allow_negative_stock BOOLEAN NOT NULL DEFAULT FALSE,
+    reservation_ttl_minutes INTEGER NOT NULL DEFAULT 15,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- One reservation per cart line. Deleting the cart line (delete_from_cart, clear_cart,
+-- checkout) releases its reservation through the cascade; expired rows are ignored.
+CREATE TABLE IF NOT EXISTS stock_reservations (
+    id SERIAL PRIMARY KEY,
+    cart_item_id INTEGER NOT NULL UNIQUE REFERENCES sales_cart(id) ON DELETE CASCADE,
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    product_id INTEGER NOT NULL REFERENCES products(id),
+    qty INTEGER NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_reservations_store_product
+    ON stock_reservations (store_id, product_id, expires_at);
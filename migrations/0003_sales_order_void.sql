@@ -0,0 +1,6 @@
+-- Orders are never deleted; voiding flags the order and keeps it visible in reports.
+ALTER TABLE sales_orders
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'completed',
+    ADD COLUMN IF NOT EXISTS voided_at TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS voided_by INTEGER REFERENCES users(id),
+    ADD COLUMN IF NOT EXISTS void_reason TEXT;
@@ -0,0 +1,30 @@
+-- Customer returns against completed sales orders. A return may cover part of an order;
+-- the sum of returned qty per sales_order_details row never exceeds the sold qty.
+CREATE TABLE IF NOT EXISTS sales_returns (
+    id SERIAL PRIMARY KEY,
+    order_id INTEGER NOT NULL REFERENCES sales_orders(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    date DATE NOT NULL,
+    total_refund NUMERIC(15, 2) NOT NULL,
+    refund_cash NUMERIC(15, 2) NOT NULL DEFAULT 0,
+    refund_non_cash NUMERIC(15, 2) NOT NULL DEFAULT 0,
+    reason TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS sales_return_details (
+    id SERIAL PRIMARY KEY,
+    return_id INTEGER NOT NULL REFERENCES sales_returns(id),
+    order_detail_id INTEGER NOT NULL REFERENCES sales_order_details(id),
+    product_id INTEGER NOT NULL REFERENCES products(id),
+    qty INTEGER NOT NULL CHECK (qty > 0),
+    unit_refund NUMERIC(15, 2) NOT NULL,
+    total_refund NUMERIC(15, 2) NOT NULL,
+    -- Resellable goods go back into store stock; the rest are written off
+    resellable BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_returns_order ON sales_returns (order_id);
+CREATE INDEX IF NOT EXISTS idx_sales_returns_store_date ON sales_returns (store_id, date);
+CREATE INDEX IF NOT EXISTS idx_sales_return_details_order_detail ON sales_return_details (order_detail_id);
@@ -0,0 +1,19 @@
+-- Company-scoped customer master data referenced by sales_orders.customer_id
+CREATE TABLE IF NOT EXISTS customers (
+    id SERIAL PRIMARY KEY,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    name VARCHAR(255) NOT NULL,
+    phone VARCHAR(50),
+    email VARCHAR(255),
+    address TEXT,
+    notes TEXT,
+    deleted_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_customers_company_name ON customers (company_id, name);
+CREATE INDEX IF NOT EXISTS idx_customers_company_phone ON customers (company_id, phone);
+
+ALTER TABLE sales_orders
+    ADD CONSTRAINT fk_sales_orders_customer FOREIGN KEY (customer_id) REFERENCES customers(id);
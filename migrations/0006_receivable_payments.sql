@@ -0,0 +1,18 @@
+-- Settlements of sales_orders.receivable. The outstanding balance of an order is
+-- receivable minus the sum of its payments and is never allowed to go below zero.
+CREATE TABLE IF NOT EXISTS receivable_payments (
+    id SERIAL PRIMARY KEY,
+    order_id INTEGER NOT NULL REFERENCES sales_orders(id),
+    customer_id INTEGER REFERENCES customers(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    date DATE NOT NULL,
+    payment_cash NUMERIC(15, 2) NOT NULL DEFAULT 0,
+    payment_non_cash NUMERIC(15, 2) NOT NULL DEFAULT 0,
+    amount NUMERIC(15, 2) NOT NULL CHECK (amount > 0),
+    note TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_receivable_payments_order ON receivable_payments (order_id);
+CREATE INDEX IF NOT EXISTS idx_receivable_payments_customer ON receivable_payments (customer_id);
@@ -0,0 +1,4 @@
+-- Change handed back to the customer when the cash tendered exceeds the grand total.
+-- payment_cash keeps the amount tendered; revenue is always grand_total.
+ALTER TABLE sales_orders
+    ADD COLUMN IF NOT EXISTS change_due NUMERIC(15, 2) NOT NULL DEFAULT 0;
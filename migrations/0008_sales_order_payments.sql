@@ -0,0 +1,27 @@
+-- Tender lines of a sales order. sales_orders.payment_cash / payment_non_cash stay as the
+-- totals of these lines (cash vs everything else).
+CREATE TABLE IF NOT EXISTS sales_order_payments (
+    id SERIAL PRIMARY KEY,
+    order_id INTEGER NOT NULL REFERENCES sales_orders(id) ON DELETE CASCADE,
+    -- cash, debit_card, credit_card, qris, bank_transfer, e_wallet, other
+    method VARCHAR(20) NOT NULL,
+    amount NUMERIC(15, 2) NOT NULL CHECK (amount > 0),
+    reference_number VARCHAR(100),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_order_payments_order ON sales_order_payments (order_id);
+
+-- Existing orders only know their cash / non-cash split
+INSERT INTO sales_order_payments (order_id, method, amount, created_at)
+SELECT id, 'cash', payment_cash, created_at FROM sales_orders
+WHERE payment_cash > 0
+AND NOT EXISTS (SELECT 1 FROM sales_order_payments p WHERE p.order_id = sales_orders.id);
+
+INSERT INTO sales_order_payments (order_id, method, amount, created_at)
+SELECT id, 'other', payment_non_cash, created_at FROM sales_orders
+WHERE payment_non_cash > 0
+AND NOT EXISTS (
+    SELECT 1 FROM sales_order_payments p
+    WHERE p.order_id = sales_orders.id AND p.method <> 'cash'
+);
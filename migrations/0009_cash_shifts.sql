@@ -0,0 +1,43 @@
+-- Cashier shifts: one open cash drawer session per user and store at a time.
+CREATE TABLE IF NOT EXISTS cash_shifts (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    -- open, closed
+    status VARCHAR(20) NOT NULL DEFAULT 'open',
+    opening_float NUMERIC(15, 2) NOT NULL DEFAULT 0,
+    opened_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    opening_note TEXT,
+    -- Filled in when the shift is closed
+    closing_count NUMERIC(15, 2),
+    expected_cash NUMERIC(15, 2),
+    variance NUMERIC(15, 2),
+    closed_at TIMESTAMP,
+    closed_by INTEGER REFERENCES users(id),
+    closing_note TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cash_shifts_one_open
+    ON cash_shifts (user_id, store_id) WHERE status = 'open';
+
+-- Cash put into or taken out of the drawer outside of sales (change top-ups, pickups, petty cash)
+CREATE TABLE IF NOT EXISTS cash_drawer_events (
+    id SERIAL PRIMARY KEY,
+    shift_id INTEGER NOT NULL REFERENCES cash_shifts(id),
+    -- cash_in, cash_out
+    event_type VARCHAR(20) NOT NULL,
+    amount NUMERIC(15, 2) NOT NULL CHECK (amount > 0),
+    reason TEXT,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_cash_drawer_events_shift ON cash_drawer_events (shift_id);
+
+ALTER TABLE sales_orders
+    ADD COLUMN IF NOT EXISTS shift_id INTEGER REFERENCES cash_shifts(id);
+
+CREATE INDEX IF NOT EXISTS idx_sales_orders_shift ON sales_orders (shift_id);
+
+ALTER TABLE company_settings
+    ADD COLUMN IF NOT EXISTS require_open_shift BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,14 @@
+-- End-of-day closing per store and business date. The headline figures are kept as
+-- columns for querying; the full report is stored as closed in figures.
+CREATE TABLE IF NOT EXISTS z_reports (
+    id SERIAL PRIMARY KEY,
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    business_date DATE NOT NULL,
+    closed_by INTEGER NOT NULL REFERENCES users(id),
+    closed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    gross_sales NUMERIC(15, 2) NOT NULL,
+    net_sales NUMERIC(15, 2) NOT NULL,
+    total_orders INTEGER NOT NULL,
+    figures JSONB NOT NULL,
+    UNIQUE (store_id, business_date)
+);
@@ -0,0 +1,9 @@
+-- Cost of goods snapshot per order line, taken from products.purchase_price at checkout.
+ALTER TABLE sales_order_details
+    ADD COLUMN IF NOT EXISTS unit_cost NUMERIC(15, 2) NOT NULL DEFAULT 0;
+
+-- Lines sold before the snapshot existed get the current purchase price as the best estimate
+UPDATE sales_order_details sod
+SET unit_cost = p.purchase_price
+FROM products p
+WHERE p.id = sod.product_id AND sod.unit_cost = 0;
@@ -0,0 +1,15 @@
+-- Server-allocated order numbers: one counter per store and business date.
+CREATE TABLE IF NOT EXISTS order_number_sequences (
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    business_date DATE NOT NULL,
+    last_seq INTEGER NOT NULL,
+    PRIMARY KEY (store_id, business_date)
+);
+
+-- Number the till sent with the order, kept for reference only
+ALTER TABLE sales_orders
+    ADD COLUMN IF NOT EXISTS external_reference VARCHAR(100);
+
+-- Historic client-supplied numbers that repeat within a store must be renamed before this runs
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sales_orders_store_order_number
+    ON sales_orders (store_id, order_number);
@@ -0,0 +1,13 @@
+-- Orders captured by tills while offline, keyed by the UUID the till generated.
+-- The row is claimed in the same transaction that creates the order, so a resent
+-- batch finds it and reports the original order instead of booking the sale twice.
+CREATE TABLE IF NOT EXISTS offline_orders (
+    client_uuid UUID PRIMARY KEY,
+    order_id INTEGER REFERENCES sales_orders(id),
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    client_created_at TIMESTAMP NOT NULL,
+    synced_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_offline_orders_order_id ON offline_orders (order_id);
@@ -0,0 +1,36 @@
+-- Cart headers. A cashier can keep several carts per store: one active cart that calls
+-- without a cart_id fall back to, plus any number of held (parked) carts.
+CREATE TABLE IF NOT EXISTS carts (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    store_id INTEGER NOT NULL REFERENCES stores(id),
+    name VARCHAR(100),
+    -- active, held
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    held_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_carts_one_active
+    ON carts (user_id, store_id) WHERE status = 'active';
+
+ALTER TABLE sales_cart ADD COLUMN IF NOT EXISTS cart_id INTEGER REFERENCES carts(id) ON DELETE CASCADE;
+
+-- Existing lines become the active cart of their user and store
+INSERT INTO carts (user_id, store_id, status, created_at, updated_at)
+SELECT user_id, store_id, 'active', MIN(created_at), MAX(updated_at)
+FROM sales_cart
+WHERE cart_id IS NULL
+GROUP BY user_id, store_id
+ON CONFLICT DO NOTHING;
+
+UPDATE sales_cart sc
+SET cart_id = c.id
+FROM carts c
+WHERE sc.cart_id IS NULL
+AND c.user_id = sc.user_id AND c.store_id = sc.store_id AND c.status = 'active';
+
+ALTER TABLE sales_cart ALTER COLUMN cart_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_sales_cart_cart_id ON sales_cart (cart_id);
@@ -0,0 +1,20 @@
+-- Per-user permissions, granted by an administrator directly in the database for now.
+-- 'price_override' lets a cashier sell a product at a price other than its catalog price.
+CREATE TABLE IF NOT EXISTS user_permissions (
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    permission VARCHAR(50) NOT NULL,
+    granted_by INTEGER REFERENCES users(id),
+    granted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, permission)
+);
+
+-- Cart and order lines keep the catalog price next to the price charged, and who overrode it
+ALTER TABLE sales_cart ADD COLUMN IF NOT EXISTS list_price NUMERIC(15, 2);
+ALTER TABLE sales_cart ADD COLUMN IF NOT EXISTS price_override_by INTEGER REFERENCES users(id);
+UPDATE sales_cart SET list_price = base_price WHERE list_price IS NULL;
+ALTER TABLE sales_cart ALTER COLUMN list_price SET NOT NULL;
+
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS list_price NUMERIC(15, 2);
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS price_override_by INTEGER REFERENCES users(id);
+UPDATE sales_order_details SET list_price = base_price WHERE list_price IS NULL;
+ALTER TABLE sales_order_details ALTER COLUMN list_price SET NOT NULL;
@@ -0,0 +1,8 @@
+-- Cart discounts can carry cents and fractional percentages
+ALTER TABLE sales_cart ALTER COLUMN discount_value TYPE NUMERIC(15, 2);
+
+-- Lines without a discount were stored as a zero fixed discount
+UPDATE sales_cart SET discount_type = 'none' WHERE discount_value = 0;
+
+-- Digits of the currency's minor unit that prices and discounts are rounded to
+ALTER TABLE company_settings ADD COLUMN IF NOT EXISTS currency_decimals INTEGER NOT NULL DEFAULT 2;
@@ -0,0 +1,14 @@
+-- Order-level discount and cash rounding. grand_total = subtotal - order_discount_amount + rounding_amount
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS subtotal NUMERIC(15, 2);
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS order_discount_type VARCHAR(20) NOT NULL DEFAULT 'none';
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS order_discount_value NUMERIC(15, 2) NOT NULL DEFAULT 0;
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS order_discount_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS rounding_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+UPDATE sales_orders SET subtotal = grand_total WHERE subtotal IS NULL;
+ALTER TABLE sales_orders ALTER COLUMN subtotal SET NOT NULL;
+
+-- Share of the order discount carried by each line; total_price is net of it
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS order_discount_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+
+-- Cash totals are rounded to a multiple of this amount, e.g. 100 for Rp100; 0 turns rounding off
+ALTER TABLE company_settings ADD COLUMN IF NOT EXISTS cash_rounding_unit NUMERIC(15, 2) NOT NULL DEFAULT 0;
@@ -0,0 +1,38 @@
+-- Automatic promotions. rule holds the rule type and its parameters, e.g.
+-- {"type": "buy_x_get_y", "product_id": 1, "buy_qty": 2, "get_qty": 1}
+CREATE TABLE IF NOT EXISTS promotions (
+    id SERIAL PRIMARY KEY,
+    company_id INTEGER NOT NULL REFERENCES companies(id),
+    name VARCHAR(255) NOT NULL,
+    rule JSONB NOT NULL,
+    -- NULL applies the promotion in every store of the company
+    store_ids INTEGER[],
+    starts_at TIMESTAMP,
+    ends_at TIMESTAMP,
+    -- Time of day window, may wrap past midnight
+    daily_start TIME,
+    daily_end TIME,
+    -- Higher priority promotions claim cart lines first
+    priority INTEGER NOT NULL DEFAULT 0,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_promotions_company ON promotions (company_id) WHERE deleted_at IS NULL;
+
+-- Promotion discount carried by each order line; total_price is net of it
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS promotion_discount_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+
+-- Promotions applied to each order line
+CREATE TABLE IF NOT EXISTS sales_order_promotions (
+    id SERIAL PRIMARY KEY,
+    order_id INTEGER NOT NULL REFERENCES sales_orders(id),
+    order_detail_id INTEGER NOT NULL REFERENCES sales_order_details(id),
+    promotion_id INTEGER NOT NULL REFERENCES promotions(id),
+    discount_amount NUMERIC(15, 2) NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_order_promotions_order ON sales_order_promotions (order_id);
+CREATE INDEX IF NOT EXISTS idx_sales_order_promotions_promotion ON sales_order_promotions (promotion_id);
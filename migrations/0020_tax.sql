@@ -0,0 +1,16 @@
+-- Company tax (PPN/VAT) configuration. tax_inclusive means catalog prices already contain the tax.
+ALTER TABLE company_settings ADD COLUMN IF NOT EXISTS tax_rate NUMERIC(5, 2) NOT NULL DEFAULT 0;
+ALTER TABLE company_settings ADD COLUMN IF NOT EXISTS tax_inclusive BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- 'standard' products are taxed at the company rate, 'exempt' products are not taxed
+ALTER TABLE products ADD COLUMN IF NOT EXISTS tax_category VARCHAR(20) NOT NULL DEFAULT 'standard';
+
+-- Tax per order line. With tax-inclusive prices total_price contains tax_amount, otherwise the
+-- tax comes on top of total_price.
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS tax_rate NUMERIC(5, 2) NOT NULL DEFAULT 0;
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS tax_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+
+-- grand_total = subtotal - order_discount_amount + rounding_amount, plus tax_amount when prices
+-- are tax-exclusive
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS tax_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS tax_inclusive BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,16 @@
+-- Store-level POS configuration. Stores without a row use the defaults below.
+-- The service charge is a percentage of the non-exempt lines, taken on their amount before
+-- tax or, with service_charge_after_tax, on their amount including tax.
+CREATE TABLE IF NOT EXISTS store_settings (
+    store_id INTEGER PRIMARY KEY REFERENCES stores(id),
+    service_charge_rate NUMERIC(5, 2) NOT NULL DEFAULT 0,
+    service_charge_after_tax BOOLEAN NOT NULL DEFAULT FALSE,
+    service_charge_exempt_category_ids INTEGER[] NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- grand_total also includes service_charge_amount, which is spread over the charged lines so
+-- returns can refund their share
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS service_charge_rate NUMERIC(5, 2) NOT NULL DEFAULT 0;
+ALTER TABLE sales_orders ADD COLUMN IF NOT EXISTS service_charge_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
+ALTER TABLE sales_order_details ADD COLUMN IF NOT EXISTS service_charge_amount NUMERIC(15, 2) NOT NULL DEFAULT 0;
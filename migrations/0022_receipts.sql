@@ -0,0 +1,5 @@
+-- Printed on receipt headers under the store name
+ALTER TABLE stores ADD COLUMN IF NOT EXISTS address TEXT;
+
+-- Free text printed at the bottom of the store's receipts, e.g. a thank-you or return policy
+ALTER TABLE store_settings ADD COLUMN IF NOT EXISTS receipt_footer TEXT;
@@ -0,0 +1,4 @@
+-- Largest manual discount, in percent of the discounted amount, a user without the
+-- price_override permission may give on a line or an order. 100 leaves discounts open to
+-- every cashier; lower it to require price_override above the limit.
+ALTER TABLE company_settings ADD COLUMN IF NOT EXISTS max_discount_percent NUMERIC(5, 2) NOT NULL DEFAULT 100;
@@ -8,16 +8,47 @@ use utoipa::{
 use crate::{
     models::{
         auth::TokenRequest,
+        cart::{Cart, CartListQuery, CartSummary, HoldCartRequest, NewCartRequest},
+        customer::{Customer, CustomerQueryParams, NewCustomer, UpdateCustomer},
+        offline_sync::{
+            OfflineOrder, OfflineOrderLine, OfflineOrderResult, OfflineSyncRequest, OfflineSyncResponse,
+        },
+        inventory::{
+            NewStockMovement, StockMovement, StockMovementQueryParams, StockQueryParams, StockShortage,
+            StoreStock,
+        },
+        receivable::{
+            AgingReport, AgingReportQuery, AgingRow, CustomerReceivables, NewReceivablePayment,
+            OrderReceivableResponse, ReceivableOrder, ReceivablePayment,
+        },
+        profit_report::{ProfitReport, ProfitReportQuery, ProfitRow, ProfitTotals},
+        tax_report::{TaxReport, TaxReportQuery, TaxReportRow, TaxReportTotals},
+        receipt::ReceiptQuery,
+        promotion::{
+            AppliedPromotion, BundleItem, NewPromotion, PriceTier, Promotion, PromotionQueryParams,
+            PromotionRule, UpdatePromotion,
+        },
         product::{ProductCategoryQueryParams, NewProduct, ProductQueryParams, ProductCategory, Product},
         response::ApiResponse,
         sales::{
             UpdateSalesCart, CreateOrderRequest, SalesReportQuery, SalesReport, SalesCart, SalesCartResponse,
-            NewSalesCart, SalesSummary, DetailedOrderResponse
+            NewSalesCart, SalesSummary, DetailedOrderResponse, VoidOrderRequest, OrderPaymentRequest,
+            SalesOrderPayment, PaymentMethodTotal
+        },
+        sales_return::{CreateReturnRequest, ReturnItemRequest, ReturnResponse, SalesReturn, SalesReturnDetail},
+        settings::{CompanySettings, StoreSettings, UpdateCompanySettings, UpdateStoreSettings},
+        shift::{
+            CashDrawerEvent, CashShift, CloseShiftRequest, CurrentShiftQuery, NewCashDrawerEvent,
+            OpenShiftRequest, ShiftCashSummary, ShiftResponse,
         },
         user::User,
+        z_report::{
+            CashierSubtotal, CloseZReportRequest, ZReport, ZReportClosing, ZReportDiscrepancy,
+            ZReportFigures, ZReportQuery,
+        },
     },
     handlers::sales::{
-        GetCartQuery, ClearCartQuery, GetSalesReportQuery
+        AddToCartQuery, GetCartQuery, ClearCartQuery, GetSalesReportQuery
     }
 };
 
@@ -32,6 +63,8 @@ use self::schemas::{StringResponse, UnitResponse};
         
         // User endpoints
         crate::handlers::user::get_user,
+        crate::handlers::user::grant_user_permission,
+        crate::handlers::user::revoke_user_permission,
         
         // Product endpoints
         crate::handlers::product::get_product_categories,
@@ -46,8 +79,60 @@ use self::schemas::{StringResponse, UnitResponse};
         crate::handlers::sales::update_cart_item,
         crate::handlers::sales::create_order,
         crate::handlers::sales::clear_cart,
+        crate::handlers::cart::create_cart,
+        crate::handlers::cart::list_carts,
+        crate::handlers::cart::hold_cart,
+        crate::handlers::cart::resume_cart,
+        crate::handlers::cart::discard_cart,
         crate::handlers::sales::get_sales_report,
         crate::handlers::sales::get_sales_order_by_id,
+        crate::handlers::sales::void_sales_order,
+        crate::handlers::offline_sync::sync_offline_orders,
+        crate::handlers::sales_return::create_sales_return,
+        crate::handlers::sales_return::get_order_returns,
+        crate::handlers::profit_report::get_profit_report,
+        crate::handlers::tax_report::get_tax_report,
+        crate::handlers::receipt::get_order_receipt,
+        crate::handlers::z_report::get_z_report,
+        crate::handlers::z_report::close_z_report,
+
+        // Inventory endpoints
+        crate::handlers::inventory::get_store_stock,
+        crate::handlers::inventory::get_product_stock,
+        crate::handlers::inventory::create_stock_movement,
+        crate::handlers::inventory::get_stock_movements,
+
+        // Customer endpoints
+        crate::handlers::customer::create_customer,
+        crate::handlers::customer::get_customers,
+        crate::handlers::customer::get_customer_by_id,
+        crate::handlers::customer::update_customer,
+        crate::handlers::customer::delete_customer,
+
+        // Promotion endpoints
+        crate::handlers::promotion::create_promotion,
+        crate::handlers::promotion::get_promotions,
+        crate::handlers::promotion::update_promotion,
+        crate::handlers::promotion::delete_promotion,
+
+        // Receivable endpoints
+        crate::handlers::receivable::create_receivable_payment,
+        crate::handlers::receivable::get_order_receivable,
+        crate::handlers::receivable::get_customer_receivables,
+        crate::handlers::receivable::get_aging_report,
+
+        // Shift endpoints
+        crate::handlers::shift::open_shift,
+        crate::handlers::shift::get_current_shift,
+        crate::handlers::shift::get_shift_by_id,
+        crate::handlers::shift::add_drawer_event,
+        crate::handlers::shift::close_shift,
+
+        // Settings endpoints
+        crate::handlers::settings::get_company_settings,
+        crate::handlers::settings::update_company_settings,
+        crate::handlers::settings::get_store_settings,
+        crate::handlers::settings::update_store_settings,
     ),
     components(
         schemas(
@@ -66,6 +151,7 @@ use self::schemas::{StringResponse, UnitResponse};
             Product,
             NewProduct,
             ProductQueryParams,
+            AddToCartQuery,
             GetCartQuery,
             ClearCartQuery,
             NewSalesCart,
@@ -74,7 +160,80 @@ use self::schemas::{StringResponse, UnitResponse};
             GetSalesReportQuery,
             SalesReport,
             SalesCart,
-            SalesCartResponse
+            SalesCartResponse,
+            Cart,
+            CartSummary,
+            NewCartRequest,
+            HoldCartRequest,
+            CartListQuery,
+            VoidOrderRequest,
+            OrderPaymentRequest,
+            SalesOrderPayment,
+            PaymentMethodTotal,
+            OfflineSyncRequest,
+            OfflineOrder,
+            OfflineOrderLine,
+            OfflineOrderResult,
+            OfflineSyncResponse,
+            CreateReturnRequest,
+            ReturnItemRequest,
+            ReturnResponse,
+            SalesReturn,
+            SalesReturnDetail,
+            StoreStock,
+            StockMovement,
+            NewStockMovement,
+            StockQueryParams,
+            StockMovementQueryParams,
+            StockShortage,
+            CompanySettings,
+            UpdateCompanySettings,
+            StoreSettings,
+            UpdateStoreSettings,
+            Customer,
+            NewCustomer,
+            UpdateCustomer,
+            CustomerQueryParams,
+            Promotion,
+            PromotionRule,
+            BundleItem,
+            PriceTier,
+            NewPromotion,
+            UpdatePromotion,
+            PromotionQueryParams,
+            AppliedPromotion,
+            ReceivablePayment,
+            NewReceivablePayment,
+            ReceivableOrder,
+            OrderReceivableResponse,
+            CustomerReceivables,
+            AgingReportQuery,
+            AgingRow,
+            AgingReport,
+            CashShift,
+            OpenShiftRequest,
+            CurrentShiftQuery,
+            CashDrawerEvent,
+            NewCashDrawerEvent,
+            CloseShiftRequest,
+            ShiftCashSummary,
+            ShiftResponse,
+            ZReportQuery,
+            CloseZReportRequest,
+            ZReport,
+            ZReportFigures,
+            ZReportClosing,
+            ZReportDiscrepancy,
+            CashierSubtotal,
+            ProfitReportQuery,
+            ProfitReport,
+            ProfitRow,
+            ProfitTotals,
+            TaxReportQuery,
+            TaxReport,
+            TaxReportRow,
+            TaxReportTotals,
+            ReceiptQuery
         )
     ),
     modifiers(&SecurityAddon),
@@ -83,6 +242,12 @@ use self::schemas::{StringResponse, UnitResponse};
         (name = "users", description = "User management endpoints"),
         (name = "products", description = "Product management endpoints"),
         (name = "sales", description = "Sales and cart management endpoints"),
+        (name = "inventory", description = "Stock levels and stock movement endpoints"),
+        (name = "customers", description = "Customer management endpoints"),
+        (name = "promotions", description = "Automatic promotion rule endpoints"),
+        (name = "receivables", description = "Customer debt settlement and aging endpoints"),
+        (name = "shifts", description = "Cashier shift and cash drawer endpoints"),
+        (name = "settings", description = "Company and store configuration endpoints"),
         (name = "system", description = "System administration endpoints"),
     )
 )]
@@ -1,3 +1,4 @@
+use crate::models::inventory::StockShortage;
 use actix_web::{error::ResponseError, http::StatusCode, HttpResponse};
 use serde::Serialize;
 use std::fmt;
@@ -13,6 +14,8 @@ pub enum ServiceError {
     DatabaseError(String),
     NotFound,
     ValidationError(String),
+    // Validation failure carrying the products that cannot be covered by available stock
+    InsufficientStock(Vec<StockShortage>),
 }
 
 #[derive(Serialize)]
@@ -33,6 +36,10 @@ impl fmt::Display for ServiceError {
             ServiceError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             ServiceError::NotFound => write!(f, "Resource not found"),
             ServiceError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            ServiceError::InsufficientStock(shortages) => {
+                let products: Vec<&str> = shortages.iter().map(|s| s.product_name.as_str()).collect();
+                write!(f, "Validation error: insufficient stock for {}", products.join(", "))
+            }
         }
     }
 }
@@ -96,6 +103,14 @@ impl ResponseError for ServiceError {
                     error_code: Some("validation_error".to_string()),
                 })
             }
+            ServiceError::InsufficientStock(shortages) => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "message": self.to_string(),
+                    "status": "error",
+                    "error_code": "insufficient_stock",
+                    "details": shortages,
+                }))
+            }
         }
     }
 
@@ -109,6 +124,7 @@ impl ResponseError for ServiceError {
             ServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ServiceError::NotFound => StatusCode::NOT_FOUND,
             ServiceError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ServiceError::InsufficientStock(_) => StatusCode::BAD_REQUEST,
         }
     }
 }
@@ -0,0 +1,278 @@
+use crate::errors::ServiceError;
+use crate::models::cart::{CartListQuery, HoldCartRequest, NewCartRequest};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::cart_service;
+use crate::services::db_service::DbConnectionManager;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/carts",
+    request_body(content = NewCartRequest, description = "Store and optional name of the new cart", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Cart created and made active; the previously active cart is held", body = ApiResponse<Cart>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn create_cart(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    cart_data: web::Json<NewCartRequest>,
+) -> HttpResponse {
+    info!("Processing create_cart request for store_id: {}", cart_data.store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match cart_service::create_cart(&db_manager, user.id, company_id, cart_data.into_inner()).await {
+        Ok(cart) => HttpResponse::Created().json(ApiResponse::success(cart)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(e) => {
+            error!("Failed to create cart: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to create cart: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sales/carts",
+    params(
+        CartListQuery
+    ),
+    responses(
+        (status = 200, description = "Carts retrieved successfully, active cart first", body = ApiResponse<Vec<CartSummary>>),
+        (status = 400, description = "Unknown status filter", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn list_carts(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<CartListQuery>,
+) -> HttpResponse {
+    info!("Processing list_carts request for store_id: {}", query.store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, _company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match cart_service::list_carts(&db_manager, user.id, query.into_inner()).await {
+        Ok(carts) => HttpResponse::Ok().json(ApiResponse::success(carts)),
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to list carts: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to list carts: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/carts/{id}/hold",
+    params(
+        ("id" = i32, Path, description = "Cart ID to hold")
+    ),
+    request_body(content = HoldCartRequest, description = "Optional label for the held cart", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Cart held successfully", body = ApiResponse<Cart>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn hold_cart(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    hold_data: web::Json<HoldCartRequest>,
+) -> HttpResponse {
+    let cart_id = path.into_inner();
+    info!("Processing hold_cart request for cart ID: {}", cart_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, _company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match cart_service::hold_cart(&db_manager, user.id, cart_id, hold_data.into_inner()).await {
+        Ok(cart) => HttpResponse::Ok().json(ApiResponse::success(cart)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found"))
+        },
+        Err(e) => {
+            error!("Failed to hold cart: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to hold cart: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/carts/{id}/resume",
+    params(
+        ("id" = i32, Path, description = "Cart ID to resume")
+    ),
+    responses(
+        (status = 200, description = "Cart is now the active cart; the previously active cart is held", body = ApiResponse<Cart>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn resume_cart(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let cart_id = path.into_inner();
+    info!("Processing resume_cart request for cart ID: {}", cart_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, _company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match cart_service::resume_cart(&db_manager, user.id, cart_id).await {
+        Ok(cart) => HttpResponse::Ok().json(ApiResponse::success(cart)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found"))
+        },
+        Err(e) => {
+            error!("Failed to resume cart: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to resume cart: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/sales/carts/{id}",
+    params(
+        ("id" = i32, Path, description = "Cart ID to discard")
+    ),
+    responses(
+        (status = 200, description = "Cart and its items discarded", body = ApiResponse<String>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn discard_cart(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let cart_id = path.into_inner();
+    info!("Processing discard_cart request for cart ID: {}", cart_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, _company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match cart_service::discard_cart(&db_manager, user.id, cart_id).await {
+        Ok(true) => HttpResponse::Ok().json(ApiResponse::success("Cart discarded successfully")),
+        Ok(false) => HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found")),
+        Err(e) => {
+            error!("Failed to discard cart: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to discard cart: {e}")))
+        }
+    }
+}
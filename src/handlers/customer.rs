@@ -0,0 +1,284 @@
+use crate::errors::ServiceError;
+use crate::models::customer::{CustomerQueryParams, NewCustomer, UpdateCustomer};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::customer_service;
+use crate::services::db_service::DbConnectionManager;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/customers",
+    request_body(content = NewCustomer, description = "Customer data to create", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Customer created successfully", body = ApiResponse<Customer>),
+        (status = 400, description = "Invalid customer data", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "customers"
+)]
+pub async fn create_customer(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    customer_data: web::Json<NewCustomer>,
+) -> HttpResponse {
+    info!("Processing create_customer request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match customer_service::create_customer(&db_manager, customer_data.into_inner(), company_id).await {
+        Ok(customer) => {
+            info!("Customer created successfully: ID {}", customer.id);
+            HttpResponse::Created().json(ApiResponse::success(customer))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to create customer: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to create customer: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/customers",
+    params(
+        CustomerQueryParams
+    ),
+    responses(
+        (status = 200, description = "Customers retrieved successfully", body = ApiResponse<Vec<Customer>>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "customers"
+)]
+pub async fn get_customers(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<CustomerQueryParams>,
+) -> HttpResponse {
+    info!("Processing get_customers request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match customer_service::get_customers(&db_manager, company_id, query.into_inner()).await {
+        Ok(customers) => {
+            info!("Successfully retrieved customers for company_id {}", company_id);
+            HttpResponse::Ok().json(ApiResponse::success(customers))
+        },
+        Err(e) => {
+            error!("Failed to retrieve customers: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve customers: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/customers/{id}",
+    params(
+        ("id" = i32, Path, description = "Customer ID to retrieve")
+    ),
+    responses(
+        (status = 200, description = "Customer retrieved successfully", body = ApiResponse<Customer>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Customer not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "customers"
+)]
+pub async fn get_customer_by_id(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let customer_id = path.into_inner();
+    info!("Processing get_customer_by_id request for customer_id: {}", customer_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match customer_service::get_customer_by_id(&db_manager, customer_id, company_id).await {
+        Ok(customer) => HttpResponse::Ok().json(ApiResponse::success(customer)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Customer not found"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve customer by ID: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve customer: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/customers/{id}",
+    params(
+        ("id" = i32, Path, description = "Customer ID to update")
+    ),
+    request_body(content = UpdateCustomer, description = "Customer fields to update", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Customer updated successfully", body = ApiResponse<Customer>),
+        (status = 400, description = "Invalid customer data", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Customer not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "customers"
+)]
+pub async fn update_customer(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    customer_update: web::Json<UpdateCustomer>,
+) -> HttpResponse {
+    let customer_id = path.into_inner();
+    info!("Processing update_customer request for customer_id: {}", customer_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match customer_service::update_customer(&db_manager, customer_id, company_id, customer_update.into_inner()).await {
+        Ok(customer) => HttpResponse::Ok().json(ApiResponse::success(customer)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Customer not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to update customer: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to update customer: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/customers/{id}",
+    params(
+        ("id" = i32, Path, description = "Customer ID to delete")
+    ),
+    responses(
+        (status = 200, description = "Customer deleted successfully", body = ApiResponse<String>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Customer not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "customers"
+)]
+pub async fn delete_customer(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let customer_id = path.into_inner();
+    info!("Processing delete_customer request for customer_id: {}", customer_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match customer_service::delete_customer(&db_manager, customer_id, company_id).await {
+        Ok(true) => HttpResponse::Ok().json(ApiResponse::success("Customer deleted successfully")),
+        Ok(false) => HttpResponse::NotFound().json(ApiResponse::<()>::error("Customer not found")),
+        Err(e) => {
+            error!("Failed to delete customer: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to delete customer: {e}")))
+        }
+    }
+}
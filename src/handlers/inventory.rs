@@ -0,0 +1,238 @@
+use crate::errors::ServiceError;
+use crate::models::inventory::{NewStockMovement, StockMovementQueryParams, StockQueryParams};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::inventory_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    get,
+    path = "/api/inventory/stock",
+    params(
+        StockQueryParams
+    ),
+    responses(
+        (status = 200, description = "Stock levels retrieved successfully", body = ApiResponse<Vec<StoreStock>>),
+        (status = 400, description = "Invalid page or size", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "inventory"
+)]
+pub async fn get_store_stock(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<StockQueryParams>,
+) -> HttpResponse {
+    info!("Processing get_store_stock request for store_id: {}", query.store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match inventory_service::get_store_stock(&db_manager, company_id, query.into_inner()).await {
+        Ok(stock) => {
+            info!("Retrieved {} stock rows", stock.items.len());
+            HttpResponse::Ok().json(ApiResponse::success(stock))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to retrieve store stock: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve store stock: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/inventory/stock/{product_id}",
+    params(
+        ("product_id" = i32, Path, description = "Product ID to get stock levels for")
+    ),
+    responses(
+        (status = 200, description = "Stock levels per store retrieved successfully", body = ApiResponse<Vec<StoreStock>>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Product not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "inventory"
+)]
+pub async fn get_product_stock(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(i32,)>, // Product ID from path
+) -> HttpResponse {
+    let product_id = path.0;
+    info!("Processing get_product_stock request for product ID: {}", product_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match inventory_service::get_product_stock(&db_manager, company_id, product_id).await {
+        Ok(stock) => {
+            info!("Retrieved stock in {} stores for product ID: {}", stock.len(), product_id);
+            HttpResponse::Ok().json(ApiResponse::success(stock))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Product not found"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve product stock: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve product stock: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/inventory/movements",
+    request_body(content = NewStockMovement, description = "Stock movement to record", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Stock movement recorded successfully", body = ApiResponse<Vec<StockMovement>>),
+        (status = 400, description = "Invalid stock movement", body = ApiResponse<()>),
+        (status = 400, description = "Invalid page or size", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store or product not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "inventory"
+)]
+pub async fn create_stock_movement(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    movement_data: web::Json<NewStockMovement>,
+) -> HttpResponse {
+    info!("Processing create_stock_movement request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match inventory_service::create_stock_movement(&db_manager, user.id, company_id, movement_data.into_inner()).await {
+        Ok(movements) => {
+            info!("Recorded {} stock movement(s)", movements.len());
+            HttpResponse::Created().json(ApiResponse::success(movements))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store or product not found"))
+        },
+        Err(e) => {
+            error!("Failed to record stock movement: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to record stock movement: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/inventory/movements",
+    params(
+        StockMovementQueryParams
+    ),
+    responses(
+        (status = 200, description = "Stock movements retrieved successfully", body = ApiResponse<Vec<StockMovement>>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "inventory"
+)]
+pub async fn get_stock_movements(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<StockMovementQueryParams>,
+) -> HttpResponse {
+    info!("Processing get_stock_movements request for store_id: {}", query.store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match inventory_service::get_stock_movements(&db_manager, company_id, query.into_inner()).await {
+        Ok(movements) => {
+            info!("Retrieved {} stock movements", movements.items.len());
+            HttpResponse::Ok().json(ApiResponse::success(movements))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to retrieve stock movements: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve stock movements: {e}")))
+        }
+    }
+}
@@ -1,5 +1,18 @@
 pub mod auth;
+pub mod cart;
+pub mod customer;
+pub mod promotion;
 pub mod user;
 pub mod product;
 pub mod debug;
 pub mod sales;
+pub mod sales_return;
+pub mod inventory;
+pub mod settings;
+pub mod receipt;
+pub mod receivable;
+pub mod shift;
+pub mod z_report;
+pub mod profit_report;
+pub mod tax_report;
+pub mod offline_sync;
@@ -0,0 +1,67 @@
+use crate::errors::ServiceError;
+use crate::models::offline_sync::OfflineSyncRequest;
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::offline_sync_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/sync",
+    request_body(content = OfflineSyncRequest, description = "Orders captured by a till while offline", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Batch processed; each order is reported as accepted, duplicate, rejected or retry", body = ApiResponse<OfflineSyncResponse>),
+        (status = 400, description = "Empty or oversized batch", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn sync_offline_orders(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    sync_data: web::Json<OfflineSyncRequest>,
+) -> HttpResponse {
+    info!(
+        "Processing sync_offline_orders request for store_id: {} with {} orders",
+        sync_data.store_id,
+        sync_data.orders.len()
+    );
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match offline_sync_service::sync_offline_orders(&db_manager, user.id, company_id, sync_data.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(ApiResponse::success(response)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to sync offline orders: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to sync offline orders: {e}")))
+        }
+    }
+}
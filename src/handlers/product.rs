@@ -140,6 +140,9 @@ pub async fn create_product(
                 data: Some(product),
             })
         }
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        }
         Err(e) => {
             error!("Failed to create product: {:?}", e);
             HttpResponse::InternalServerError().json(ApiResponse {
@@ -0,0 +1,61 @@
+use crate::errors::ServiceError;
+use crate::models::profit_report::ProfitReportQuery;
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::profit_report_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    get,
+    path = "/api/sales/profit-report",
+    params(
+        ProfitReportQuery
+    ),
+    responses(
+        (status = 200, description = "Profit report generated successfully", body = ApiResponse<ProfitReport>),
+        (status = 400, description = "Invalid date range or grouping", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn get_profit_report(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<ProfitReportQuery>,
+) -> HttpResponse {
+    info!("Processing get_profit_report request from {} to {}", query.start_date, query.end_date);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match profit_report_service::generate_profit_report(&db_manager, company_id, query.into_inner()).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::success(report)),
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to generate profit report: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to generate profit report: {e}")))
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::errors::ServiceError;
+use crate::models::promotion::{NewPromotion, PromotionQueryParams, UpdatePromotion};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::promotion_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/promotions",
+    request_body(content = NewPromotion, description = "Promotion rule, stores and time windows", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Promotion created successfully", body = ApiResponse<Promotion>),
+        (status = 400, description = "Invalid promotion data", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "promotions"
+)]
+pub async fn create_promotion(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    promotion_data: web::Json<NewPromotion>,
+) -> HttpResponse {
+    info!("Processing create_promotion request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match promotion_service::create_promotion(&db_manager, company_id, promotion_data.into_inner()).await {
+        Ok(promotion) => HttpResponse::Created().json(ApiResponse::success(promotion)),
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to create promotion: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to create promotion: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/promotions",
+    params(
+        PromotionQueryParams
+    ),
+    responses(
+        (status = 200, description = "Promotions retrieved successfully, highest priority first", body = ApiResponse<Vec<Promotion>>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "promotions"
+)]
+pub async fn get_promotions(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<PromotionQueryParams>,
+) -> HttpResponse {
+    info!("Processing get_promotions request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match promotion_service::get_promotions(&db_manager, company_id, query.into_inner()).await {
+        Ok(promotions) => HttpResponse::Ok().json(ApiResponse::success(promotions)),
+        Err(e) => {
+            error!("Failed to retrieve promotions: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve promotions: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/promotions/{id}",
+    params(
+        ("id" = i32, Path, description = "Promotion ID to update")
+    ),
+    request_body(content = UpdatePromotion, description = "New definition of the promotion", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Promotion updated successfully", body = ApiResponse<Promotion>),
+        (status = 400, description = "Invalid promotion data", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Promotion not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "promotions"
+)]
+pub async fn update_promotion(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    promotion_update: web::Json<UpdatePromotion>,
+) -> HttpResponse {
+    let promotion_id = path.into_inner();
+    info!("Processing update_promotion request for promotion_id: {}", promotion_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match promotion_service::update_promotion(&db_manager, promotion_id, company_id, promotion_update.into_inner()).await {
+        Ok(promotion) => HttpResponse::Ok().json(ApiResponse::success(promotion)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Promotion not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to update promotion: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to update promotion: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/promotions/{id}",
+    params(
+        ("id" = i32, Path, description = "Promotion ID to delete")
+    ),
+    responses(
+        (status = 200, description = "Promotion deleted successfully", body = ApiResponse<String>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Promotion not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "promotions"
+)]
+pub async fn delete_promotion(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let promotion_id = path.into_inner();
+    info!("Processing delete_promotion request for promotion_id: {}", promotion_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match promotion_service::delete_promotion(&db_manager, promotion_id, company_id).await {
+        Ok(true) => HttpResponse::Ok().json(ApiResponse::success("Promotion deleted successfully")),
+        Ok(false) => HttpResponse::NotFound().json(ApiResponse::<()>::error("Promotion not found")),
+        Err(e) => {
+            error!("Failed to delete promotion: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to delete promotion: {e}")))
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::errors::ServiceError;
+use crate::models::receipt::ReceiptQuery;
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::receipt_service::{self, RenderedReceipt};
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    get,
+    path = "/api/sales/orders/{id}/receipt",
+    params(
+        ("id" = i32, Path, description = "Sales order ID to print"),
+        ReceiptQuery
+    ),
+    responses(
+        (status = 200, description = "Receipt rendered for the paper width", content(
+            ("text/plain" = String),
+            ("application/octet-stream" = Vec<u8>),
+            ("application/pdf" = Vec<u8>)
+        )),
+        (status = 400, description = "Unknown width or format", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Order not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn get_order_receipt(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    query: web::Query<ReceiptQuery>,
+) -> HttpResponse {
+    let order_id = path.into_inner();
+    info!("Processing get_order_receipt request for order ID: {}", order_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match receipt_service::render_receipt(&db_manager, order_id, company_id, query.into_inner()).await {
+        Ok(RenderedReceipt::Text(text)) => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(text),
+        Ok(RenderedReceipt::EscPos(bytes)) => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(bytes),
+        Ok(RenderedReceipt::Pdf(bytes)) => HttpResponse::Ok()
+            .content_type("application/pdf")
+            .body(bytes),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Sales order not found or not accessible by this user"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to render receipt: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to render receipt: {e}")))
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use crate::errors::ServiceError;
+use crate::models::receivable::{AgingReportQuery, NewReceivablePayment};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::receivable_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/receivables/orders/{id}/payments",
+    params(
+        ("id" = i32, Path, description = "Sales order ID to settle")
+    ),
+    request_body(content = NewReceivablePayment, description = "Payment applied to the order's outstanding balance", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Payment recorded successfully", body = ApiResponse<ReceivablePayment>),
+        (status = 400, description = "Invalid payment or payment exceeds the outstanding balance", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Sales order not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "receivables"
+)]
+pub async fn create_receivable_payment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    payment_data: web::Json<NewReceivablePayment>,
+) -> HttpResponse {
+    let order_id = path.into_inner();
+    info!("Processing create_receivable_payment request for order_id: {}", order_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match receivable_service::create_receivable_payment(&db_manager, order_id, user.id, company_id, payment_data.into_inner()).await {
+        Ok(payment) => HttpResponse::Created().json(ApiResponse::success(payment)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Sales order not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to record receivable payment: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to record payment: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/receivables/orders/{id}",
+    params(
+        ("id" = i32, Path, description = "Sales order ID")
+    ),
+    responses(
+        (status = 200, description = "Order receivable retrieved successfully", body = ApiResponse<OrderReceivableResponse>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Sales order not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "receivables"
+)]
+pub async fn get_order_receivable(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let order_id = path.into_inner();
+    info!("Processing get_order_receivable request for order_id: {}", order_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match receivable_service::get_order_receivable(&db_manager, order_id, company_id).await {
+        Ok(receivable) => HttpResponse::Ok().json(ApiResponse::success(receivable)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Sales order not found"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve order receivable: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve order receivable: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/receivables/customers/{id}",
+    params(
+        ("id" = i32, Path, description = "Customer ID")
+    ),
+    responses(
+        (status = 200, description = "Customer receivables retrieved successfully", body = ApiResponse<CustomerReceivables>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Customer not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "receivables"
+)]
+pub async fn get_customer_receivables(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let customer_id = path.into_inner();
+    info!("Processing get_customer_receivables request for customer_id: {}", customer_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match receivable_service::get_customer_receivables(&db_manager, customer_id, company_id).await {
+        Ok(receivables) => HttpResponse::Ok().json(ApiResponse::success(receivables)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Customer not found"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve customer receivables: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve customer receivables: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/receivables/aging",
+    params(
+        AgingReportQuery
+    ),
+    responses(
+        (status = 200, description = "Aging report generated successfully", body = ApiResponse<AgingReport>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "receivables"
+)]
+pub async fn get_aging_report(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<AgingReportQuery>,
+) -> HttpResponse {
+    info!("Processing get_aging_report request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match receivable_service::generate_aging_report(&db_manager, company_id, query.into_inner()).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::success(report)),
+        Err(e) => {
+            error!("Failed to generate aging report: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to generate aging report: {e}")))
+        }
+    }
+}
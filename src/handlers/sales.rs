@@ -1,16 +1,26 @@
 use crate::models::{AppState, response::ApiResponse};
-use crate::models::sales::{SalesCartResponse, NewSalesCart, UpdateSalesCart, CreateOrderRequest, SalesReport, DetailedOrderResponse, SalesReportQuery};
+use crate::models::sales::{SalesCartResponse, NewSalesCart, UpdateSalesCart, CreateOrderRequest, SalesReport, DetailedOrderResponse, SalesReportQuery, VoidOrderRequest, REPORT_FORMAT_CSV, REPORT_FORMAT_JSON, REPORT_FORMAT_PDF, REPORT_FORMAT_XLSX};
 use crate::services::db_service::DbConnectionManager;
-use crate::services::sales_service;
-use actix_web::{web, HttpResponse, HttpRequest};
+use crate::services::{sales_report_export_service, sales_service};
+use crate::errors::ServiceError;
+use actix_web::{web, HttpResponse, HttpRequest, ResponseError};
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AddToCartQuery {
+    /// Add as a new line even if the cart already has a line for the same product, price and discount
+    pub separate_line: Option<bool>,
+}
+
 #[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
 #[into_params(parameter_in = Query)]
 pub struct GetCartQuery {
     /// Store ID to get cart items for
     pub store_id: i32,
+    /// Cart ID (defaults to the active cart)
+    pub cart_id: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
@@ -18,6 +28,8 @@ pub struct GetCartQuery {
 pub struct ClearCartQuery {
     /// Store ID to clear the cart for
     pub store_id: i32,
+    /// Cart ID (defaults to the active cart)
+    pub cart_id: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams)]
@@ -29,16 +41,24 @@ pub struct GetSalesReportQuery {
     pub end_date: chrono::NaiveDate,
     /// Store ID (0 for all stores)
     pub store_id: i32,
+    /// `json`, `pdf`, `csv` (zip of CSV files) or `xlsx` (defaults to `json`)
+    pub format: Option<String>,
 }
 use log::{error, info};
 
 #[utoipa::path(
     post,
     path = "/api/sales/cart",
+    params(
+        AddToCartQuery
+    ),
     request_body(content = NewSalesCart, description = "Item to add to cart", content_type = "application/json"),
     responses(
-        (status = 201, description = "Item added to cart successfully", body = ApiResponse<SalesCart>),
+        (status = 201, description = "Item added to cart, merged into a matching line unless separate_line is set", body = ApiResponse<SalesCartResponse>),
+        (status = 400, description = "Invalid quantity, unknown product or insufficient stock", body = ApiResponse<()>),
         (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "Price differs from the catalog or the discount is above the company limit, and the user lacks the price_override permission", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
         (status = 500, description = "Internal server error", body = ApiResponse<()>)
     ),
     security(
@@ -49,6 +69,7 @@ use log::{error, info};
 pub async fn add_to_cart(
     req: HttpRequest,
     data: web::Data<AppState>,
+    query: web::Query<AddToCartQuery>,
     cart_data: web::Json<NewSalesCart>,
 ) -> HttpResponse {
     info!("Processing add_to_cart request");
@@ -60,7 +81,7 @@ pub async fn add_to_cart(
     let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
     
     // Handle authentication result
-    let (user, _company_id) = match auth_result {
+    let (user, company_id) = match auth_result {
         Ok((user, company_id)) => {
             info!("User authenticated: {} (company_id: {})", user.email, company_id);
             (user, company_id)
@@ -72,11 +93,30 @@ pub async fn add_to_cart(
     };
     
     // Process the request with the authenticated user's ID
-    match sales_service::add_to_cart(&db_manager, cart_data.into_inner(), user.id).await {
+    match sales_service::add_to_cart(
+        &db_manager,
+        cart_data.into_inner(),
+        user.id,
+        company_id,
+        query.separate_line.unwrap_or(false),
+    ).await {
         Ok(cart_item) => {
             info!("Item added to cart successfully with ID: {}", cart_item.id);
             HttpResponse::Created().json(ApiResponse::success(cart_item))
         },
+        Err(e @ ServiceError::InsufficientStock(_)) => {
+            info!("Cannot add item to cart: {}", e);
+            e.error_response()
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Price overrides and discounts above the company limit require the price_override permission"))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
         Err(e) => {
             error!("Failed to add item to cart: {:?}", e);
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to add item to cart: {}", e)))
@@ -153,6 +193,7 @@ pub async fn delete_from_cart(
     responses(
         (status = 200, description = "Cart items retrieved successfully", body = ApiResponse<Vec<SalesCartResponse>>),
         (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
         (status = 500, description = "Internal server error", body = ApiResponse<()>)
     ),
     security(
@@ -174,7 +215,7 @@ pub async fn get_cart_items(
     let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
     
     // Handle authentication result
-    let (user, _company_id) = match auth_result {
+    let (user, company_id) = match auth_result {
         Ok((user, company_id)) => {
             info!("User authenticated: {} (company_id: {})", user.email, company_id);
             (user, company_id)
@@ -186,11 +227,14 @@ pub async fn get_cart_items(
     };
     
     // Process the request with the authenticated user's ID
-    match sales_service::get_cart_items(&db_manager, user.id, query.store_id).await {
+    match sales_service::get_cart_items(&db_manager, user.id, company_id, query.store_id, query.cart_id).await {
         Ok(cart_items) => {
             info!("Retrieved {} cart items for user ID: {}", cart_items.len(), user.id);
             HttpResponse::Ok().json(ApiResponse::success(cart_items))
         },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found"))
+        },
         Err(e) => {
             error!("Failed to retrieve cart items: {:?}", e);
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve cart items: {}", e)))
@@ -207,7 +251,9 @@ pub async fn get_cart_items(
     request_body(content = UpdateSalesCart, description = "Cart item updates", content_type = "application/json"),
     responses(
         (status = 200, description = "Item updated successfully", body = ApiResponse<SalesCart>),
+        (status = 400, description = "Invalid quantity, unknown product or insufficient stock", body = ApiResponse<()>),
         (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "Price differs from the catalog or the discount is above the company limit, and the user lacks the price_override permission", body = ApiResponse<()>),
         (status = 404, description = "Item not found or not owned by user", body = ApiResponse<()>),
         (status = 500, description = "Internal server error", body = ApiResponse<()>)
     ),
@@ -231,7 +277,7 @@ pub async fn update_cart_item(
     let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
     
     // Handle authentication result
-    let (user, _company_id) = match auth_result {
+    let (user, company_id) = match auth_result {
         Ok((user, company_id)) => {
             info!("User authenticated: {} (company_id: {})", user.email, company_id);
             (user, company_id)
@@ -243,11 +289,24 @@ pub async fn update_cart_item(
     };
     
     // Process the update request with the authenticated user's ID
-    match sales_service::update_cart_item(&db_manager, path.0, user.id, cart_update.into_inner()).await {
+    match sales_service::update_cart_item(&db_manager, path.0, user.id, company_id, cart_update.into_inner()).await {
         Ok(updated_item) => {
             info!("Item updated successfully with ID: {}", path.0);
             HttpResponse::Ok().json(ApiResponse::success(updated_item))
         },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Item not found or not owned by this user"))
+        },
+        Err(e @ ServiceError::InsufficientStock(_)) => {
+            info!("Cannot update cart item: {}", e);
+            e.error_response()
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Price overrides and discounts above the company limit require the price_override permission"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
         Err(e) => {
             error!("Failed to update cart item: {:?}", e);
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to update cart item: {}", e)))
@@ -258,10 +317,16 @@ pub async fn update_cart_item(
 #[utoipa::path(
     post,
     path = "/api/sales/orders",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-generated key; retries with the same key return the original order")
+    ),
     request_body(content = CreateOrderRequest, description = "Order details to create", content_type = "application/json"),
     responses(
-        (status = 201, description = "Order created successfully", body = ApiResponse<i32>),
+        (status = 201, description = "Order created successfully, or the original order when replayed", body = ApiResponse<i32>),
+        (status = 400, description = "Empty cart or insufficient stock", body = ApiResponse<()>),
         (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "Order discount is above the company limit and the user lacks the price_override permission", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
         (status = 500, description = "Internal server error", body = ApiResponse<()>)
     ),
     security(
@@ -283,7 +348,7 @@ pub async fn create_order(
     let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
     
     // Handle authentication result
-    let (user, _company_id) = match auth_result {
+    let (user, company_id) = match auth_result {
         Ok((user, company_id)) => {
             info!("User authenticated: {} (company_id: {})", user.email, company_id);
             (user, company_id)
@@ -294,12 +359,30 @@ pub async fn create_order(
         }
     };
     
+    let idempotency_key = req
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|value| value.to_str().ok());
+
     // Process the request with the authenticated user's ID
-    match sales_service::create_sales_order(&db_manager, user.id, order_request.into_inner()).await {
+    match sales_service::create_sales_order(&db_manager, user.id, company_id, order_request.into_inner(), idempotency_key).await {
         Ok(response) => {
             info!("Order created successfully with ID: {}", response.order.id);
             HttpResponse::Created().json(ApiResponse::success(response))
         },
+        Err(e @ ServiceError::InsufficientStock(_)) => {
+            info!("Cannot create order: {}", e);
+            e.error_response()
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Order discounts above the company limit require the price_override permission"))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
         Err(e) => {
             error!("Failed to create order: {:?}", e);
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to create order: {}", e)))
@@ -318,6 +401,7 @@ pub async fn create_order(
     responses(
         (status = 200, description = "Cart cleared successfully", body = ApiResponse<String>),
         (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Cart not found", body = ApiResponse<()>),
         (status = 500, description = "Internal server error", body = ApiResponse<()>)
     ),
     security(
@@ -351,7 +435,7 @@ pub async fn clear_cart(
     };
     
     // Process the request with the authenticated user's ID and store_id
-    match sales_service::clear_cart(&db_manager, user.id, query.store_id).await {
+    match sales_service::clear_cart(&db_manager, user.id, query.store_id, query.cart_id).await {
         Ok(cleared) => {
             if cleared {
                 info!("Cart cleared successfully for user ID: {} in store ID: {}", user.id, query.store_id);
@@ -361,6 +445,9 @@ pub async fn clear_cart(
                 HttpResponse::Ok().json(ApiResponse::success("No items to clear"))
             }
         },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Cart not found"))
+        },
         Err(e) => {
             error!("Failed to clear cart: {:?}", e);
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to clear cart: {}", e)))
@@ -377,7 +464,13 @@ pub async fn clear_cart(
         GetSalesReportQuery
     ),
     responses(
-        (status = 200, description = "Sales report generated successfully", body = ApiResponse<SalesReport>),
+        (status = 200, description = "Sales report generated successfully", content(
+            ("application/json" = ApiResponse<SalesReport>),
+            ("application/pdf" = Vec<u8>),
+            ("application/zip" = Vec<u8>),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = Vec<u8>)
+        )),
+        (status = 400, description = "Unknown format", body = ApiResponse<()>),
         (status = 401, description = "Authentication required", body = ApiResponse<()>),
         (status = 500, description = "Internal server error", body = ApiResponse<()>)
     ),
@@ -412,6 +505,11 @@ pub async fn get_sales_report(
         }
     };
     
+    let format = query.format.clone().unwrap_or_else(|| REPORT_FORMAT_JSON.to_string());
+    if ![REPORT_FORMAT_JSON, REPORT_FORMAT_PDF, REPORT_FORMAT_CSV, REPORT_FORMAT_XLSX].contains(&format.as_str()) {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(&format!("Unknown format '{format}', expected json, pdf, csv or xlsx")));
+    }
+
     // Convert query to service model
     let report_query = crate::models::sales::SalesReportQuery {
         start_date: query.start_date,
@@ -420,14 +518,31 @@ pub async fn get_sales_report(
     };
     
     // Process the request with the authenticated user's ID
-    match sales_service::generate_sales_report(&db_manager, user.id, company_id, report_query).await {
+    let report = match sales_service::generate_sales_report(&db_manager, user.id, company_id, report_query.clone()).await {
         Ok(report) => {
             info!("Generated sales report with {} orders", report.orders.len());
-            HttpResponse::Ok().json(ApiResponse::success(report))
+            report
         },
         Err(e) => {
             error!("Failed to generate sales report: {:?}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to generate sales report: {}", e)))
+            return HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to generate sales report: {e}")));
+        }
+    };
+    if format == REPORT_FORMAT_JSON {
+        return HttpResponse::Ok().json(ApiResponse::success(report));
+    }
+
+    match sales_report_export_service::export_sales_report(&db_manager, company_id, &report_query, &report, &format).await {
+        Ok(exported) => HttpResponse::Ok()
+            .content_type(exported.content_type)
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", exported.file_name)))
+            .body(exported.bytes),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(e) => {
+            error!("Failed to export sales report: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to export sales report: {e}")))
         }
     }
 }
@@ -495,3 +610,67 @@ pub async fn get_sales_order_by_id(
         }
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/orders/{id}/void",
+    params(
+        ("id" = i32, Path, description = "Sales order ID to void")
+    ),
+    request_body(content = VoidOrderRequest, description = "Reason for voiding the order", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Sales order voided successfully", body = ApiResponse<SalesOrder>),
+        (status = 400, description = "Missing reason, order already voided or order has returns or receivable payments", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Order not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn void_sales_order(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(i32,)>, // Sales order ID from path
+    void_request: web::Json<VoidOrderRequest>,
+) -> HttpResponse {
+    let order_id = path.0;
+    info!("Processing void_sales_order request for order ID: {}", order_id);
+    
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+    
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+    
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match sales_service::void_sales_order(&db_manager, order_id, user.id, company_id, void_request.into_inner()).await {
+        Ok(order) => {
+            info!("Sales order ID {} voided successfully", order.id);
+            HttpResponse::Ok().json(ApiResponse::success(order))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Sales order not found or not accessible by this user"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to void sales order: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to void sales order: {e}")))
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::errors::ServiceError;
+use crate::models::sales_return::CreateReturnRequest;
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::sales_return_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/orders/{id}/returns",
+    params(
+        ("id" = i32, Path, description = "Sales order ID the items are returned from")
+    ),
+    request_body(content = CreateReturnRequest, description = "Lines to return and refund split", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Sales return created successfully", body = ApiResponse<ReturnResponse>),
+        (status = 400, description = "Invalid return request", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Order not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn create_sales_return(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(i32,)>, // Sales order ID from path
+    return_request: web::Json<CreateReturnRequest>,
+) -> HttpResponse {
+    let order_id = path.0;
+    info!("Processing create_sales_return request for order ID: {}", order_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match sales_return_service::create_sales_return(&db_manager, order_id, user.id, company_id, return_request.into_inner()).await {
+        Ok(response) => {
+            info!("Sales return created successfully with ID: {}", response.sales_return.id);
+            HttpResponse::Created().json(ApiResponse::success(response))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Sales order not found or not accessible by this user"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to create sales return: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to create sales return: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sales/orders/{id}/returns",
+    params(
+        ("id" = i32, Path, description = "Sales order ID to list returns for")
+    ),
+    responses(
+        (status = 200, description = "Sales returns retrieved successfully", body = ApiResponse<Vec<ReturnResponse>>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Order not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn get_order_returns(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(i32,)>, // Sales order ID from path
+) -> HttpResponse {
+    let order_id = path.0;
+    info!("Processing get_order_returns request for order ID: {}", order_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match sales_return_service::get_order_returns(&db_manager, order_id, company_id).await {
+        Ok(returns) => HttpResponse::Ok().json(ApiResponse::success(returns)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Sales order not found or not accessible by this user"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve sales returns: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve sales returns: {e}")))
+        }
+    }
+}
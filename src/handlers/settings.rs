@@ -0,0 +1,235 @@
+use crate::errors::ServiceError;
+use crate::models::settings::{UpdateCompanySettings, UpdateStoreSettings};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::settings_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    get,
+    path = "/api/settings/company",
+    responses(
+        (status = 200, description = "Company settings retrieved successfully", body = ApiResponse<CompanySettings>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "settings"
+)]
+pub async fn get_company_settings(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+) -> HttpResponse {
+    info!("Processing get_company_settings request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match settings_service::get_company_settings(&db_manager, company_id).await {
+        Ok(settings) => HttpResponse::Ok().json(ApiResponse::success(settings)),
+        Err(e) => {
+            error!("Failed to retrieve company settings: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve company settings: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/settings/company",
+    request_body(content = UpdateCompanySettings, description = "Settings to change", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Company settings updated successfully", body = ApiResponse<CompanySettings>),
+        (status = 400, description = "Invalid settings", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "The user may not change settings", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "settings"
+)]
+pub async fn update_company_settings(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    settings_update: web::Json<UpdateCompanySettings>,
+) -> HttpResponse {
+    info!("Processing update_company_settings request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match settings_service::update_company_settings(&db_manager, user.id, company_id, settings_update.into_inner()).await {
+        Ok(settings) => {
+            info!("Company settings updated for company_id: {}", company_id);
+            HttpResponse::Ok().json(ApiResponse::success(settings))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Changing settings requires the manage_settings permission"))
+        },
+        Err(e) => {
+            error!("Failed to update company settings: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to update company settings: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/settings/stores/{id}",
+    params(
+        ("id" = i32, Path, description = "Store ID")
+    ),
+    responses(
+        (status = 200, description = "Store settings retrieved successfully", body = ApiResponse<StoreSettings>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "settings"
+)]
+pub async fn get_store_settings(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let store_id = path.into_inner();
+    info!("Processing get_store_settings request for store_id: {}", store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match settings_service::get_store_settings(&db_manager, company_id, store_id).await {
+        Ok(settings) => HttpResponse::Ok().json(ApiResponse::success(settings)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve store settings: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve store settings: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/settings/stores/{id}",
+    params(
+        ("id" = i32, Path, description = "Store ID")
+    ),
+    request_body(content = UpdateStoreSettings, description = "Settings to change", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Store settings updated successfully", body = ApiResponse<StoreSettings>),
+        (status = 400, description = "Invalid settings", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "The user may not change settings", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "settings"
+)]
+pub async fn update_store_settings(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    settings_update: web::Json<UpdateStoreSettings>,
+) -> HttpResponse {
+    let store_id = path.into_inner();
+    info!("Processing update_store_settings request for store_id: {}", store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match settings_service::update_store_settings(&db_manager, user.id, company_id, store_id, settings_update.into_inner()).await {
+        Ok(settings) => {
+            info!("Store settings updated for store_id: {}", store_id);
+            HttpResponse::Ok().json(ApiResponse::success(settings))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Changing settings requires the manage_settings permission"))
+        },
+        Err(e) => {
+            error!("Failed to update store settings: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to update store settings: {e}")))
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::errors::ServiceError;
+use crate::models::shift::{CloseShiftRequest, CurrentShiftQuery, NewCashDrawerEvent, OpenShiftRequest};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::shift_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    post,
+    path = "/api/shifts",
+    request_body(content = OpenShiftRequest, description = "Store and opening float of the new shift", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Shift opened successfully", body = ApiResponse<CashShift>),
+        (status = 400, description = "Invalid request or a shift is already open", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "shifts"
+)]
+pub async fn open_shift(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    shift_data: web::Json<OpenShiftRequest>,
+) -> HttpResponse {
+    info!("Processing open_shift request");
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match shift_service::open_shift(&db_manager, user.id, company_id, shift_data.into_inner()).await {
+        Ok(shift) => HttpResponse::Created().json(ApiResponse::success(shift)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to open shift: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to open shift: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/shifts/current",
+    params(
+        CurrentShiftQuery
+    ),
+    responses(
+        (status = 200, description = "Open shift retrieved successfully", body = ApiResponse<ShiftResponse>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "No open shift in this store", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "shifts"
+)]
+pub async fn get_current_shift(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<CurrentShiftQuery>,
+) -> HttpResponse {
+    info!("Processing get_current_shift request for store_id: {}", query.store_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match shift_service::get_current_shift(&db_manager, user.id, company_id, query.store_id).await {
+        Ok(shift) => HttpResponse::Ok().json(ApiResponse::success(shift)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("No open shift in this store"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve current shift: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve current shift: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/shifts/{id}",
+    params(
+        ("id" = i32, Path, description = "Shift ID to retrieve")
+    ),
+    responses(
+        (status = 200, description = "Shift retrieved successfully", body = ApiResponse<ShiftResponse>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Shift not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "shifts"
+)]
+pub async fn get_shift_by_id(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let shift_id = path.into_inner();
+    info!("Processing get_shift_by_id request for shift_id: {}", shift_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match shift_service::get_shift_by_id(&db_manager, shift_id, company_id).await {
+        Ok(shift) => HttpResponse::Ok().json(ApiResponse::success(shift)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Shift not found"))
+        },
+        Err(e) => {
+            error!("Failed to retrieve shift: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to retrieve shift: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/shifts/{id}/cash-events",
+    params(
+        ("id" = i32, Path, description = "Open shift ID")
+    ),
+    request_body(content = NewCashDrawerEvent, description = "Cash put into or taken out of the drawer", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Drawer event recorded successfully", body = ApiResponse<CashDrawerEvent>),
+        (status = 400, description = "Invalid event or shift already closed", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "The shift belongs to another cashier", body = ApiResponse<()>),
+        (status = 404, description = "Shift not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "shifts"
+)]
+pub async fn add_drawer_event(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    event_data: web::Json<NewCashDrawerEvent>,
+) -> HttpResponse {
+    let shift_id = path.into_inner();
+    info!("Processing add_drawer_event request for shift_id: {}", shift_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match shift_service::add_drawer_event(&db_manager, shift_id, user.id, company_id, event_data.into_inner()).await {
+        Ok(event) => HttpResponse::Created().json(ApiResponse::success(event)),
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Only the shift's cashier or a supervisor can do this"))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Shift not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to record drawer event: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to record drawer event: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/shifts/{id}/close",
+    params(
+        ("id" = i32, Path, description = "Open shift ID")
+    ),
+    request_body(content = CloseShiftRequest, description = "Cash counted in the drawer", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Shift closed with its cash variance", body = ApiResponse<ShiftResponse>),
+        (status = 400, description = "Invalid count or shift already closed", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "The shift belongs to another cashier", body = ApiResponse<()>),
+        (status = 404, description = "Shift not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "shifts"
+)]
+pub async fn close_shift(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<i32>,
+    close_data: web::Json<CloseShiftRequest>,
+) -> HttpResponse {
+    let shift_id = path.into_inner();
+    info!("Processing close_shift request for shift_id: {}", shift_id);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match shift_service::close_shift(&db_manager, shift_id, user.id, company_id, close_data.into_inner()).await {
+        Ok(shift) => HttpResponse::Ok().json(ApiResponse::success(shift)),
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Only the shift's cashier or a supervisor can do this"))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Shift not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to close shift: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to close shift: {e}")))
+        }
+    }
+}
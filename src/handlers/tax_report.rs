@@ -0,0 +1,61 @@
+use crate::errors::ServiceError;
+use crate::models::tax_report::TaxReportQuery;
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::tax_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    get,
+    path = "/api/sales/tax-report",
+    params(
+        TaxReportQuery
+    ),
+    responses(
+        (status = 200, description = "Tax report generated successfully", body = ApiResponse<TaxReport>),
+        (status = 400, description = "Invalid date range or period", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn get_tax_report(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<TaxReportQuery>,
+) -> HttpResponse {
+    info!("Processing get_tax_report request from {} to {}", query.start_date, query.end_date);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match tax_service::generate_tax_report(&db_manager, company_id, query.into_inner()).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::success(report)),
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to generate tax report: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to generate tax report: {e}")))
+        }
+    }
+}
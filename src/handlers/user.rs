@@ -1,7 +1,7 @@
 use crate::models::{response::ApiResponse, AppState};
 use crate::services::auth::verify_jwt;
 use crate::services::db_service::DbConnectionManager;
-use crate::services::user_service;
+use crate::services::{permission_service, user_service};
 use actix_web::{web, HttpRequest, HttpResponse};
 use log::{error, info};
 
@@ -81,3 +81,131 @@ pub async fn get_user(req: HttpRequest, data: web::Data<AppState>) -> HttpRespon
         }
     }
 }
+
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}/permissions/{permission}",
+    params(
+        ("id" = i32, Path, description = "User ID"),
+        ("permission" = String, Path, description = "`price_override`, `manage_permissions`, `manage_settings` or `supervise_shifts`")
+    ),
+    responses(
+        (status = 200, description = "Permission granted", body = ApiResponse<String>),
+        (status = 400, description = "Unknown permission", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "The user may not manage permissions", body = ApiResponse<()>),
+        (status = 404, description = "User not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn grant_user_permission(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(i32, String)>,
+) -> HttpResponse {
+    let (user_id, permission) = path.into_inner();
+    info!("Processing grant_user_permission request for user ID: {} ({})", user_id, permission);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match permission_service::grant_permission(&db_manager, user.id, company_id, user_id, &permission).await {
+        Ok(()) => HttpResponse::Ok().json(ApiResponse::success(format!("Permission {permission} granted"))),
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Managing permissions requires the manage_permissions permission"))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("User not found"))
+        },
+        Err(e) => {
+            error!("Failed to grant permission: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to grant permission: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/permissions/{permission}",
+    params(
+        ("id" = i32, Path, description = "User ID"),
+        ("permission" = String, Path, description = "`price_override`, `manage_permissions`, `manage_settings` or `supervise_shifts`")
+    ),
+    responses(
+        (status = 200, description = "Permission revoked", body = ApiResponse<String>),
+        (status = 400, description = "Unknown permission", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 403, description = "The user may not manage permissions", body = ApiResponse<()>),
+        (status = 404, description = "User not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn revoke_user_permission(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(i32, String)>,
+) -> HttpResponse {
+    let (user_id, permission) = path.into_inner();
+    info!("Processing revoke_user_permission request for user ID: {} ({})", user_id, permission);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match permission_service::revoke_permission(&db_manager, user.id, company_id, user_id, &permission).await {
+        Ok(()) => HttpResponse::Ok().json(ApiResponse::success(format!("Permission {permission} revoked"))),
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(ServiceError::Unauthorized) => {
+            HttpResponse::Forbidden().json(ApiResponse::<()>::error("Managing permissions requires the manage_permissions permission"))
+        },
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("User not found"))
+        },
+        Err(e) => {
+            error!("Failed to revoke permission: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to revoke permission: {e}")))
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::errors::ServiceError;
+use crate::models::z_report::{CloseZReportRequest, ZReportQuery};
+use crate::models::{response::ApiResponse, AppState};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::z_report_service;
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::{error, info};
+
+#[utoipa::path(
+    get,
+    path = "/api/sales/z-report",
+    params(
+        ZReportQuery
+    ),
+    responses(
+        (status = 200, description = "Z report generated; closed days include discrepancies against the closed figures", body = ApiResponse<ZReport>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn get_z_report(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    query: web::Query<ZReportQuery>,
+) -> HttpResponse {
+    info!("Processing get_z_report request for store_id: {} on {}", query.store_id, query.date);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (_user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match z_report_service::get_z_report(&db_manager, company_id, query.into_inner()).await {
+        Ok(report) => HttpResponse::Ok().json(ApiResponse::success(report)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(e) => {
+            error!("Failed to generate Z report: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to generate Z report: {e}")))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sales/z-report/close",
+    request_body(content = CloseZReportRequest, description = "Store and business date to close", content_type = "application/json"),
+    responses(
+        (status = 201, description = "Business date closed successfully", body = ApiResponse<ZReport>),
+        (status = 400, description = "Business date already closed", body = ApiResponse<()>),
+        (status = 401, description = "Authentication required", body = ApiResponse<()>),
+        (status = 404, description = "Store not found", body = ApiResponse<()>),
+        (status = 500, description = "Internal server error", body = ApiResponse<()>)
+    ),
+    security(
+        ("cookie_auth" = [])
+    ),
+    tag = "sales"
+)]
+pub async fn close_z_report(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    close_data: web::Json<CloseZReportRequest>,
+) -> HttpResponse {
+    info!("Processing close_z_report request for store_id: {} on {}", close_data.store_id, close_data.date);
+
+    // Create database connection manager
+    let db_manager = DbConnectionManager::new(data.db_connection_string.clone());
+
+    // Extract authentication using our helper function
+    let auth_result = crate::middleware::extract_auth::extract_auth_user(&req, &db_manager).await;
+
+    // Handle authentication result
+    let (user, company_id) = match auth_result {
+        Ok((user, company_id)) => {
+            info!("User authenticated: {} (company_id: {})", user.email, company_id);
+            (user, company_id)
+        },
+        Err(e) => {
+            error!("Authentication failed: {:?}", e);
+            return HttpResponse::Unauthorized().json(ApiResponse::<()>::error(&format!("Authentication failed: {e}")));
+        }
+    };
+
+    match z_report_service::close_z_report(&db_manager, user.id, company_id, close_data.into_inner()).await {
+        Ok(report) => HttpResponse::Created().json(ApiResponse::success(report)),
+        Err(ServiceError::NotFound) => {
+            HttpResponse::NotFound().json(ApiResponse::<()>::error("Store not found"))
+        },
+        Err(ServiceError::ValidationError(msg)) => {
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(&msg))
+        },
+        Err(e) => {
+            error!("Failed to close Z report: {:?}", e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(&format!("Failed to close Z report: {e}")))
+        }
+    }
+}
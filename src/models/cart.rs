@@ -0,0 +1,60 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+// The active cart is the one cart calls without a cart_id work on; held carts are parked
+pub const CART_STATUS_ACTIVE: &str = "active";
+pub const CART_STATUS_HELD: &str = "held";
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Cart {
+    pub id: i32,
+    pub user_id: i32,
+    pub store_id: i32,
+    pub name: Option<String>,
+    pub status: String,
+    pub held_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CartSummary {
+    pub id: i32,
+    pub store_id: i32,
+    pub name: Option<String>,
+    pub status: String,
+    pub held_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub item_count: i64,
+    pub total_qty: i64,
+    // Sum of sale_price x qty over the cart lines
+    pub total_amount: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewCartRequest {
+    #[schema(example = 1)]
+    pub store_id: i32,
+    #[schema(example = "Customer in blue shirt")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HoldCartRequest {
+    // Label shown in the list of held carts
+    #[schema(example = "Forgot wallet")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CartListQuery {
+    /// Store ID to list carts for
+    pub store_id: i32,
+    /// Only carts with this status (`active` or `held`)
+    pub status: Option<String>,
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Customer {
+    pub id: i32,
+    pub company_id: i32,
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewCustomer {
+    #[schema(example = "Budi Santoso")]
+    pub name: String,
+    #[schema(example = "081234567890")]
+    pub phone: Option<String>,
+    #[schema(example = "budi@example.com")]
+    pub email: Option<String>,
+    #[schema(example = "Jl. Merdeka No. 1, Jakarta")]
+    pub address: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateCustomer {
+    #[schema(example = "Budi Santoso")]
+    pub name: Option<String>,
+    #[schema(example = "081234567890")]
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CustomerQueryParams {
+    /// Optional search term matched against name or phone
+    pub search: Option<String>,
+    /// Page number for pagination
+    #[schema(default = "1")]
+    pub page: Option<i32>,
+    /// Number of items per page
+    #[schema(default = "10")]
+    pub size: Option<i32>,
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+// Movement types recorded in the stock_movements ledger
+pub const MOVEMENT_SALE: &str = "sale";
+pub const MOVEMENT_ADJUSTMENT: &str = "adjustment";
+pub const MOVEMENT_TRANSFER: &str = "transfer";
+pub const MOVEMENT_RECEIPT: &str = "receipt";
+pub const MOVEMENT_RETURN: &str = "return";
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct StoreStock {
+    pub store_id: i32,
+    pub store_initial: String,
+    pub product_id: i32,
+    pub product_name: String,
+    pub sku: String,
+    pub unit_name: Option<String>,
+    pub qty: i32,
+    // None when the product has never had a movement in this store
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct StockMovement {
+    pub id: i32,
+    pub store_id: i32,
+    pub product_id: i32,
+    pub movement_type: String,
+    pub qty: i32,
+    pub balance_after: i32,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+    pub note: Option<String>,
+    pub user_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
+// A product whose requested quantity exceeds what the store can still sell
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StockShortage {
+    pub product_id: i32,
+    pub product_name: String,
+    pub requested_qty: i32,
+    pub available_qty: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewStockMovement {
+    #[schema(example = 1)]
+    pub store_id: i32,
+    #[schema(example = 1)]
+    pub product_id: i32,
+    // One of: adjustment, receipt, transfer, return (sales are recorded by checkout)
+    #[schema(example = "receipt")]
+    pub movement_type: String,
+    // Signed for adjustments, positive for receipts, returns and transfers
+    #[schema(example = 24)]
+    pub qty: i32,
+    // Destination store, required for transfers
+    #[schema(example = 2)]
+    pub to_store_id: Option<i32>,
+    #[schema(example = "Delivery from supplier")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockQueryParams {
+    /// Store ID to get stock levels for
+    pub store_id: i32,
+    /// Optional search term on product name or SKU
+    pub search: Option<String>,
+    /// Page number for pagination
+    #[schema(default = "1")]
+    pub page: Option<i32>,
+    /// Number of items per page
+    #[schema(default = "10")]
+    pub size: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockMovementQueryParams {
+    /// Store ID to get movements for
+    pub store_id: i32,
+    /// Optional product filter
+    pub product_id: Option<i32>,
+    /// Page number for pagination
+    #[schema(default = "1")]
+    pub page: Option<i32>,
+    /// Number of items per page
+    #[schema(default = "10")]
+    pub size: Option<i32>,
+}
@@ -1,9 +1,22 @@
 pub mod app_state;
 pub mod auth;
+pub mod cart;
+pub mod customer;
+pub mod inventory;
+pub mod offline_sync;
 pub mod response;
 pub mod user;
 pub mod product;
+pub mod profit_report;
+pub mod promotion;
+pub mod receipt;
+pub mod receivable;
 pub mod sales;
+pub mod sales_return;
+pub mod settings;
+pub mod shift;
+pub mod tax_report;
+pub mod z_report;
 
 pub use app_state::AppState;
 pub use response::ApiResponse;
@@ -0,0 +1,88 @@
+use crate::models::sales::OrderPaymentRequest;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// Outcome of each order in a sync batch
+pub const SYNC_STATUS_ACCEPTED: &str = "accepted";
+pub const SYNC_STATUS_DUPLICATE: &str = "duplicate";
+pub const SYNC_STATUS_REJECTED: &str = "rejected";
+// The server could not book the order right now; the till keeps it queued and resends it
+pub const SYNC_STATUS_RETRY: &str = "retry";
+
+// Largest batch a till may send in one request
+pub const MAX_SYNC_BATCH: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OfflineSyncRequest {
+    #[schema(example = 1)]
+    pub store_id: i32,
+    pub orders: Vec<OfflineOrder>,
+}
+
+// A complete order captured by a till while it was offline
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OfflineOrder {
+    // Generated by the till; resending the same UUID never creates a second order
+    #[schema(example = "0f8fad5b-d9cb-469f-a165-70867728950e", value_type = String)]
+    pub client_uuid: Uuid,
+    // When the sale was rung up on the till
+    #[schema(example = "2024-01-15T10:30:00", value_type = String)]
+    pub client_created_at: NaiveDateTime,
+    // Business date, defaults to the date of client_created_at
+    #[schema(example = "2024-01-15", value_type = Option<String>)]
+    pub date: Option<NaiveDate>,
+    pub customer_id: Option<i32>,
+    #[schema(example = "TILL2-000123")]
+    pub external_reference: Option<String>,
+    pub lines: Vec<OfflineOrderLine>,
+    pub payments: Vec<OrderPaymentRequest>,
+    // Discount on the whole order, applied the same way as at online checkout
+    #[schema(example = "fixed")]
+    pub order_discount_type: Option<String>,
+    #[schema(example = "0", value_type = Option<String>)]
+    pub order_discount_value: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OfflineOrderLine {
+    #[schema(example = 1)]
+    pub product_id: i32,
+    #[schema(example = 2)]
+    pub qty: i32,
+    // Must match the product's current sale price unless the cashier may override prices
+    #[schema(example = "15.99", value_type = String)]
+    pub base_price: Decimal,
+    #[schema(example = "fixed")]
+    pub discount_type: Option<String>,
+    #[schema(example = "0", value_type = Option<String>)]
+    pub discount_value: Option<Decimal>,
+    // Unit price after discount as charged by the till, checked against base_price and the discount
+    #[schema(example = "15.99", value_type = Option<String>)]
+    pub sale_price: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OfflineOrderResult {
+    #[schema(value_type = String)]
+    pub client_uuid: Uuid,
+    // accepted, duplicate, rejected or retry
+    pub status: String,
+    // Set for accepted orders and for duplicates of an order that was already synced
+    pub order_id: Option<i32>,
+    pub order_number: Option<String>,
+    // Reason for a rejection or retry
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OfflineSyncResponse {
+    pub accepted: i32,
+    pub duplicates: i32,
+    pub rejected: i32,
+    pub retry: i32,
+    // One entry per submitted order, in request order
+    pub results: Vec<OfflineOrderResult>,
+}
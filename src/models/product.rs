@@ -3,6 +3,10 @@ use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
 use utoipa::{ToSchema, IntoParams};
 
+// Tax categories: standard products are taxed at the company rate, exempt products are not taxed
+pub const TAX_CATEGORY_STANDARD: &str = "standard";
+pub const TAX_CATEGORY_EXEMPT: &str = "exempt";
+
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct ProductCategory {
     pub id: i32,
@@ -39,6 +43,7 @@ pub struct Product {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub category_id: Option<i32>,
+    pub tax_category: String,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -55,6 +60,9 @@ pub struct NewProduct {
     pub unit_name: Option<String>,
     #[schema(example = 1)]
     pub category_id: Option<i32>,
+    // standard or exempt, defaults to standard
+    #[schema(example = "standard")]
+    pub tax_category: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema, IntoParams)]
@@ -0,0 +1,58 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+// Dimensions the profit report can be grouped by
+pub const PROFIT_GROUP_SKU: &str = "sku";
+pub const PROFIT_GROUP_CATEGORY: &str = "category";
+pub const PROFIT_GROUP_STORE: &str = "store";
+pub const PROFIT_GROUP_DAY: &str = "day";
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProfitReportQuery {
+    /// Start date for the report (YYYY-MM-DD)
+    pub start_date: NaiveDate,
+    /// End date for the report (YYYY-MM-DD)
+    pub end_date: NaiveDate,
+    /// Store ID (0 or omitted for all stores)
+    pub store_id: Option<i32>,
+    /// One of `sku`, `category`, `store`, `day` (defaults to `sku`)
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ProfitRow {
+    // Product ID, category ID, store ID or date depending on the grouping
+    pub key: String,
+    pub label: String,
+    pub total_qty: i64,
+    pub revenue: Decimal,
+    // Cost of goods sold, from the unit cost snapshotted on each order line
+    pub cogs: Decimal,
+    pub gross_profit: Decimal,
+    // gross_profit / revenue x 100, None when there is no revenue
+    pub margin_percent: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProfitTotals {
+    pub total_qty: i64,
+    pub revenue: Decimal,
+    pub cogs: Decimal,
+    pub gross_profit: Decimal,
+    pub margin_percent: Option<Decimal>,
+}
+
+// Profit on the lines of non-voided orders dated within the period, less the returns dated within it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProfitReport {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub store_id: i32,
+    pub group_by: String,
+    pub rows: Vec<ProfitRow>,
+    pub totals: ProfitTotals,
+}
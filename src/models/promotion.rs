@@ -0,0 +1,126 @@
+use chrono::{NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+// What a promotion does. Stored as JSON in promotions.rule, tagged by "type".
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PromotionRule {
+    // For every buy_qty units of the product, get_qty more are free
+    BuyXGetY {
+        product_id: i32,
+        buy_qty: i32,
+        get_qty: i32,
+    },
+    // The listed products together sell for a fixed price
+    BundlePrice {
+        items: Vec<BundleItem>,
+        #[schema(value_type = String)]
+        price: Decimal,
+    },
+    // Unit price drops once the quantity of the product reaches a tier
+    QuantityTier {
+        product_id: i32,
+        tiers: Vec<PriceTier>,
+    },
+    // Percentage off every product of a category
+    CategoryPercentage {
+        category_id: i32,
+        #[schema(value_type = String)]
+        percentage: Decimal,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BundleItem {
+    #[schema(example = 1)]
+    pub product_id: i32,
+    #[schema(example = 1)]
+    pub qty: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PriceTier {
+    #[schema(example = 10)]
+    pub min_qty: i32,
+    #[schema(example = "12.50", value_type = String)]
+    pub unit_price: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Promotion {
+    pub id: i32,
+    pub company_id: i32,
+    pub name: String,
+    #[sqlx(json)]
+    pub rule: PromotionRule,
+    // Stores the promotion runs in, all stores of the company when empty
+    pub store_ids: Option<Vec<i32>>,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    #[schema(value_type = Option<String>)]
+    pub daily_start: Option<NaiveTime>,
+    #[schema(value_type = Option<String>)]
+    pub daily_end: Option<NaiveTime>,
+    pub priority: i32,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewPromotion {
+    #[schema(example = "Buy 2 get 1 free")]
+    pub name: String,
+    pub rule: PromotionRule,
+    pub store_ids: Option<Vec<i32>>,
+    #[schema(example = "2024-01-01T00:00:00")]
+    pub starts_at: Option<NaiveDateTime>,
+    #[schema(example = "2024-01-31T23:59:59")]
+    pub ends_at: Option<NaiveDateTime>,
+    // Happy hour style window, e.g. 15:00 to 17:00
+    #[schema(example = "15:00:00", value_type = Option<String>)]
+    pub daily_start: Option<NaiveTime>,
+    #[schema(example = "17:00:00", value_type = Option<String>)]
+    pub daily_end: Option<NaiveTime>,
+    #[serde(default)]
+    #[schema(example = 0)]
+    pub priority: i32,
+}
+
+// Replaces every field of the promotion; active is left as is when omitted
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePromotion {
+    #[schema(example = "Buy 2 get 1 free")]
+    pub name: String,
+    pub rule: PromotionRule,
+    pub store_ids: Option<Vec<i32>>,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    #[schema(value_type = Option<String>)]
+    pub daily_start: Option<NaiveTime>,
+    #[schema(value_type = Option<String>)]
+    pub daily_end: Option<NaiveTime>,
+    #[serde(default)]
+    pub priority: i32,
+    #[schema(example = true)]
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PromotionQueryParams {
+    /// Only list promotions that are switched on
+    pub active_only: Option<bool>,
+}
+
+// A promotion applied to an order or cart line and what it took off the line
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AppliedPromotion {
+    #[schema(example = 1)]
+    pub promotion_id: i32,
+    #[schema(example = "5.00", value_type = String)]
+    pub discount_amount: Decimal,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+// Thermal paper widths and the characters per line they fit in the printer's standard font
+pub const RECEIPT_WIDTH_58MM: &str = "58mm";
+pub const RECEIPT_WIDTH_80MM: &str = "80mm";
+pub const RECEIPT_COLUMNS_58MM: usize = 32;
+pub const RECEIPT_COLUMNS_80MM: usize = 48;
+
+// Output formats of the receipt endpoint
+pub const RECEIPT_FORMAT_TEXT: &str = "text";
+pub const RECEIPT_FORMAT_ESCPOS: &str = "escpos";
+pub const RECEIPT_FORMAT_PDF: &str = "pdf";
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReceiptQuery {
+    /// Paper width, `58mm` or `80mm` (defaults to `80mm`)
+    pub width: Option<String>,
+    /// `text` for plain text, `escpos` for printer commands or `pdf` (defaults to `text`)
+    pub format: Option<String>,
+}
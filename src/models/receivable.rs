@@ -0,0 +1,91 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ReceivablePayment {
+    pub id: i32,
+    pub order_id: i32,
+    pub customer_id: Option<i32>,
+    pub user_id: i32,
+    pub store_id: i32,
+    pub date: NaiveDate,
+    pub payment_cash: Decimal,
+    pub payment_non_cash: Decimal,
+    pub amount: Decimal,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewReceivablePayment {
+    #[schema(example = "25.00", value_type = String)]
+    pub payment_cash: Decimal,
+    #[schema(example = "0.00", value_type = String)]
+    pub payment_non_cash: Decimal,
+    #[schema(example = "2025-07-20")]
+    pub date: Option<NaiveDate>,
+    #[schema(example = "Second installment")]
+    pub note: Option<String>,
+}
+
+// Receivable position of a single order
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ReceivableOrder {
+    pub order_id: i32,
+    pub order_number: String,
+    pub customer_id: Option<i32>,
+    pub store_id: i32,
+    pub date: NaiveDate,
+    pub grand_total: Decimal,
+    pub receivable: Decimal,
+    pub paid_amount: Decimal,
+    pub outstanding: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrderReceivableResponse {
+    pub order: ReceivableOrder,
+    pub payments: Vec<ReceivablePayment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomerReceivables {
+    pub customer_id: i32,
+    pub customer_name: String,
+    pub total_outstanding: Decimal,
+    // Orders that still have an outstanding balance, oldest first
+    pub orders: Vec<ReceivableOrder>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AgingReportQuery {
+    /// Date the balances are aged against (YYYY-MM-DD), defaults to today
+    pub as_of: Option<NaiveDate>,
+    /// Store ID (0 or omitted for all stores)
+    pub store_id: Option<i32>,
+}
+
+// Outstanding balances of one customer split by age of the order
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AgingRow {
+    // None groups orders made without a customer
+    pub customer_id: Option<i32>,
+    pub customer_name: Option<String>,
+    pub days_0_30: Decimal,
+    pub days_31_60: Decimal,
+    pub days_61_90: Decimal,
+    pub days_over_90: Decimal,
+    pub total: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AgingReport {
+    pub as_of: NaiveDate,
+    pub store_id: i32,
+    pub rows: Vec<AgingRow>,
+    pub totals: AgingRow,
+}
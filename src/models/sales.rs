@@ -4,25 +4,54 @@ use sqlx::FromRow;
 use rust_decimal::Decimal;
 use utoipa::ToSchema;
 
+// Lifecycle states of a sales order
+pub const ORDER_STATUS_COMPLETED: &str = "completed";
+pub const ORDER_STATUS_VOIDED: &str = "voided";
+
+// Tender types accepted in sales_order_payments.method
+pub const PAYMENT_METHOD_CASH: &str = "cash";
+pub const PAYMENT_METHOD_DEBIT_CARD: &str = "debit_card";
+pub const PAYMENT_METHOD_CREDIT_CARD: &str = "credit_card";
+pub const PAYMENT_METHOD_QRIS: &str = "qris";
+pub const PAYMENT_METHOD_BANK_TRANSFER: &str = "bank_transfer";
+pub const PAYMENT_METHOD_E_WALLET: &str = "e_wallet";
+// Non-cash amount sent through the legacy payment_non_cash field, method unknown
+pub const PAYMENT_METHOD_OTHER: &str = "other";
+
+pub const PAYMENT_METHODS: [&str; 7] = [
+    PAYMENT_METHOD_CASH,
+    PAYMENT_METHOD_DEBIT_CARD,
+    PAYMENT_METHOD_CREDIT_CARD,
+    PAYMENT_METHOD_QRIS,
+    PAYMENT_METHOD_BANK_TRANSFER,
+    PAYMENT_METHOD_E_WALLET,
+    PAYMENT_METHOD_OTHER,
+];
+
 #[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct SalesCart {
     pub id: i32,
+    pub cart_id: i32,
     pub user_id: i32,
     pub store_id: i32,
     pub product_id: i32,
     pub base_price: Decimal,
     pub qty: i32,
     pub discount_type: String,
-    pub discount_value: i32,
+    pub discount_value: Decimal,
     pub discount_amount: Decimal,
     pub sale_price: Decimal,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    // Catalog price when the line was priced; differs from base_price on overridden lines
+    pub list_price: Decimal,
+    pub price_override_by: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct SalesCartResponse {
     pub id: i32,
+    pub cart_id: i32,
     pub user_id: i32,
     pub store_id: i32,
     pub product_id: i32,
@@ -31,43 +60,58 @@ pub struct SalesCartResponse {
     pub base_price: Decimal,
     pub qty: i32,
     pub discount_type: String,
-    pub discount_value: i32,
+    pub discount_value: Decimal,
     pub discount_amount: Decimal,
     pub sale_price: Decimal,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub list_price: Decimal,
+    pub price_override_by: Option<i32>,
+    // Expiry of the line's stock reservation; in the past once the reservation has lapsed
+    pub reserved_until: Option<NaiveDateTime>,
+    // Promotions checkout would apply to the line and what they take off the line total
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub promotion_ids: Vec<i32>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub promotion_discount: Decimal,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct NewSalesCart {
     #[schema(example = 1)]
     pub store_id: i32,
+    // Cart to add to, defaults to the active cart (created when there is none)
+    #[schema(example = 1)]
+    pub cart_id: Option<i32>,
     #[schema(example = 1)]
     pub product_id: i32,
-    #[schema(example = "15.99", value_type = String)]
-    pub base_price: Decimal,
+    // Unit price; the product's catalog price is used when omitted. Any other price is a
+    // manual override and needs the price_override permission.
+    #[schema(example = "15.99", value_type = Option<String>)]
+    pub base_price: Option<Decimal>,
     #[schema(example = 2)]
     pub qty: i32,
+    // none, percentage or fixed
     #[schema(example = "percentage")]
     pub discount_type: Option<String>,
-    #[schema(example = 10)]
-    pub discount_value: Option<i32>,
-    #[schema(example = "1.60", value_type = String)]
-    pub discount_amount: Option<Decimal>,
-    #[schema(example = "30.38", value_type = String)]
-    pub sale_price: Option<Decimal>,
+    // Percentage (0-100) or amount off one unit, depending on discount_type
+    #[schema(example = "10", value_type = Option<String>)]
+    pub discount_value: Option<Decimal>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateSalesCart {
+    // Manual unit price, subject to the same override rule as NewSalesCart.base_price
     #[schema(example = "18.99", value_type = String)]
     pub base_price: Option<Decimal>,
     #[schema(example = 3)]
     pub qty: Option<i32>,
     #[schema(example = "fixed")]
     pub discount_type: Option<String>,
-    #[schema(example = 5)]
-    pub discount_value: Option<i32>,
+    #[schema(example = "5.50", value_type = Option<String>)]
+    pub discount_value: Option<Decimal>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -77,12 +121,32 @@ pub struct SalesOrder {
     pub user_id: i32,
     pub store_id: i32,
     pub date: NaiveDate,
+    // Sum of the line totals before the order discount
+    pub subtotal: Decimal,
+    pub order_discount_type: String,
+    pub order_discount_value: Decimal,
+    pub order_discount_amount: Decimal,
+    // Cash rounding, positive when rounded up
+    pub rounding_amount: Decimal,
+    // Sum of the line taxes; added to grand_total unless prices are tax-inclusive
+    pub tax_amount: Decimal,
+    pub tax_inclusive: bool,
+    // Store service charge in percent and the amount added to grand_total
+    pub service_charge_rate: Decimal,
+    pub service_charge_amount: Decimal,
     pub grand_total: Decimal,
     pub payment_cash: Decimal,
     pub payment_non_cash: Decimal,
     pub receivable: Decimal,
+    pub change_due: Decimal,
     pub created_at: NaiveDateTime,
     pub customer_id: Option<i32>,
+    pub status: String,
+    pub voided_at: Option<NaiveDateTime>,
+    pub voided_by: Option<i32>,
+    pub void_reason: Option<String>,
+    pub shift_id: Option<i32>,
+    pub external_reference: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -96,29 +160,88 @@ pub struct SalesOrderDetail {
     pub discount_value: Decimal,
     pub discount_amount: Decimal, 
     pub sale_price: Decimal,
+    // Discount from promotions over the whole line
+    pub promotion_discount_amount: Decimal,
+    // Share of the order discount; total_price is net of it and of promotion_discount_amount
+    pub order_discount_amount: Decimal,
     pub total_price: Decimal,
+    // Tax rate in percent and the tax contained in or added to total_price
+    pub tax_rate: Decimal,
+    pub tax_amount: Decimal,
+    // Share of the order's service charge, on top of total_price
+    pub service_charge_amount: Decimal,
+    // Purchase price of the product at the time of sale
+    pub unit_cost: Decimal,
+    pub list_price: Decimal,
+    // User who sold the line at a price other than list_price
+    pub price_override_by: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateOrderRequest {
-    #[schema(example = "ORD001")]
-    pub order_number: String,
+    // The order number is allocated by the server; a number sent by the client is only kept
+    // as a reference to the till's own numbering
+    #[serde(default, alias = "order_number")]
+    #[schema(example = "TILL2-000123")]
+    pub external_reference: Option<String>,
     #[schema(example = 1)]
     pub store_id: i32,
+    // Cart to check out, defaults to the active cart
+    #[schema(example = 1)]
+    pub cart_id: Option<i32>,
     #[schema(example = "2025-07-09")]
     pub date: Option<NaiveDate>,
+    // Legacy totals, only used when no tender lines are given
+    #[serde(default)]
     #[schema(example = "50.00", value_type = String)]
     pub payment_cash: Decimal,
+    #[serde(default)]
     #[schema(example = "0.00", value_type = String)]
     pub payment_non_cash: Decimal,
     #[schema(example = 1)]
     pub customer_id: Option<i32>,
+    #[serde(default)]
+    pub payments: Vec<OrderPaymentRequest>,
+    // Discount on the whole order, spread over the lines in proportion to their totals
+    #[schema(example = "percentage")]
+    pub order_discount_type: Option<String>,
+    #[schema(example = "10", value_type = Option<String>)]
+    pub order_discount_value: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrderPaymentRequest {
+    /// One of `cash`, `debit_card`, `credit_card`, `qris`, `bank_transfer`, `e_wallet`
+    #[schema(example = "qris")]
+    pub method: String,
+    #[schema(example = "50.00", value_type = String)]
+    pub amount: Decimal,
+    /// Approval code or transaction reference printed by the EDC terminal
+    #[schema(example = "APPR-123456")]
+    pub reference_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SalesOrderPayment {
+    pub id: i32,
+    pub order_id: i32,
+    pub method: String,
+    pub amount: Decimal,
+    pub reference_number: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VoidOrderRequest {
+    #[schema(example = "Customer cancelled before payment")]
+    pub reason: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrderResponse {
     pub order: SalesOrder,
     pub details: Vec<SalesOrderDetail>,
+    pub payments: Vec<SalesOrderPayment>,
 }
 
 // Enhanced OrderResponse with more details
@@ -126,6 +249,7 @@ pub struct OrderResponse {
 pub struct DetailedOrderResponse {
     pub order: DetailedSalesOrder,
     pub details: Vec<DetailedSalesOrderDetail>,
+    pub payments: Vec<SalesOrderPayment>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -137,12 +261,32 @@ pub struct DetailedSalesOrder {
     pub store_id: i32,
     pub store_initial: String,
     pub date: NaiveDate,
+    // Sum of the line totals before the order discount
+    pub subtotal: Decimal,
+    pub order_discount_type: String,
+    pub order_discount_value: Decimal,
+    pub order_discount_amount: Decimal,
+    // Cash rounding, positive when rounded up
+    pub rounding_amount: Decimal,
+    // Sum of the line taxes; added to grand_total unless prices are tax-inclusive
+    pub tax_amount: Decimal,
+    pub tax_inclusive: bool,
+    // Store service charge in percent and the amount added to grand_total
+    pub service_charge_rate: Decimal,
+    pub service_charge_amount: Decimal,
     pub grand_total: Decimal,
     pub payment_cash: Decimal,
     pub payment_non_cash: Decimal,
     pub receivable: Decimal,
+    pub change_due: Decimal,
     pub created_at: NaiveDateTime,
     pub customer_id: Option<i32>,
+    pub status: String,
+    pub voided_at: Option<NaiveDateTime>,
+    pub voided_by: Option<i32>,
+    pub void_reason: Option<String>,
+    pub shift_id: Option<i32>,
+    pub external_reference: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -158,11 +302,31 @@ pub struct DetailedSalesOrderDetail {
     pub discount_value: Decimal,
     pub discount_amount: Decimal,
     pub sale_price: Decimal,
+    // Discount from promotions over the whole line
+    pub promotion_discount_amount: Decimal,
+    // Share of the order discount; total_price is net of it and of promotion_discount_amount
+    pub order_discount_amount: Decimal,
     pub total_price: Decimal,
+    // Tax rate in percent and the tax contained in or added to total_price
+    pub tax_rate: Decimal,
+    pub tax_amount: Decimal,
+    // Share of the order's service charge, on top of total_price
+    pub service_charge_amount: Decimal,
+    pub list_price: Decimal,
+    pub price_override_by: Option<i32>,
+    pub promotion_ids: Vec<i32>,
 }
 
 // Sales Report models moved from sales_report.rs
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+
+// Output formats of the sales report endpoint
+pub const REPORT_FORMAT_JSON: &str = "json";
+pub const REPORT_FORMAT_PDF: &str = "pdf";
+// Orders, line items and SKU summary as CSV files in a zip archive, or as XLSX worksheets
+pub const REPORT_FORMAT_CSV: &str = "csv";
+pub const REPORT_FORMAT_XLSX: &str = "xlsx";
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SalesReportQuery {
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
@@ -178,12 +342,32 @@ pub struct SalesReportOrder {
     pub store_id: i32,
     pub store_initial: String, // Added field for store initial
     pub date: NaiveDate,
+    // Sum of the line totals before the order discount
+    pub subtotal: Decimal,
+    pub order_discount_type: String,
+    pub order_discount_value: Decimal,
+    pub order_discount_amount: Decimal,
+    // Cash rounding, positive when rounded up
+    pub rounding_amount: Decimal,
+    // Sum of the line taxes; added to grand_total unless prices are tax-inclusive
+    pub tax_amount: Decimal,
+    pub tax_inclusive: bool,
+    // Store service charge in percent and the amount added to grand_total
+    pub service_charge_rate: Decimal,
+    pub service_charge_amount: Decimal,
     pub grand_total: Decimal,
     pub payment_cash: Decimal,
     pub payment_non_cash: Decimal,
     pub receivable: Decimal,
+    pub change_due: Decimal,
     pub created_at: NaiveDateTime,
     pub customer_id: Option<i32>,
+    pub status: String,
+    pub voided_at: Option<NaiveDateTime>,
+    pub voided_by: Option<i32>,
+    pub void_reason: Option<String>,
+    pub shift_id: Option<i32>,
+    pub external_reference: Option<String>,
     #[sqlx(skip)]
     #[serde(default)]
     pub items: Vec<SalesReportOrderItem>, // Added field for order items
@@ -202,7 +386,16 @@ pub struct SalesReportOrderItem {
     pub discount_value: Decimal,
     pub discount_amount: Decimal,
     pub sale_price: Decimal,
+    // Discount from promotions over the whole line
+    pub promotion_discount_amount: Decimal,
+    // Share of the order discount; total_price is net of it and of promotion_discount_amount
+    pub order_discount_amount: Decimal,
     pub total_price: Decimal,
+    // Tax rate in percent and the tax contained in or added to total_price
+    pub tax_rate: Decimal,
+    pub tax_amount: Decimal,
+    // Share of the order's service charge, on top of total_price
+    pub service_charge_amount: Decimal,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -216,10 +409,38 @@ pub struct SkuSummaryItem {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SalesSummary {
+    // Cash kept by the store, i.e. cash tendered minus change given back
     pub total_payment_cash: Decimal,
     pub total_payment_non_cash: Decimal,
     pub total_receivable: Decimal,
+    pub total_change_due: Decimal,
+    // Counts and totals above exclude voided orders
     pub total_orders: i32,
+    pub total_voided_orders: i32,
+    pub total_voided_amount: Decimal,
+    // Sum of grand_total over non-voided orders
+    pub gross_sales: Decimal,
+    // Returns processed within the report period, whatever the date of the original order
+    pub total_returns: i32,
+    pub total_refund: Decimal,
+    pub total_refund_cash: Decimal,
+    pub total_refund_non_cash: Decimal,
+    // Order-level discounts, cash rounding, tax and service charge included in gross_sales
+    pub total_order_discount: Decimal,
+    pub total_rounding: Decimal,
+    pub total_tax: Decimal,
+    pub total_service_charge: Decimal,
+    // gross_sales minus total_refund
+    pub net_sales: Decimal,
+    // Tender totals of non-voided orders, cash is net of change given
+    pub payment_methods: Vec<PaymentMethodTotal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct PaymentMethodTotal {
+    pub method: String,
+    pub total_payments: i32,
+    pub total_amount: Decimal,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
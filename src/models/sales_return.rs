@@ -0,0 +1,65 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SalesReturn {
+    pub id: i32,
+    pub order_id: i32,
+    pub user_id: i32,
+    pub store_id: i32,
+    pub date: NaiveDate,
+    pub total_refund: Decimal,
+    pub refund_cash: Decimal,
+    pub refund_non_cash: Decimal,
+    pub reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SalesReturnDetail {
+    pub id: i32,
+    pub return_id: i32,
+    pub order_detail_id: i32,
+    pub product_id: i32,
+    pub qty: i32,
+    // total_refund / qty for display; total_refund is what was actually refunded
+    pub unit_refund: Decimal,
+    pub total_refund: Decimal,
+    pub resellable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReturnItemRequest {
+    // Line of the original order being returned
+    #[schema(example = 10)]
+    pub order_detail_id: i32,
+    #[schema(example = 1)]
+    pub qty: i32,
+    // Whether the goods can go back on the shelf
+    #[schema(example = true)]
+    pub resellable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateReturnRequest {
+    pub items: Vec<ReturnItemRequest>,
+    // The refund split must add up to the value of the returned lines
+    #[schema(example = "15.99", value_type = String)]
+    pub refund_cash: Decimal,
+    #[schema(example = "0.00", value_type = String)]
+    pub refund_non_cash: Decimal,
+    #[schema(example = "2025-07-10")]
+    pub date: Option<NaiveDate>,
+    #[schema(example = "Damaged packaging")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReturnResponse {
+    #[serde(rename = "return")]
+    pub sales_return: SalesReturn,
+    pub details: Vec<SalesReturnDetail>,
+}
@@ -0,0 +1,118 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CompanySettings {
+    pub company_id: i32,
+    // Allow checkout and cart reservations to take stock below zero
+    pub allow_negative_stock: bool,
+    // How long a cart line holds its stock reservation
+    pub reservation_ttl_minutes: i32,
+    // Refuse checkout when the cashier has no open shift in the store
+    pub require_open_shift: bool,
+    // Digits of the currency's minor unit, e.g. 2 for cents or 0 for rupiah
+    pub currency_decimals: i32,
+    // Cash-paid totals are rounded to a multiple of this amount, e.g. 100 for Rp100; 0 is off
+    #[schema(value_type = String)]
+    pub cash_rounding_unit: Decimal,
+    // PPN/VAT rate in percent applied to standard-rated products, 0 for no tax
+    #[schema(value_type = String)]
+    pub tax_rate: Decimal,
+    // Whether catalog prices already include the tax
+    pub tax_inclusive: bool,
+    // Largest manual line or order discount, in percent, allowed without the price_override
+    // permission
+    #[schema(value_type = String)]
+    pub max_discount_percent: Decimal,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl CompanySettings {
+    // Settings used for companies that have never saved a configuration
+    #[must_use]
+    pub fn defaults(company_id: i32) -> Self {
+        Self {
+            company_id,
+            allow_negative_stock: false,
+            reservation_ttl_minutes: 15,
+            require_open_shift: false,
+            currency_decimals: 2,
+            cash_rounding_unit: Decimal::ZERO,
+            tax_rate: Decimal::ZERO,
+            tax_inclusive: true,
+            max_discount_percent: Decimal::ONE_HUNDRED,
+            updated_at: None,
+        }
+    }
+
+    // currency_decimals as a rounding scale
+    #[must_use]
+    pub fn currency_scale(&self) -> u32 {
+        u32::try_from(self.currency_decimals).unwrap_or(2)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct StoreSettings {
+    pub store_id: i32,
+    // Service charge in percent of the charged lines, 0 for none
+    #[schema(value_type = String)]
+    pub service_charge_rate: Decimal,
+    // Charge on line amounts including tax instead of before tax
+    pub service_charge_after_tax: bool,
+    // Product categories the service charge does not apply to
+    pub service_charge_exempt_category_ids: Vec<i32>,
+    // Printed at the bottom of receipts
+    pub receipt_footer: Option<String>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl StoreSettings {
+    // Settings used for stores that have never saved a configuration
+    #[must_use]
+    pub fn defaults(store_id: i32) -> Self {
+        Self {
+            store_id,
+            service_charge_rate: Decimal::ZERO,
+            service_charge_after_tax: false,
+            service_charge_exempt_category_ids: Vec::new(),
+            receipt_footer: None,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateStoreSettings {
+    #[schema(example = "5", value_type = Option<String>)]
+    pub service_charge_rate: Option<Decimal>,
+    #[schema(example = false)]
+    pub service_charge_after_tax: Option<bool>,
+    pub service_charge_exempt_category_ids: Option<Vec<i32>>,
+    // An empty string removes the footer
+    #[schema(example = "Thank you for your visit")]
+    pub receipt_footer: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateCompanySettings {
+    #[schema(example = false)]
+    pub allow_negative_stock: Option<bool>,
+    #[schema(example = 15)]
+    pub reservation_ttl_minutes: Option<i32>,
+    #[schema(example = false)]
+    pub require_open_shift: Option<bool>,
+    #[schema(example = 2)]
+    pub currency_decimals: Option<i32>,
+    #[schema(example = "100", value_type = Option<String>)]
+    pub cash_rounding_unit: Option<Decimal>,
+    #[schema(example = "11", value_type = Option<String>)]
+    pub tax_rate: Option<Decimal>,
+    #[schema(example = true)]
+    pub tax_inclusive: Option<bool>,
+    #[schema(example = "10", value_type = Option<String>)]
+    pub max_discount_percent: Option<Decimal>,
+}
@@ -0,0 +1,100 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+pub const SHIFT_STATUS_OPEN: &str = "open";
+pub const SHIFT_STATUS_CLOSED: &str = "closed";
+
+// Cash drawer events recorded outside of sales
+pub const DRAWER_EVENT_CASH_IN: &str = "cash_in";
+pub const DRAWER_EVENT_CASH_OUT: &str = "cash_out";
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CashShift {
+    pub id: i32,
+    pub user_id: i32,
+    pub store_id: i32,
+    pub status: String,
+    pub opening_float: Decimal,
+    pub opened_at: NaiveDateTime,
+    pub opening_note: Option<String>,
+    pub closing_count: Option<Decimal>,
+    pub expected_cash: Option<Decimal>,
+    // closing_count minus expected_cash: negative when the drawer is short
+    pub variance: Option<Decimal>,
+    pub closed_at: Option<NaiveDateTime>,
+    pub closed_by: Option<i32>,
+    pub closing_note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OpenShiftRequest {
+    #[schema(example = 1)]
+    pub store_id: i32,
+    #[schema(example = "200000.00", value_type = String)]
+    pub opening_float: Decimal,
+    #[schema(example = "Morning shift")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CurrentShiftQuery {
+    /// Store the shift is open in
+    pub store_id: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CashDrawerEvent {
+    pub id: i32,
+    pub shift_id: i32,
+    pub event_type: String,
+    pub amount: Decimal,
+    pub reason: Option<String>,
+    pub user_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewCashDrawerEvent {
+    /// `cash_in` or `cash_out`
+    #[schema(example = "cash_out")]
+    pub event_type: String,
+    #[schema(example = "500000.00", value_type = String)]
+    pub amount: Decimal,
+    #[schema(example = "Mid-day pickup")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CloseShiftRequest {
+    // Cash counted in the drawer at close
+    #[schema(example = "1250000.00", value_type = String)]
+    pub closing_count: Decimal,
+    #[schema(example = "Counted with supervisor")]
+    pub note: Option<String>,
+}
+
+// Cash the drawer should hold, built from the shift's orders and drawer events
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ShiftCashSummary {
+    pub total_orders: i32,
+    // Cash taken on non-voided orders, net of change given
+    pub cash_sales: Decimal,
+    // Cash refunds and debt settlements handled by the cashier in the store while the shift was open
+    pub cash_refunds: Decimal,
+    pub receivable_cash: Decimal,
+    pub cash_in: Decimal,
+    pub cash_out: Decimal,
+    // opening_float + cash_sales + receivable_cash + cash_in - cash_refunds - cash_out
+    pub expected_cash: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ShiftResponse {
+    pub shift: CashShift,
+    pub events: Vec<CashDrawerEvent>,
+    pub summary: ShiftCashSummary,
+}
@@ -0,0 +1,61 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+// Periods the tax report can be broken down by
+pub const TAX_PERIOD_DAY: &str = "day";
+pub const TAX_PERIOD_MONTH: &str = "month";
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TaxReportQuery {
+    /// Start date for the report (YYYY-MM-DD)
+    pub start_date: NaiveDate,
+    /// End date for the report (YYYY-MM-DD)
+    pub end_date: NaiveDate,
+    /// Store ID (0 or omitted for all stores)
+    pub store_id: Option<i32>,
+    /// One of `day`, `month` (defaults to `month`)
+    pub period: Option<String>,
+}
+
+// Sales are counted on the order date and returns on the return date
+#[derive(Debug, Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TaxReportRow {
+    // YYYY-MM-DD or YYYY-MM depending on the period
+    pub period: String,
+    pub total_orders: i64,
+    // Tax base (DPP) of taxed lines, excluding the tax itself
+    pub taxable_sales: Decimal,
+    pub tax_amount: Decimal,
+    // Lines of tax-exempt products
+    pub exempt_sales: Decimal,
+    pub returned_taxable_sales: Decimal,
+    pub returned_tax_amount: Decimal,
+    // tax_amount minus returned_tax_amount
+    pub net_tax_amount: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TaxReportTotals {
+    pub total_orders: i64,
+    pub taxable_sales: Decimal,
+    pub tax_amount: Decimal,
+    pub exempt_sales: Decimal,
+    pub returned_taxable_sales: Decimal,
+    pub returned_tax_amount: Decimal,
+    pub net_tax_amount: Decimal,
+}
+
+// Tax on non-voided orders and on returns dated within the period
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TaxReport {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub store_id: i32,
+    pub period: String,
+    pub rows: Vec<TaxReportRow>,
+    pub totals: TaxReportTotals,
+}
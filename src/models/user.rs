@@ -61,3 +61,15 @@ pub struct NewUserStore {
     pub user_id: i32,
     pub store_id: i32,
 }
+
+// Permissions held in user_permissions
+pub const PERMISSION_PRICE_OVERRIDE: &str = "price_override";
+pub const PERMISSION_MANAGE_PERMISSIONS: &str = "manage_permissions";
+pub const PERMISSION_MANAGE_SETTINGS: &str = "manage_settings";
+pub const PERMISSION_SUPERVISE_SHIFTS: &str = "supervise_shifts";
+pub const PERMISSIONS: [&str; 4] = [
+    PERMISSION_PRICE_OVERRIDE,
+    PERMISSION_MANAGE_PERMISSIONS,
+    PERMISSION_MANAGE_SETTINGS,
+    PERMISSION_SUPERVISE_SHIFTS,
+];
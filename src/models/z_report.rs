@@ -0,0 +1,85 @@
+use crate::models::sales::PaymentMethodTotal;
+use chrono::{NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ZReportQuery {
+    /// Store ID
+    pub store_id: i32,
+    /// Business date (YYYY-MM-DD)
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CloseZReportRequest {
+    #[schema(example = 1)]
+    pub store_id: i32,
+    #[schema(example = "2025-07-09")]
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CashierSubtotal {
+    pub user_id: i32,
+    pub user_initial: String,
+    pub total_orders: i32,
+    pub gross_sales: Decimal,
+    pub total_discounts: Decimal,
+    // Cash kept on the cashier's orders, net of change given
+    pub cash_sales: Decimal,
+}
+
+// Closing figures of one store for one business date. Voided orders only count
+// towards the voided totals.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ZReportFigures {
+    pub gross_sales: Decimal,
+    // Line discounts (discount_amount x qty), promotion discounts and order discounts given on the orders
+    pub total_discounts: Decimal,
+    pub total_orders: i32,
+    // gross_sales / total_orders
+    pub average_basket: Decimal,
+    pub first_order_number: Option<String>,
+    pub last_order_number: Option<String>,
+    pub total_voided_orders: i32,
+    pub total_voided_amount: Decimal,
+    // Returns processed in the store that day
+    pub total_returns: i32,
+    pub total_refund: Decimal,
+    pub net_sales: Decimal,
+    pub total_change_due: Decimal,
+    pub tenders: Vec<PaymentMethodTotal>,
+    pub cashiers: Vec<CashierSubtotal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ZReportClosing {
+    pub id: i32,
+    pub closed_at: NaiveDateTime,
+    pub closed_by: i32,
+    // Figures as they were when the day was closed
+    pub figures: ZReportFigures,
+}
+
+// A closed figure that no longer matches the orders, e.g. after a late void or edit
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ZReportDiscrepancy {
+    pub field: String,
+    pub closed_value: String,
+    pub current_value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ZReport {
+    pub store_id: i32,
+    pub store_initial: String,
+    pub business_date: NaiveDate,
+    // Figures computed from the orders as they are now
+    pub figures: ZReportFigures,
+    // Present once the day has been closed
+    pub closing: Option<ZReportClosing>,
+    pub discrepancies: Vec<ZReportDiscrepancy>,
+}
@@ -0,0 +1,13 @@
+use actix_web::web;
+use crate::handlers::customer::{create_customer, delete_customer, get_customer_by_id, get_customers, update_customer};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/customers")
+            .route("", web::post().to(create_customer))
+            .route("", web::get().to(get_customers))
+            .route("/{id}", web::get().to(get_customer_by_id))
+            .route("/{id}", web::put().to(update_customer))
+            .route("/{id}", web::delete().to(delete_customer))
+    );
+}
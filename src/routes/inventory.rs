@@ -0,0 +1,12 @@
+use actix_web::web;
+use crate::handlers::inventory;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/inventory")
+            .route("/stock", web::get().to(inventory::get_store_stock))
+            .route("/stock/{product_id}", web::get().to(inventory::get_product_stock))
+            .route("/movements", web::post().to(inventory::create_stock_movement))
+            .route("/movements", web::get().to(inventory::get_stock_movements))
+    );
+}
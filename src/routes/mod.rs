@@ -1,19 +1,31 @@
 pub mod auth;
+pub mod customers;
 pub mod health;
+pub mod inventory;
 pub mod orders;
 pub mod products;
+pub mod promotions;
+pub mod receivables;
 pub mod user;
 pub mod debug;
 pub mod sales;
+pub mod settings;
+pub mod shifts;
 
 // Re-export all route configuration functions
 pub use auth::configure as configure_auth;
+pub use customers::configure as configure_customers;
 pub use health::configure as configure_health;
+pub use inventory::configure as configure_inventory;
 pub use orders::configure as configure_orders;
 pub use products::configure as configure_products;
+pub use promotions::configure as configure_promotions;
+pub use receivables::configure as configure_receivables;
 pub use user::configure as configure_user;
 pub use debug::configure as configure_debug;
 pub use sales::config as configure_sales;
+pub use settings::configure as configure_settings;
+pub use shifts::configure as configure_shifts;
 
 use actix_web::web;
 
@@ -30,7 +42,13 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .configure(configure_products)
             .configure(configure_orders)
             .configure(configure_user)
-            .configure(configure_sales),
+            .configure(configure_sales)
+            .configure(configure_inventory)
+            .configure(configure_settings)
+            .configure(configure_customers)
+            .configure(configure_promotions)
+            .configure(configure_receivables)
+            .configure(configure_shifts),
     );
 
     // Configure user routes
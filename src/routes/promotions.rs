@@ -0,0 +1,12 @@
+use actix_web::web;
+use crate::handlers::promotion::{create_promotion, delete_promotion, get_promotions, update_promotion};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/promotions")
+            .route("", web::post().to(create_promotion))
+            .route("", web::get().to(get_promotions))
+            .route("/{id}", web::put().to(update_promotion))
+            .route("/{id}", web::delete().to(delete_promotion))
+    );
+}
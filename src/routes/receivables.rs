@@ -0,0 +1,12 @@
+use actix_web::web;
+use crate::handlers::receivable::{create_receivable_payment, get_aging_report, get_customer_receivables, get_order_receivable};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/receivables")
+            .route("/aging", web::get().to(get_aging_report))
+            .route("/orders/{id}", web::get().to(get_order_receivable))
+            .route("/orders/{id}/payments", web::post().to(create_receivable_payment))
+            .route("/customers/{id}", web::get().to(get_customer_receivables))
+    );
+}
@@ -1,5 +1,5 @@
 use actix_web::web;
-use crate::handlers::sales;
+use crate::handlers::{cart, offline_sync, profit_report, receipt, sales, sales_return, tax_report, z_report};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -18,17 +18,67 @@ pub fn config(cfg: &mut web::ServiceConfig) {
                     .route(web::delete().to(sales::delete_from_cart))
                     .route(web::put().to(sales::update_cart_item))
             )
+            .service(
+                web::resource("/carts")
+                    .route(web::post().to(cart::create_cart))
+                    .route(web::get().to(cart::list_carts))
+            )
+            .service(
+                web::resource("/carts/{id}")
+                    .route(web::delete().to(cart::discard_cart))
+            )
+            .service(
+                web::resource("/carts/{id}/hold")
+                    .route(web::post().to(cart::hold_cart))
+            )
+            .service(
+                web::resource("/carts/{id}/resume")
+                    .route(web::post().to(cart::resume_cart))
+            )
             .service(
                 web::resource("/orders")
                     .route(web::post().to(sales::create_order))  // Add POST route for creating orders
             )
+            .service(
+                web::resource("/sync")
+                    .route(web::post().to(offline_sync::sync_offline_orders))
+            )
             .service(
                 web::resource("/orders/{id}")
                     .route(web::get().to(sales::get_sales_order_by_id))  // Add GET route for fetching order by ID
             )
+            .service(
+                web::resource("/orders/{id}/receipt")
+                    .route(web::get().to(receipt::get_order_receipt))
+            )
+            .service(
+                web::resource("/orders/{id}/void")
+                    .route(web::post().to(sales::void_sales_order))
+            )
+            .service(
+                web::resource("/orders/{id}/returns")
+                    .route(web::post().to(sales_return::create_sales_return))
+                    .route(web::get().to(sales_return::get_order_returns))
+            )
             .service(
                 web::resource("/report")
                     .route(web::get().to(sales::get_sales_report))  // Add GET route for sales report
             )
+            .service(
+                web::resource("/profit-report")
+                    .route(web::get().to(profit_report::get_profit_report))
+            )
+            .service(
+                web::resource("/tax-report")
+                    .route(web::get().to(tax_report::get_tax_report))
+            )
+            .service(
+                web::resource("/z-report")
+                    .route(web::get().to(z_report::get_z_report))
+            )
+            .service(
+                web::resource("/z-report/close")
+                    .route(web::post().to(z_report::close_z_report))
+            )
     );
 }
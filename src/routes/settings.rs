@@ -0,0 +1,18 @@
+use actix_web::web;
+use crate::handlers::settings;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/settings")
+            .service(
+                web::resource("/company")
+                    .route(web::get().to(settings::get_company_settings))
+                    .route(web::put().to(settings::update_company_settings))
+            )
+            .service(
+                web::resource("/stores/{id}")
+                    .route(web::get().to(settings::get_store_settings))
+                    .route(web::put().to(settings::update_store_settings))
+            )
+    );
+}
@@ -0,0 +1,13 @@
+use actix_web::web;
+use crate::handlers::shift::{add_drawer_event, close_shift, get_current_shift, get_shift_by_id, open_shift};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/shifts")
+            .route("", web::post().to(open_shift))
+            .route("/current", web::get().to(get_current_shift))
+            .route("/{id}", web::get().to(get_shift_by_id))
+            .route("/{id}/cash-events", web::post().to(add_drawer_event))
+            .route("/{id}/close", web::post().to(close_shift))
+    );
+}
@@ -1,10 +1,15 @@
 use actix_web::web;
 
-use crate::handlers::user::get_user;
+use crate::handlers::user::{get_user, grant_user_permission, revoke_user_permission};
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/users")
-            .route("/get-user", web::get().to(get_user)),
+            .route("/get-user", web::get().to(get_user))
+            .service(
+                web::resource("/{id}/permissions/{permission}")
+                    .route(web::put().to(grant_user_permission))
+                    .route(web::delete().to(revoke_user_permission))
+            ),
     );
 }
@@ -0,0 +1,394 @@
+use crate::errors::ServiceError;
+use crate::models::cart::{
+    Cart, CartListQuery, CartSummary, HoldCartRequest, NewCartRequest, CART_STATUS_ACTIVE,
+    CART_STATUS_HELD,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::inventory_service::ensure_store_in_company;
+use log::{error, info};
+use sqlx::{PgExecutor, Postgres, Transaction};
+
+const CART_COLUMNS: &str = "id, user_id, store_id, name, status, held_at, created_at, updated_at";
+
+// Resolve the cart a cart call works on: the given cart when it belongs to the user and store,
+// otherwise the user's active cart in the store, if there is one
+pub async fn find_cart_id<'e, E>(
+    executor: E,
+    user_id: i32,
+    store_id: i32,
+    cart_id: Option<i32>,
+) -> Result<Option<i32>, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    let result = match cart_id {
+        Some(cart_id) => sqlx::query_scalar::<_, i32>(
+            "SELECT id FROM carts WHERE id = $1 AND user_id = $2 AND store_id = $3"
+        )
+        .bind(cart_id)
+        .bind(user_id)
+        .bind(store_id)
+        .fetch_optional(executor)
+        .await,
+        None => sqlx::query_scalar::<_, i32>(
+            "SELECT id FROM carts WHERE user_id = $1 AND store_id = $2 AND status = $3"
+        )
+        .bind(user_id)
+        .bind(store_id)
+        .bind(CART_STATUS_ACTIVE)
+        .fetch_optional(executor)
+        .await,
+    };
+
+    match result {
+        Ok(None) if cart_id.is_some() => {
+            info!("Cart {:?} not found for user {} in store {}", cart_id, user_id, store_id);
+            Err(ServiceError::NotFound)
+        }
+        Ok(found) => Ok(found),
+        Err(e) => {
+            error!("Database error while resolving cart for user {}: {}", user_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Like find_cart_id, but creates the active cart when there is none. Also bumps the cart's
+// updated_at so recently used carts sort first.
+pub async fn touch_cart_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    store_id: i32,
+    cart_id: Option<i32>,
+) -> Result<i32, ServiceError> {
+    let result = match cart_id {
+        Some(cart_id) => sqlx::query_scalar::<_, i32>(
+            "UPDATE carts SET updated_at = NOW()
+             WHERE id = $1 AND user_id = $2 AND store_id = $3
+             RETURNING id"
+        )
+        .bind(cart_id)
+        .bind(user_id)
+        .bind(store_id)
+        .fetch_optional(&mut **transaction)
+        .await,
+        None => sqlx::query_scalar::<_, i32>(
+            "INSERT INTO carts (user_id, store_id, status, created_at, updated_at)
+             VALUES ($1, $2, $3, NOW(), NOW())
+             ON CONFLICT (user_id, store_id) WHERE status = 'active'
+             DO UPDATE SET updated_at = NOW()
+             RETURNING id"
+        )
+        .bind(user_id)
+        .bind(store_id)
+        .bind(CART_STATUS_ACTIVE)
+        .fetch_optional(&mut **transaction)
+        .await,
+    };
+
+    match result {
+        Ok(Some(id)) => Ok(id),
+        Ok(None) => {
+            info!("Cart {:?} not found for user {} in store {}", cart_id, user_id, store_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while resolving cart for user {}: {}", user_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Park whatever cart is active for the user in the store, except the given one
+async fn hold_active_cart_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    store_id: i32,
+    except_cart_id: i32,
+) -> Result<(), ServiceError> {
+    match sqlx::query(
+        "UPDATE carts SET status = $1, held_at = NOW(), updated_at = NOW()
+         WHERE user_id = $2 AND store_id = $3 AND status = $4 AND id <> $5"
+    )
+    .bind(CART_STATUS_HELD)
+    .bind(user_id)
+    .bind(store_id)
+    .bind(CART_STATUS_ACTIVE)
+    .bind(except_cart_id)
+    .execute(&mut **transaction)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Database error while holding active cart for user {}: {}", user_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Start a new active cart, parking the current one
+pub async fn create_cart(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    company_id: i32,
+    new_cart: NewCartRequest,
+) -> Result<Cart, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    ensure_store_in_company(&mut transaction, new_cart.store_id, company_id).await?;
+    hold_active_cart_tx(&mut transaction, user_id, new_cart.store_id, 0).await?;
+
+    let cart = match sqlx::query_as::<_, Cart>(&format!(
+        "INSERT INTO carts (user_id, store_id, name, status, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, NOW(), NOW())
+         RETURNING {CART_COLUMNS}"
+    ))
+    .bind(user_id)
+    .bind(new_cart.store_id)
+    .bind(&new_cart.name)
+    .bind(CART_STATUS_ACTIVE)
+    .fetch_one(&mut *transaction)
+    .await
+    {
+        Ok(cart) => cart,
+        Err(e) => {
+            error!("Database error while creating cart: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Created cart ID {} for user {} in store {}", cart.id, user_id, cart.store_id);
+    Ok(cart)
+}
+
+pub async fn list_carts(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    query: CartListQuery,
+) -> Result<Vec<CartSummary>, ServiceError> {
+    if let Some(status) = query.status.as_deref() {
+        if status != CART_STATUS_ACTIVE && status != CART_STATUS_HELD {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown cart status '{status}', expected active or held"
+            )));
+        }
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let carts = match sqlx::query_as::<_, CartSummary>(
+        "SELECT c.id, c.store_id, c.name, c.status, c.held_at, c.created_at, c.updated_at,
+                COUNT(sc.id) as item_count,
+                COALESCE(SUM(sc.qty), 0)::int8 as total_qty,
+                COALESCE(SUM(sc.sale_price * sc.qty), 0) as total_amount
+         FROM carts c
+         LEFT JOIN sales_cart sc ON sc.cart_id = c.id
+         WHERE c.user_id = $1 AND c.store_id = $2
+         AND ($3::text IS NULL OR c.status = $3)
+         GROUP BY c.id
+         ORDER BY c.status = 'active' DESC, c.updated_at DESC"
+    )
+    .bind(user_id)
+    .bind(query.store_id)
+    .bind(&query.status)
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(carts) => carts,
+        Err(e) => {
+            error!("Database error while listing carts: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Retrieved {} carts for user {} in store {}", carts.len(), user_id, query.store_id);
+    Ok(carts)
+}
+
+// Lock one of the user's carts
+async fn fetch_cart_for_update(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    cart_id: i32,
+) -> Result<Cart, ServiceError> {
+    match sqlx::query_as::<_, Cart>(&format!(
+        "SELECT {CART_COLUMNS} FROM carts WHERE id = $1 AND user_id = $2 FOR UPDATE"
+    ))
+    .bind(cart_id)
+    .bind(user_id)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(Some(cart)) => Ok(cart),
+        Ok(None) => {
+            info!("Cart ID {} not found for user {}", cart_id, user_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while fetching cart {}: {}", cart_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Park a cart. Its lines keep their stock reservations until they expire; checkout re-checks stock.
+pub async fn hold_cart(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    cart_id: i32,
+    hold_data: HoldCartRequest,
+) -> Result<Cart, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let cart = match sqlx::query_as::<_, Cart>(&format!(
+        "UPDATE carts
+         SET status = $1, held_at = COALESCE(held_at, NOW()), name = COALESCE($2, name), updated_at = NOW()
+         WHERE id = $3 AND user_id = $4
+         RETURNING {CART_COLUMNS}"
+    ))
+    .bind(CART_STATUS_HELD)
+    .bind(&hold_data.name)
+    .bind(cart_id)
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(Some(cart)) => cart,
+        Ok(None) => {
+            info!("Cart ID {} not found for user {}", cart_id, user_id);
+            return Err(ServiceError::NotFound);
+        }
+        Err(e) => {
+            error!("Database error while holding cart {}: {}", cart_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Held cart ID {} for user {}", cart_id, user_id);
+    Ok(cart)
+}
+
+// Make a held cart the active one again, parking the cart that was active
+pub async fn resume_cart(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    cart_id: i32,
+) -> Result<Cart, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let cart = fetch_cart_for_update(&mut transaction, user_id, cart_id).await?;
+    if cart.status == CART_STATUS_ACTIVE {
+        if let Err(rollback_err) = transaction.rollback().await {
+            error!("Failed to rollback transaction: {:?}", rollback_err);
+        }
+        return Ok(cart);
+    }
+
+    hold_active_cart_tx(&mut transaction, user_id, cart.store_id, cart_id).await?;
+
+    let cart = match sqlx::query_as::<_, Cart>(&format!(
+        "UPDATE carts SET status = $1, held_at = NULL, updated_at = NOW()
+         WHERE id = $2
+         RETURNING {CART_COLUMNS}"
+    ))
+    .bind(CART_STATUS_ACTIVE)
+    .bind(cart_id)
+    .fetch_one(&mut *transaction)
+    .await
+    {
+        Ok(cart) => cart,
+        Err(e) => {
+            error!("Database error while resuming cart {}: {}", cart_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Resumed cart ID {} for user {}", cart_id, user_id);
+    Ok(cart)
+}
+
+// Delete a cart with its lines; their stock reservations go with them through the cascade
+pub async fn discard_cart(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    cart_id: i32,
+) -> Result<bool, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let result = match sqlx::query("DELETE FROM carts WHERE id = $1 AND user_id = $2")
+        .bind(cart_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Database error while discarding cart {}: {}", cart_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let deleted = result.rows_affected() > 0;
+    if deleted {
+        info!("Discarded cart ID {} for user {}", cart_id, user_id);
+    } else {
+        info!("No cart found with ID: {} for user: {}", cart_id, user_id);
+    }
+    Ok(deleted)
+}
@@ -0,0 +1,261 @@
+use crate::errors::ServiceError;
+use crate::models::customer::{Customer, CustomerQueryParams, NewCustomer, UpdateCustomer};
+use crate::models::product::PaginatedResponse;
+use crate::services::db_service::DbConnectionManager;
+use log::{error, info};
+use sqlx::{Postgres, Transaction};
+
+fn validate_name(name: &str) -> Result<(), ServiceError> {
+    if name.trim().is_empty() {
+        return Err(ServiceError::ValidationError("Customer name is required".to_string()));
+    }
+    Ok(())
+}
+
+// Check that a customer exists, is not deleted and belongs to the given company
+pub async fn customer_exists_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    customer_id: i32,
+    company_id: i32,
+) -> Result<bool, ServiceError> {
+    match sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM customers WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL"
+    )
+    .bind(customer_id)
+    .bind(company_id)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(found) => Ok(found.is_some()),
+        Err(e) => {
+            error!("Database error while checking customer {}: {}", customer_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn create_customer(
+    db_manager: &DbConnectionManager,
+    new_customer: NewCustomer,
+    company_id: i32,
+) -> Result<Customer, ServiceError> {
+    validate_name(&new_customer.name)?;
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let customer = match sqlx::query_as::<_, Customer>(
+        "INSERT INTO customers (
+            company_id, name, phone, email, address, notes, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+        RETURNING id, company_id, name, phone, email, address, notes, created_at, updated_at"
+    )
+    .bind(company_id)
+    .bind(new_customer.name.trim())
+    .bind(&new_customer.phone)
+    .bind(&new_customer.email)
+    .bind(&new_customer.address)
+    .bind(&new_customer.notes)
+    .fetch_one(&pool)
+    .await {
+        Ok(customer) => customer,
+        Err(e) => {
+            error!("Database error while creating customer: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Customer created successfully with ID: {}", customer.id);
+    Ok(customer)
+}
+
+pub async fn get_customers(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    params: CustomerQueryParams,
+) -> Result<PaginatedResponse<Customer>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Default pagination values
+    let page = params.page.unwrap_or(1);
+    let size = params.size.unwrap_or(10);
+    let offset = (page - 1) * size;
+    let search_term = params.search.as_ref().map(|term| format!("%{term}%"));
+
+    // Get total count
+    let total: i64 = match sqlx::query_scalar(
+        "SELECT COUNT(*) FROM customers
+         WHERE company_id = $1 AND deleted_at IS NULL
+         AND ($2::text IS NULL OR name ILIKE $2::text OR phone ILIKE $2::text)"
+    )
+    .bind(company_id)
+    .bind(&search_term)
+    .fetch_one(&pool)
+    .await {
+        Ok(count) => count,
+        Err(e) => {
+            error!("Database error while counting customers: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Get paginated results
+    let rows = match sqlx::query_as::<_, Customer>(
+        "SELECT id, company_id, name, phone, email, address, notes, created_at, updated_at
+         FROM customers
+         WHERE company_id = $1 AND deleted_at IS NULL
+         AND ($2::text IS NULL OR name ILIKE $2::text OR phone ILIKE $2::text)
+         ORDER BY name ASC LIMIT $3::int4 OFFSET $4::int4"
+    )
+    .bind(company_id)
+    .bind(&search_term)
+    .bind(size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Database error while fetching customers: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Retrieved {} customers for company_id {}", rows.len(), company_id);
+    Ok(PaginatedResponse::new(page, size, total, rows))
+}
+
+pub async fn get_customer_by_id(
+    db_manager: &DbConnectionManager,
+    customer_id: i32,
+    company_id: i32, // Use company_id to ensure user can only access customers from their company
+) -> Result<Customer, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    match sqlx::query_as::<_, Customer>(
+        "SELECT id, company_id, name, phone, email, address, notes, created_at, updated_at
+         FROM customers
+         WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL"
+    )
+    .bind(customer_id)
+    .bind(company_id)
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(Some(customer)) => Ok(customer),
+        Ok(None) => {
+            info!("Customer with ID {} not found for company_id {}", customer_id, company_id);
+            Err(ServiceError::NotFound)
+        },
+        Err(e) => {
+            error!("Database error while fetching customer by ID {}: {}", customer_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn update_customer(
+    db_manager: &DbConnectionManager,
+    customer_id: i32,
+    company_id: i32,
+    update_data: UpdateCustomer,
+) -> Result<Customer, ServiceError> {
+    if let Some(name) = &update_data.name {
+        validate_name(name)?;
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Fields that are not provided keep their current value
+    match sqlx::query_as::<_, Customer>(
+        "UPDATE customers
+         SET name = COALESCE($1, name), phone = COALESCE($2, phone), email = COALESCE($3, email),
+             address = COALESCE($4, address), notes = COALESCE($5, notes), updated_at = NOW()
+         WHERE id = $6 AND company_id = $7 AND deleted_at IS NULL
+         RETURNING id, company_id, name, phone, email, address, notes, created_at, updated_at"
+    )
+    .bind(update_data.name.as_deref().map(str::trim))
+    .bind(&update_data.phone)
+    .bind(&update_data.email)
+    .bind(&update_data.address)
+    .bind(&update_data.notes)
+    .bind(customer_id)
+    .bind(company_id)
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(Some(customer)) => {
+            info!("Successfully updated customer with ID: {}", customer_id);
+            Ok(customer)
+        },
+        Ok(None) => {
+            info!("Customer with ID {} not found for company_id {}", customer_id, company_id);
+            Err(ServiceError::NotFound)
+        },
+        Err(e) => {
+            error!("Database error while updating customer {}: {}", customer_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn delete_customer(
+    db_manager: &DbConnectionManager,
+    customer_id: i32,
+    company_id: i32,
+) -> Result<bool, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Soft delete so existing orders keep their customer reference
+    let result = match sqlx::query(
+        "UPDATE customers SET deleted_at = NOW(), updated_at = NOW()
+         WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL"
+    )
+    .bind(customer_id)
+    .bind(company_id)
+    .execute(&pool)
+    .await {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Database error while deleting customer {}: {}", customer_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let deleted = result.rows_affected() > 0;
+    if deleted {
+        info!("Successfully deleted customer with ID: {}", customer_id);
+    } else {
+        info!("No customer found with ID: {} for company_id: {}", customer_id, company_id);
+    }
+
+    Ok(deleted)
+}
@@ -0,0 +1,271 @@
+use crate::errors::ServiceError;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+// Discount types accepted on cart and order lines
+pub const DISCOUNT_TYPE_NONE: &str = "none";
+pub const DISCOUNT_TYPE_PERCENTAGE: &str = "percentage";
+pub const DISCOUNT_TYPE_FIXED: &str = "fixed";
+
+// Per-unit pricing of a line after its discount
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiscount {
+    // Unit price before the discount, rounded to the minor unit
+    pub base_price: Decimal,
+    pub discount_type: &'static str,
+    pub discount_value: Decimal,
+    // Amount taken off one unit, never more than the base price
+    pub discount_amount: Decimal,
+    pub sale_price: Decimal,
+}
+
+// Round an amount to the currency's minor unit, halves away from zero
+#[must_use]
+pub fn round_currency(amount: Decimal, decimals: u32) -> Decimal {
+    amount.round_dp_with_strategy(decimals, RoundingStrategy::MidpointAwayFromZero)
+}
+
+// An amount for printing, with exactly the currency's number of decimals
+#[must_use]
+pub fn format_currency(amount: Decimal, decimals: u32) -> String {
+    let mut amount = round_currency(amount, decimals);
+    amount.rescale(decimals);
+    amount.to_string()
+}
+
+// Price one unit of a line. A missing type means "fixed" when a value is given and "none"
+// otherwise. Percentages go from 0 to 100; a fixed discount larger than the price brings the
+// sale price down to zero rather than below it.
+pub fn apply_line_discount(
+    base_price: Decimal,
+    discount_type: Option<&str>,
+    discount_value: Option<Decimal>,
+    decimals: u32,
+) -> Result<LineDiscount, ServiceError> {
+    if base_price < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Price cannot be negative".to_string()));
+    }
+    let value = discount_value.unwrap_or(Decimal::ZERO);
+    if value < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Discount cannot be negative".to_string()));
+    }
+
+    let discount_type = match discount_type {
+        Some(DISCOUNT_TYPE_NONE) => DISCOUNT_TYPE_NONE,
+        Some(DISCOUNT_TYPE_PERCENTAGE) => DISCOUNT_TYPE_PERCENTAGE,
+        Some(DISCOUNT_TYPE_FIXED) => DISCOUNT_TYPE_FIXED,
+        Some(other) => {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown discount type '{other}', expected none, percentage or fixed"
+            )));
+        }
+        None if value.is_zero() => DISCOUNT_TYPE_NONE,
+        None => DISCOUNT_TYPE_FIXED,
+    };
+
+    let base_price = round_currency(base_price, decimals);
+    let discount_amount = match discount_type {
+        DISCOUNT_TYPE_PERCENTAGE => {
+            if value > Decimal::ONE_HUNDRED {
+                return Err(ServiceError::ValidationError(
+                    "Percentage discount cannot exceed 100".to_string(),
+                ));
+            }
+            round_currency(base_price * value / Decimal::ONE_HUNDRED, decimals)
+        }
+        DISCOUNT_TYPE_FIXED => round_currency(value, decimals).min(base_price),
+        _ => {
+            if !value.is_zero() {
+                return Err(ServiceError::ValidationError(
+                    "A discount value needs a discount type".to_string(),
+                ));
+            }
+            Decimal::ZERO
+        }
+    };
+
+    Ok(LineDiscount {
+        base_price,
+        discount_type,
+        discount_value: value,
+        discount_amount,
+        sale_price: base_price - discount_amount,
+    })
+}
+
+// Split an order-level amount across lines in proportion to their totals. Each share is
+// rounded to the minor unit and the rounding difference goes to the largest line, so the
+// shares always add up to the amount.
+#[must_use]
+pub fn prorate_amount(amount: Decimal, line_totals: &[Decimal], decimals: u32) -> Vec<Decimal> {
+    let total: Decimal = line_totals.iter().copied().sum();
+    if total.is_zero() {
+        return vec![Decimal::ZERO; line_totals.len()];
+    }
+
+    let mut shares: Vec<Decimal> = line_totals
+        .iter()
+        .map(|line_total| round_currency(amount * *line_total / total, decimals))
+        .collect();
+    let remainder = amount - shares.iter().copied().sum::<Decimal>();
+    if let Some(largest) = line_totals
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, line_total)| **line_total)
+        .map(|(index, _)| index)
+    {
+        shares[largest] += remainder;
+    }
+    shares
+}
+
+// Round a total to the nearest multiple of the cash rounding unit, halves away from zero.
+// A unit of zero leaves the total as it is.
+#[must_use]
+pub fn round_to_cash_unit(amount: Decimal, unit: Decimal) -> Decimal {
+    if unit <= Decimal::ZERO {
+        return amount;
+    }
+    (amount / unit).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero) * unit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn no_discount_keeps_base_price() {
+        let line = apply_line_discount(dec("15.99"), None, None, 2).unwrap();
+        assert_eq!(line.discount_type, DISCOUNT_TYPE_NONE);
+        assert_eq!(line.discount_amount, Decimal::ZERO);
+        assert_eq!(line.sale_price, dec("15.99"));
+    }
+
+    #[test]
+    fn explicit_none_with_zero_value_is_accepted() {
+        let line = apply_line_discount(dec("10"), Some("none"), Some(Decimal::ZERO), 2).unwrap();
+        assert_eq!(line.sale_price, dec("10"));
+    }
+
+    #[test]
+    fn none_with_a_value_is_rejected() {
+        assert!(apply_line_discount(dec("10"), Some("none"), Some(dec("1")), 2).is_err());
+    }
+
+    #[test]
+    fn value_without_type_is_fixed() {
+        let line = apply_line_discount(dec("10"), None, Some(dec("2.50")), 2).unwrap();
+        assert_eq!(line.discount_type, DISCOUNT_TYPE_FIXED);
+        assert_eq!(line.sale_price, dec("7.50"));
+    }
+
+    #[test]
+    fn fixed_discount_keeps_cents() {
+        let line = apply_line_discount(dec("15.99"), Some("fixed"), Some(dec("0.99")), 2).unwrap();
+        assert_eq!(line.discount_amount, dec("0.99"));
+        assert_eq!(line.sale_price, dec("15.00"));
+    }
+
+    #[test]
+    fn fixed_discount_above_price_clamps_to_zero() {
+        let line = apply_line_discount(dec("5.00"), Some("fixed"), Some(dec("7.25")), 2).unwrap();
+        assert_eq!(line.discount_amount, dec("5.00"));
+        assert_eq!(line.sale_price, Decimal::ZERO);
+        assert_eq!(line.discount_value, dec("7.25"));
+    }
+
+    #[test]
+    fn fixed_discount_is_rounded_to_minor_unit() {
+        let line = apply_line_discount(dec("10"), Some("fixed"), Some(dec("1.005")), 2).unwrap();
+        assert_eq!(line.discount_amount, dec("1.01"));
+        assert_eq!(line.sale_price, dec("8.99"));
+    }
+
+    #[test]
+    fn percentage_rounds_half_away_from_zero() {
+        // 10% of 15.99 is 1.599
+        let line = apply_line_discount(dec("15.99"), Some("percentage"), Some(dec("10")), 2).unwrap();
+        assert_eq!(line.discount_amount, dec("1.60"));
+        assert_eq!(line.sale_price, dec("14.39"));
+
+        // 50% of 0.05 is 0.025
+        let line = apply_line_discount(dec("0.05"), Some("percentage"), Some(dec("50")), 2).unwrap();
+        assert_eq!(line.discount_amount, dec("0.03"));
+        assert_eq!(line.sale_price, dec("0.02"));
+    }
+
+    #[test]
+    fn percentage_accepts_decimal_values() {
+        let line = apply_line_discount(dec("200"), Some("percentage"), Some(dec("12.5")), 2).unwrap();
+        assert_eq!(line.discount_amount, dec("25.00"));
+        assert_eq!(line.sale_price, dec("175.00"));
+    }
+
+    #[test]
+    fn full_percentage_discount_is_free() {
+        let line = apply_line_discount(dec("9.99"), Some("percentage"), Some(dec("100")), 2).unwrap();
+        assert_eq!(line.sale_price, Decimal::ZERO);
+    }
+
+    #[test]
+    fn percentage_above_hundred_is_rejected() {
+        assert!(apply_line_discount(dec("10"), Some("percentage"), Some(dec("100.01")), 2).is_err());
+    }
+
+    #[test]
+    fn negative_values_are_rejected() {
+        assert!(apply_line_discount(dec("10"), Some("fixed"), Some(dec("-1")), 2).is_err());
+        assert!(apply_line_discount(dec("10"), Some("percentage"), Some(dec("-5")), 2).is_err());
+        assert!(apply_line_discount(dec("-10"), None, None, 2).is_err());
+    }
+
+    #[test]
+    fn unknown_type_is_rejected() {
+        assert!(apply_line_discount(dec("10"), Some("bogo"), Some(dec("1")), 2).is_err());
+    }
+
+    #[test]
+    fn zero_decimal_currency_rounds_to_whole_units() {
+        // 15% of Rp 9,999 is 1,499.85
+        let line = apply_line_discount(dec("9999"), Some("percentage"), Some(dec("15")), 0).unwrap();
+        assert_eq!(line.discount_amount, dec("1500"));
+        assert_eq!(line.sale_price, dec("8499"));
+    }
+
+    #[test]
+    fn round_currency_handles_negative_halves() {
+        assert_eq!(round_currency(dec("-0.125"), 2), dec("-0.13"));
+        assert_eq!(round_currency(dec("2.5"), 0), dec("3"));
+    }
+
+    #[test]
+    fn prorated_shares_add_up_to_the_amount() {
+        // 10.00 over three equal lines is 3.33 each plus a cent left over
+        let shares = prorate_amount(dec("10.00"), &[dec("30"), dec("30"), dec("30")], 2);
+        assert_eq!(shares.iter().copied().sum::<Decimal>(), dec("10.00"));
+        assert_eq!(shares, vec![dec("3.33"), dec("3.33"), dec("3.34")]);
+    }
+
+    #[test]
+    fn prorated_remainder_goes_to_the_largest_line() {
+        let shares = prorate_amount(dec("1.00"), &[dec("1"), dec("5"), dec("1")], 2);
+        assert_eq!(shares, vec![dec("0.14"), dec("0.72"), dec("0.14")]);
+    }
+
+    #[test]
+    fn prorating_over_zero_totals_gives_nothing() {
+        assert_eq!(prorate_amount(dec("5"), &[Decimal::ZERO, Decimal::ZERO], 2), vec![Decimal::ZERO; 2]);
+    }
+
+    #[test]
+    fn cash_rounding_goes_to_the_nearest_unit() {
+        assert_eq!(round_to_cash_unit(dec("15249"), dec("100")), dec("15200"));
+        assert_eq!(round_to_cash_unit(dec("15250"), dec("100")), dec("15300"));
+        assert_eq!(round_to_cash_unit(dec("12.37"), dec("0.05")), dec("12.35"));
+        assert_eq!(round_to_cash_unit(dec("12.37"), Decimal::ZERO), dec("12.37"));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::errors::ServiceError;
+use log::{error, info};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::types::Json;
+use sqlx::{Postgres, Transaction};
+
+// Scopes keep keys used on different endpoints apart
+pub const IDEMPOTENCY_SCOPE_CREATE_ORDER: &str = "create_order";
+
+pub fn validate_idempotency_key(key: &str) -> Result<(), ServiceError> {
+    if key.trim().is_empty() || key.len() > 255 {
+        return Err(ServiceError::ValidationError(
+            "Idempotency-Key must be between 1 and 255 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Claim a key for the current transaction. Returns the stored response when the key was
+// already used by a committed request, or None when this request owns the key now.
+// A concurrent request with the same key blocks on the insert until the first one commits
+// or rolls back.
+pub async fn claim_idempotency_key_tx<T: DeserializeOwned + Send + Unpin + 'static>(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    scope: &str,
+    key: &str,
+) -> Result<Option<T>, ServiceError> {
+    let claimed = match sqlx::query_scalar::<_, i32>(
+        "INSERT INTO idempotency_keys (user_id, scope, idempotency_key, created_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (user_id, scope, idempotency_key) DO NOTHING
+         RETURNING user_id"
+    )
+    .bind(user_id)
+    .bind(scope)
+    .bind(key)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(claimed) => claimed.is_some(),
+        Err(e) => {
+            error!("Database error while claiming idempotency key: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if claimed {
+        return Ok(None);
+    }
+
+    match sqlx::query_scalar::<_, Option<Json<T>>>(
+        "SELECT response FROM idempotency_keys
+         WHERE user_id = $1 AND scope = $2 AND idempotency_key = $3"
+    )
+    .bind(user_id)
+    .bind(scope)
+    .bind(key)
+    .fetch_one(&mut **transaction)
+    .await
+    {
+        Ok(Some(response)) => {
+            info!("Replaying stored {} response for idempotency key {}", scope, key);
+            Ok(Some(response.0))
+        }
+        Ok(None) => {
+            // Only possible if a request committed without storing its response
+            error!("Idempotency key {} for user {} has no stored response", key, user_id);
+            Err(ServiceError::DatabaseError("Idempotency key has no stored response".to_string()))
+        }
+        Err(e) => {
+            error!("Database error while reading idempotency key: {}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Store the response of the request that claimed the key, in the same transaction
+pub async fn store_idempotent_response_tx<T: Serialize + Sync>(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    scope: &str,
+    key: &str,
+    response: &T,
+) -> Result<(), ServiceError> {
+    match sqlx::query(
+        "UPDATE idempotency_keys SET response = $4
+         WHERE user_id = $1 AND scope = $2 AND idempotency_key = $3"
+    )
+    .bind(user_id)
+    .bind(scope)
+    .bind(key)
+    .bind(Json(response))
+    .execute(&mut **transaction)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Database error while storing idempotent response: {}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,587 @@
+use crate::errors::ServiceError;
+use crate::models::inventory::{
+    NewStockMovement, StockMovement, StockMovementQueryParams, StockQueryParams, StockShortage,
+    StoreStock, MOVEMENT_ADJUSTMENT, MOVEMENT_RECEIPT, MOVEMENT_RETURN, MOVEMENT_SALE, MOVEMENT_TRANSFER,
+};
+use crate::models::product::PaginatedResponse;
+use crate::services::db_service::DbConnectionManager;
+use log::{error, info};
+use sqlx::{FromRow, Postgres, Transaction};
+use std::collections::BTreeMap;
+
+// A single ledger line to be written by record_movement_tx
+pub struct LedgerEntry<'a> {
+    pub store_id: i32,
+    pub product_id: i32,
+    pub movement_type: &'a str,
+    pub qty: i32,
+    pub reference_type: Option<&'a str>,
+    pub reference_id: Option<i32>,
+    pub note: Option<&'a str>,
+    pub user_id: i32,
+}
+
+// Apply a movement to store_stock and append it to the ledger within an existing transaction.
+// Callers own the transaction so the ledger always commits or rolls back with the business document.
+pub async fn record_movement_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    entry: &LedgerEntry<'_>,
+) -> Result<StockMovement, ServiceError> {
+    let balance_after: i32 = match sqlx::query_scalar(
+        "INSERT INTO store_stock (store_id, product_id, qty, updated_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (store_id, product_id)
+         DO UPDATE SET qty = store_stock.qty + EXCLUDED.qty, updated_at = NOW()
+         RETURNING qty"
+    )
+    .bind(entry.store_id)
+    .bind(entry.product_id)
+    .bind(entry.qty)
+    .fetch_one(&mut **transaction)
+    .await {
+        Ok(qty) => qty,
+        Err(e) => {
+            error!("Database error while updating store stock: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let movement = match sqlx::query_as::<_, StockMovement>(
+        "INSERT INTO stock_movements (
+            store_id, product_id, movement_type, qty, balance_after,
+            reference_type, reference_id, note, user_id, created_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+        RETURNING id, store_id, product_id, movement_type, qty, balance_after,
+                 reference_type, reference_id, note, user_id, created_at"
+    )
+    .bind(entry.store_id)
+    .bind(entry.product_id)
+    .bind(entry.movement_type)
+    .bind(entry.qty)
+    .bind(balance_after)
+    .bind(entry.reference_type)
+    .bind(entry.reference_id)
+    .bind(entry.note)
+    .bind(entry.user_id)
+    .fetch_one(&mut **transaction)
+    .await {
+        Ok(movement) => movement,
+        Err(e) => {
+            error!("Database error while recording stock movement: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!(
+        "Recorded {} movement of {} for product {} in store {} (balance: {})",
+        movement.movement_type, movement.qty, movement.product_id, movement.store_id, balance_after
+    );
+    Ok(movement)
+}
+
+// Stock position of one product in one store, used by the availability check
+#[derive(FromRow)]
+struct AvailabilityRow {
+    product_name: String,
+    on_hand: i32,
+    reserved: i32,
+}
+
+// Check whether the store can cover the requested (product_id, qty) pairs and return the shortfalls.
+// Each product is locked for the rest of the transaction so concurrent carts cannot both take the
+// last unit. Active reservations held by `own_cart_item_ids` are not counted against availability,
+// which lets callers re-check cart lines they have already reserved.
+pub async fn check_availability_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    store_id: i32,
+    requested: &[(i32, i32)],
+    own_cart_item_ids: &[i32],
+) -> Result<Vec<StockShortage>, ServiceError> {
+    // Sum duplicate lines per product; BTreeMap keeps the lock order stable across transactions
+    let mut requested_by_product: BTreeMap<i32, i32> = BTreeMap::new();
+    for (product_id, qty) in requested {
+        *requested_by_product.entry(*product_id).or_insert(0) += qty;
+    }
+
+    let mut shortages = Vec::new();
+    for (product_id, requested_qty) in requested_by_product {
+        if let Err(e) = sqlx::query("SELECT pg_advisory_xact_lock($1, $2)")
+            .bind(store_id)
+            .bind(product_id)
+            .execute(&mut **transaction)
+            .await
+        {
+            error!("Failed to lock stock for product {} in store {}: {}", product_id, store_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+
+        let row = match sqlx::query_as::<_, AvailabilityRow>(
+            "SELECT p.name as product_name,
+                    COALESCE(ss.qty, 0) as on_hand,
+                    COALESCE((
+                        SELECT SUM(r.qty) FROM stock_reservations r
+                        WHERE r.store_id = $1 AND r.product_id = p.id
+                        AND r.expires_at > NOW()
+                        AND NOT (r.cart_item_id = ANY($3))
+                    ), 0)::int4 as reserved
+             FROM products p
+             LEFT JOIN store_stock ss ON ss.store_id = $1 AND ss.product_id = p.id
+             WHERE p.id = $2"
+        )
+        .bind(store_id)
+        .bind(product_id)
+        .bind(own_cart_item_ids)
+        .fetch_optional(&mut **transaction)
+        .await {
+            Ok(Some(row)) => row,
+            Ok(None) => {
+                info!("Product {} not found while checking availability", product_id);
+                return Err(ServiceError::NotFound);
+            }
+            Err(e) => {
+                error!("Database error while checking availability of product {}: {}", product_id, e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
+            }
+        };
+
+        let available_qty = row.on_hand - row.reserved;
+        if requested_qty > available_qty {
+            info!(
+                "Insufficient stock for product {} in store {}: requested {}, available {}",
+                product_id, store_id, requested_qty, available_qty
+            );
+            shortages.push(StockShortage {
+                product_id,
+                product_name: row.product_name,
+                requested_qty,
+                available_qty,
+            });
+        }
+    }
+
+    Ok(shortages)
+}
+
+// Create or refresh the reservation held by a cart line
+pub async fn reserve_cart_item_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    cart_item_id: i32,
+    store_id: i32,
+    product_id: i32,
+    qty: i32,
+    ttl_minutes: i32,
+) -> Result<(), ServiceError> {
+    if let Err(e) = sqlx::query(
+        "INSERT INTO stock_reservations (cart_item_id, store_id, product_id, qty, expires_at, created_at)
+         VALUES ($1, $2, $3, $4, NOW() + make_interval(mins => $5), NOW())
+         ON CONFLICT (cart_item_id) DO UPDATE
+         SET qty = EXCLUDED.qty, expires_at = EXCLUDED.expires_at"
+    )
+    .bind(cart_item_id)
+    .bind(store_id)
+    .bind(product_id)
+    .bind(qty)
+    .bind(ttl_minutes)
+    .execute(&mut **transaction)
+    .await
+    {
+        error!("Database error while reserving stock for cart item {}: {}", cart_item_id, e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Reserved {} of product {} for cart item {}", qty, product_id, cart_item_id);
+    Ok(())
+}
+
+// Drop the reservations of the given cart lines, e.g. when they are converted into a sale
+pub async fn release_reservations_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    cart_item_ids: &[i32],
+) -> Result<u64, ServiceError> {
+    match sqlx::query("DELETE FROM stock_reservations WHERE cart_item_id = ANY($1)")
+        .bind(cart_item_ids)
+        .execute(&mut **transaction)
+        .await
+    {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(e) => {
+            error!("Database error while releasing stock reservations: {}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Helper function to check that a store belongs to the given company
+pub async fn ensure_store_in_company(
+    transaction: &mut Transaction<'_, Postgres>,
+    store_id: i32,
+    company_id: i32,
+) -> Result<(), ServiceError> {
+    match sqlx::query_scalar::<_, i32>("SELECT id FROM stores WHERE id = $1 AND company_id = $2")
+        .bind(store_id)
+        .bind(company_id)
+        .fetch_optional(&mut **transaction)
+        .await
+    {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            info!("Store {} not found for company_id {}", store_id, company_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while checking store {}: {}", store_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Helper function to check that a product belongs to the given company and is not deleted
+async fn ensure_product_in_company(
+    transaction: &mut Transaction<'_, Postgres>,
+    product_id: i32,
+    company_id: i32,
+) -> Result<(), ServiceError> {
+    match sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM products WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL"
+    )
+    .bind(product_id)
+    .bind(company_id)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            info!("Product {} not found for company_id {}", product_id, company_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while checking product {}: {}", product_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+fn validate_new_movement(movement: &NewStockMovement) -> Result<(), ServiceError> {
+    match movement.movement_type.as_str() {
+        MOVEMENT_ADJUSTMENT => {
+            if movement.qty == 0 {
+                return Err(ServiceError::ValidationError(
+                    "Adjustment quantity must not be zero".to_string(),
+                ));
+            }
+        }
+        MOVEMENT_RECEIPT | MOVEMENT_RETURN => {
+            if movement.qty <= 0 {
+                return Err(ServiceError::ValidationError(format!(
+                    "Quantity for {} must be greater than zero",
+                    movement.movement_type.as_str()
+                )));
+            }
+        }
+        MOVEMENT_TRANSFER => {
+            if movement.qty <= 0 {
+                return Err(ServiceError::ValidationError(
+                    "Transfer quantity must be greater than zero".to_string(),
+                ));
+            }
+            match movement.to_store_id {
+                None => {
+                    return Err(ServiceError::ValidationError(
+                        "to_store_id is required for transfers".to_string(),
+                    ));
+                }
+                Some(to_store_id) if to_store_id == movement.store_id => {
+                    return Err(ServiceError::ValidationError(
+                        "Cannot transfer stock to the same store".to_string(),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        MOVEMENT_SALE => {
+            return Err(ServiceError::ValidationError(
+                "Sale movements are recorded by checkout and cannot be created manually".to_string(),
+            ));
+        }
+        other => {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown movement type: {other}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn create_stock_movement(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    company_id: i32,
+    new_movement: NewStockMovement,
+) -> Result<Vec<StockMovement>, ServiceError> {
+    validate_new_movement(&new_movement)?;
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    ensure_store_in_company(&mut transaction, new_movement.store_id, company_id).await?;
+    ensure_product_in_company(&mut transaction, new_movement.product_id, company_id).await?;
+
+    let mut movements = Vec::new();
+
+    if new_movement.movement_type == MOVEMENT_TRANSFER {
+        // validate_new_movement guarantees a destination for transfers
+        let to_store_id = new_movement.to_store_id.unwrap_or_default();
+        ensure_store_in_company(&mut transaction, to_store_id, company_id).await?;
+
+        // Each side of the transfer references the other store
+        let outgoing = LedgerEntry {
+            store_id: new_movement.store_id,
+            product_id: new_movement.product_id,
+            movement_type: MOVEMENT_TRANSFER,
+            qty: -new_movement.qty,
+            reference_type: Some("store"),
+            reference_id: Some(to_store_id),
+            note: new_movement.note.as_deref(),
+            user_id,
+        };
+        let incoming = LedgerEntry {
+            store_id: to_store_id,
+            product_id: new_movement.product_id,
+            movement_type: MOVEMENT_TRANSFER,
+            qty: new_movement.qty,
+            reference_type: Some("store"),
+            reference_id: Some(new_movement.store_id),
+            note: new_movement.note.as_deref(),
+            user_id,
+        };
+
+        movements.push(record_movement_tx(&mut transaction, &outgoing).await?);
+        movements.push(record_movement_tx(&mut transaction, &incoming).await?);
+    } else {
+        let entry = LedgerEntry {
+            store_id: new_movement.store_id,
+            product_id: new_movement.product_id,
+            movement_type: &new_movement.movement_type,
+            qty: new_movement.qty,
+            reference_type: None,
+            reference_id: None,
+            note: new_movement.note.as_deref(),
+            user_id,
+        };
+
+        movements.push(record_movement_tx(&mut transaction, &entry).await?);
+    }
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Created {} stock movement(s) for product {}", movements.len(), new_movement.product_id);
+    Ok(movements)
+}
+
+// Pages start at 1 and hold at most 100 rows
+fn validate_pagination(page: i32, size: i32) -> Result<(), ServiceError> {
+    if page < 1 {
+        return Err(ServiceError::ValidationError("page must be at least 1".to_string()));
+    }
+    if !(1..=100).contains(&size) {
+        return Err(ServiceError::ValidationError("size must be between 1 and 100".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn get_store_stock(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    params: StockQueryParams,
+) -> Result<PaginatedResponse<StoreStock>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Default pagination values
+    let page = params.page.unwrap_or(1);
+    let size = params.size.unwrap_or(10);
+    validate_pagination(page, size)?;
+    let offset = (page - 1) * size;
+
+    let search_term = params.search.as_ref().map(|term| format!("%{term}%"));
+
+    // Every active product of the company is listed; products without a stock row have zero on hand
+    let from_clause = "FROM products p
+        JOIN stores s ON s.id = $1 AND s.company_id = $2
+        LEFT JOIN store_stock ss ON ss.store_id = s.id AND ss.product_id = p.id
+        WHERE p.company_id = $2 AND p.deleted_at IS NULL
+        AND ($3::text IS NULL OR p.name ILIKE $3::text OR p.sku ILIKE $3::text)";
+
+    // Get total count
+    let total: i64 = match sqlx::query_scalar(&format!("SELECT COUNT(*) {from_clause}"))
+        .bind(params.store_id)
+        .bind(company_id)
+        .bind(&search_term)
+        .fetch_one(&pool)
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => {
+            error!("Database error while counting store stock: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Get paginated results
+    let query = format!(
+        "SELECT s.id as store_id, s.initial as store_initial, p.id as product_id,
+                p.name as product_name, p.sku, p.unit_name,
+                COALESCE(ss.qty, 0) as qty, ss.updated_at
+         {from_clause}
+         ORDER BY p.name ASC LIMIT $4::int4 OFFSET $5::int4"
+    );
+
+    let rows = match sqlx::query_as::<_, StoreStock>(&query)
+        .bind(params.store_id)
+        .bind(company_id)
+        .bind(&search_term)
+        .bind(size)
+        .bind(offset)
+        .fetch_all(&pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Database error while fetching store stock: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Retrieved {} stock rows for store {}", rows.len(), params.store_id);
+    Ok(PaginatedResponse::new(page, size, total, rows))
+}
+
+pub async fn get_product_stock(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    product_id: i32,
+) -> Result<Vec<StoreStock>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let rows = match sqlx::query_as::<_, StoreStock>(
+        "SELECT s.id as store_id, s.initial as store_initial, p.id as product_id,
+                p.name as product_name, p.sku, p.unit_name,
+                COALESCE(ss.qty, 0) as qty, ss.updated_at
+         FROM products p
+         JOIN stores s ON s.company_id = p.company_id
+         LEFT JOIN store_stock ss ON ss.store_id = s.id AND ss.product_id = p.id
+         WHERE p.id = $1 AND p.company_id = $2 AND p.deleted_at IS NULL
+         ORDER BY s.id"
+    )
+    .bind(product_id)
+    .bind(company_id)
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Database error while fetching stock for product {}: {}", product_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if rows.is_empty() {
+        info!("Product {} not found for company_id {}", product_id, company_id);
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(rows)
+}
+
+pub async fn get_stock_movements(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    params: StockMovementQueryParams,
+) -> Result<PaginatedResponse<StockMovement>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Default pagination values
+    let page = params.page.unwrap_or(1);
+    let size = params.size.unwrap_or(10);
+    validate_pagination(page, size)?;
+    let offset = (page - 1) * size;
+
+    let total: i64 = match sqlx::query_scalar(
+        "SELECT COUNT(*)
+         FROM stock_movements sm
+         JOIN stores s ON sm.store_id = s.id
+         WHERE sm.store_id = $1 AND s.company_id = $2
+         AND ($3::int4 IS NULL OR sm.product_id = $3::int4)"
+    )
+    .bind(params.store_id)
+    .bind(company_id)
+    .bind(params.product_id)
+    .fetch_one(&pool)
+    .await {
+        Ok(count) => count,
+        Err(e) => {
+            error!("Database error while counting stock movements: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let rows = match sqlx::query_as::<_, StockMovement>(
+        "SELECT sm.id, sm.store_id, sm.product_id, sm.movement_type, sm.qty, sm.balance_after,
+                sm.reference_type, sm.reference_id, sm.note, sm.user_id, sm.created_at
+         FROM stock_movements sm
+         JOIN stores s ON sm.store_id = s.id
+         WHERE sm.store_id = $1 AND s.company_id = $2
+         AND ($3::int4 IS NULL OR sm.product_id = $3::int4)
+         ORDER BY sm.created_at DESC, sm.id DESC
+         LIMIT $4::int4 OFFSET $5::int4"
+    )
+    .bind(params.store_id)
+    .bind(company_id)
+    .bind(params.product_id)
+    .bind(size)
+    .bind(offset)
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Database error while fetching stock movements: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Retrieved {} stock movements for store {}", rows.len(), params.store_id);
+    Ok(PaginatedResponse::new(page, size, total, rows))
+}
@@ -1,7 +1,28 @@
 pub mod auth;
+pub mod cart_service;
+pub mod customer_service;
 pub mod db_service;
+pub mod discount_engine;
 pub mod user_service;
 pub mod error_handler;
 pub mod google_auth;
+pub mod idempotency_service;
+pub mod inventory_service;
+pub mod offline_sync_service;
+pub mod payment_service;
+pub mod pdf_service;
+pub mod permission_service;
+pub mod pricing_service;
 pub mod product_service;
+pub mod profit_report_service;
+pub mod promotion_engine;
+pub mod promotion_service;
+pub mod receipt_service;
+pub mod receivable_service;
+pub mod sales_report_export_service;
+pub mod sales_return_service;
 pub mod sales_service;
+pub mod settings_service;
+pub mod shift_service;
+pub mod tax_service;
+pub mod z_report_service;
@@ -0,0 +1,460 @@
+use crate::errors::ServiceError;
+use crate::models::inventory::MOVEMENT_SALE;
+use crate::models::offline_sync::{
+    OfflineOrder, OfflineOrderLine, OfflineOrderResult, OfflineSyncRequest, OfflineSyncResponse,
+    MAX_SYNC_BATCH, SYNC_STATUS_ACCEPTED, SYNC_STATUS_DUPLICATE, SYNC_STATUS_REJECTED, SYNC_STATUS_RETRY,
+};
+use crate::models::sales::CreateOrderRequest;
+use crate::models::user::PERMISSION_PRICE_OVERRIDE;
+use crate::services::db_service::DbConnectionManager;
+use crate::services::discount_engine::apply_line_discount;
+use crate::services::inventory_service::{ensure_store_in_company, record_movement_tx, LedgerEntry};
+use crate::services::payment_service::{insert_order_payments_tx, split_payment_lines};
+use crate::services::permission_service::has_permission;
+use crate::services::pricing_service::within_discount_limit;
+use crate::services::promotion_service::apply_promotions;
+use crate::services::sales_service::{
+    insert_sales_order, insert_sales_order_detail, is_cash_sale, mark_service_charge_exempt, price_order,
+    promotion_line, settle_payment, OrderLine,
+};
+use crate::services::settings_service::{load_company_settings, load_store_settings};
+use crate::services::shift_service::active_shift_id_tx;
+use crate::services::tax_service::assign_tax_rates;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// Book a batch of orders captured offline. Each order is committed on its own, so one bad
+// order does not hold back the rest of the queue; failures are reported per order.
+pub async fn sync_offline_orders(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    company_id: i32,
+    request: OfflineSyncRequest,
+) -> Result<OfflineSyncResponse, ServiceError> {
+    if request.orders.is_empty() {
+        return Err(ServiceError::ValidationError("No orders to sync".to_string()));
+    }
+    if request.orders.len() > MAX_SYNC_BATCH {
+        return Err(ServiceError::ValidationError(format!(
+            "A sync batch can hold at most {MAX_SYNC_BATCH} orders"
+        )));
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+    let store_check = ensure_store_in_company(&mut transaction, request.store_id, company_id).await;
+    if let Err(rollback_err) = transaction.rollback().await {
+        error!("Failed to rollback transaction: {:?}", rollback_err);
+    }
+    store_check?;
+
+    let mut response = OfflineSyncResponse {
+        accepted: 0,
+        duplicates: 0,
+        rejected: 0,
+        retry: 0,
+        results: Vec::with_capacity(request.orders.len()),
+    };
+
+    for order in request.orders {
+        let client_uuid = order.client_uuid;
+        let result = match sync_offline_order(&pool, user_id, company_id, request.store_id, order).await {
+            Ok(result) => result,
+            Err(e) => {
+                let (status, message) = failure_status(&e);
+                info!("Offline order {} not booked ({}): {}", client_uuid, status, e);
+                OfflineOrderResult {
+                    client_uuid,
+                    status: status.to_string(),
+                    order_id: None,
+                    order_number: None,
+                    error: Some(message),
+                }
+            }
+        };
+
+        match result.status.as_str() {
+            SYNC_STATUS_ACCEPTED => response.accepted += 1,
+            SYNC_STATUS_DUPLICATE => response.duplicates += 1,
+            SYNC_STATUS_RETRY => response.retry += 1,
+            _ => response.rejected += 1,
+        }
+        response.results.push(result);
+    }
+
+    info!(
+        "Synced offline orders for store {}: {} accepted, {} duplicates, {} rejected, {} to retry",
+        request.store_id, response.accepted, response.duplicates, response.rejected, response.retry
+    );
+    Ok(response)
+}
+
+// Orders that cannot be booked as sent are rejected with the reason. Database and other
+// server failures say nothing about the order, so the till is told to retry it instead and
+// the details stay in the server log.
+fn failure_status(e: &ServiceError) -> (&'static str, String) {
+    match e {
+        ServiceError::ValidationError(msg) | ServiceError::BadRequest(msg) => (SYNC_STATUS_REJECTED, msg.clone()),
+        ServiceError::NotFound | ServiceError::Unauthorized | ServiceError::InsufficientStock(_) => {
+            (SYNC_STATUS_REJECTED, e.to_string())
+        }
+        ServiceError::DatabaseConnectionError
+        | ServiceError::DatabaseQueryError(_)
+        | ServiceError::DatabaseError(_)
+        | ServiceError::InternalServerError => {
+            error!("Failed to book offline order: {}", e);
+            (SYNC_STATUS_RETRY, "Temporary server error, retry later".to_string())
+        }
+    }
+}
+
+async fn sync_offline_order(
+    pool: &PgPool,
+    user_id: i32,
+    company_id: i32,
+    store_id: i32,
+    order: OfflineOrder,
+) -> Result<OfflineOrderResult, ServiceError> {
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    match book_offline_order_tx(&mut transaction, user_id, company_id, store_id, &order).await {
+        Ok(result) if result.status == SYNC_STATUS_ACCEPTED => {
+            if let Err(e) = transaction.commit().await {
+                error!("Failed to commit transaction: {:?}", e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
+            }
+            Ok(result)
+        }
+        Ok(result) => {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            Ok(result)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn book_offline_order_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    company_id: i32,
+    store_id: i32,
+    order: &OfflineOrder,
+) -> Result<OfflineOrderResult, ServiceError> {
+    if order.lines.is_empty() {
+        return Err(ServiceError::ValidationError("Order has no lines".to_string()));
+    }
+    let (payment_cash, payment_non_cash) = split_payment_lines(&order.payments)?;
+
+    // Claim the UUID first. A resend of an order that is still being booked waits here
+    // until the first attempt commits or rolls back.
+    let claimed = match sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO offline_orders (client_uuid, store_id, user_id, client_created_at, synced_at)
+         VALUES ($1, $2, $3, $4, NOW())
+         ON CONFLICT (client_uuid) DO NOTHING
+         RETURNING client_uuid"
+    )
+    .bind(order.client_uuid)
+    .bind(store_id)
+    .bind(user_id)
+    .bind(order.client_created_at)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(claimed) => claimed.is_some(),
+        Err(e) => {
+            error!("Database error while claiming offline order {}: {}", order.client_uuid, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+    if !claimed {
+        return find_synced_order_tx(transaction, company_id, order.client_uuid).await;
+    }
+
+    let prices = fetch_product_prices_tx(transaction, company_id, &order.lines).await?;
+
+    // Lines the till sold at a manual price are kept only if the cashier may override prices
+    let has_override = order.lines.iter().any(|line| {
+        prices.get(&line.product_id).is_some_and(|price| *price != line.base_price)
+    });
+    let price_override_by = if has_override
+        && has_permission(&mut **transaction, user_id, PERMISSION_PRICE_OVERRIDE).await?
+    {
+        Some(user_id)
+    } else {
+        None
+    };
+
+    let settings = load_company_settings(&mut **transaction, company_id).await?;
+    let decimals = settings.currency_scale();
+    let mut lines = order
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| price_offline_line(index + 1, line, &prices, price_override_by, decimals))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Apply the promotions that were running when the till rang up the sale, as it did
+    let promotion_lines = lines
+        .iter()
+        .map(|line| promotion_line(line.product_id, line.qty, line.sale_price, line.discount_type))
+        .collect();
+    let applied = apply_promotions(
+        transaction,
+        company_id,
+        store_id,
+        order.client_created_at,
+        promotion_lines,
+        decimals,
+    ).await?;
+    for (line, promotions) in lines.iter_mut().zip(applied) {
+        line.promotions = promotions;
+    }
+
+    assign_tax_rates(transaction, &settings, &mut lines).await?;
+    let store_settings = load_store_settings(&mut **transaction, store_id).await?;
+    mark_service_charge_exempt(transaction, &store_settings, &mut lines).await?;
+    let mut totals = price_order(
+        &mut lines,
+        order.order_discount_type.as_deref(),
+        order.order_discount_value,
+        &settings,
+        &store_settings,
+        is_cash_sale(payment_cash, payment_non_cash),
+    )?;
+
+    // Discounts above the company's limit, like manual prices, need the price_override permission
+    let over_limit = !within_discount_limit(&settings, totals.subtotal, totals.order_discount_amount)
+        || lines
+            .iter()
+            .any(|line| !within_discount_limit(&settings, line.base_price, line.discount_amount));
+    if over_limit && !has_permission(&mut **transaction, user_id, PERMISSION_PRICE_OVERRIDE).await? {
+        return Err(ServiceError::ValidationError(format!(
+            "Discounts above {}% need the price_override permission",
+            settings.max_discount_percent
+        )));
+    }
+    (totals.receivable, totals.change_due) =
+        settle_payment(totals.grand_total, payment_cash, payment_non_cash)?;
+
+    // The sale already happened, so a missing open shift or stock shortage does not block it.
+    // Cash lands in the drawer of the shift that is open when the till syncs.
+    let shift_id = active_shift_id_tx(transaction, user_id, store_id).await?;
+
+    let order_request = CreateOrderRequest {
+        external_reference: order.external_reference.clone(),
+        store_id,
+        cart_id: None,
+        date: Some(order.date.unwrap_or_else(|| order.client_created_at.date())),
+        payment_cash,
+        payment_non_cash,
+        customer_id: order.customer_id,
+        payments: Vec::new(),
+        order_discount_type: order.order_discount_type.clone(),
+        order_discount_value: order.order_discount_value,
+    };
+    let sales_order = insert_sales_order(
+        transaction,
+        user_id,
+        company_id,
+        &order_request,
+        &totals,
+        shift_id,
+    ).await?;
+
+    for line in &lines {
+        let detail = insert_sales_order_detail(transaction, sales_order.id, line).await?;
+
+        let movement = LedgerEntry {
+            store_id,
+            product_id: detail.product_id,
+            movement_type: MOVEMENT_SALE,
+            qty: -detail.qty,
+            reference_type: Some("sales_order"),
+            reference_id: Some(sales_order.id),
+            note: None,
+            user_id,
+        };
+        record_movement_tx(transaction, &movement).await?;
+    }
+
+    insert_order_payments_tx(transaction, sales_order.id, &order.payments).await?;
+
+    if let Err(e) = sqlx::query("UPDATE offline_orders SET order_id = $2 WHERE client_uuid = $1")
+        .bind(order.client_uuid)
+        .bind(sales_order.id)
+        .execute(&mut **transaction)
+        .await
+    {
+        error!("Database error while linking offline order {}: {}", order.client_uuid, e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Booked offline order {} as sales order ID {}", order.client_uuid, sales_order.id);
+    Ok(OfflineOrderResult {
+        client_uuid: order.client_uuid,
+        status: SYNC_STATUS_ACCEPTED.to_string(),
+        order_id: Some(sales_order.id),
+        order_number: Some(sales_order.order_number),
+        error: None,
+    })
+}
+
+// Report the order an already synced UUID was booked as
+async fn find_synced_order_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: i32,
+    client_uuid: Uuid,
+) -> Result<OfflineOrderResult, ServiceError> {
+    match sqlx::query_as::<_, (Option<i32>, Option<String>)>(
+        "SELECT oo.order_id, so.order_number
+         FROM offline_orders oo
+         JOIN stores s ON oo.store_id = s.id
+         LEFT JOIN sales_orders so ON oo.order_id = so.id
+         WHERE oo.client_uuid = $1 AND s.company_id = $2"
+    )
+    .bind(client_uuid)
+    .bind(company_id)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(Some((order_id, order_number))) => {
+            info!("Offline order {} was already synced", client_uuid);
+            Ok(OfflineOrderResult {
+                client_uuid,
+                status: SYNC_STATUS_DUPLICATE.to_string(),
+                order_id,
+                order_number,
+                error: None,
+            })
+        }
+        Ok(None) => Err(ServiceError::ValidationError(format!(
+            "client_uuid {client_uuid} is already in use"
+        ))),
+        Err(e) => {
+            error!("Database error while fetching offline order {}: {}", client_uuid, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Current sale prices of the company's active products on the order
+async fn fetch_product_prices_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: i32,
+    lines: &[OfflineOrderLine],
+) -> Result<HashMap<i32, Decimal>, ServiceError> {
+    let product_ids: Vec<i32> = lines.iter().map(|line| line.product_id).collect();
+    match sqlx::query_as::<_, (i32, Decimal)>(
+        "SELECT id, sale_price FROM products
+         WHERE company_id = $1 AND deleted_at IS NULL AND id = ANY($2)"
+    )
+    .bind(company_id)
+    .bind(&product_ids)
+    .fetch_all(&mut **transaction)
+    .await
+    {
+        Ok(rows) => Ok(rows.into_iter().collect()),
+        Err(e) => {
+            error!("Database error while fetching product prices: {}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Check a till line against the product's price and recompute its discount with the same
+// engine as the cart. price_override_by is set when the cashier may sell at a manual price.
+fn price_offline_line(
+    line_no: usize,
+    line: &OfflineOrderLine,
+    prices: &HashMap<i32, Decimal>,
+    price_override_by: Option<i32>,
+    decimals: u32,
+) -> Result<OrderLine<'static>, ServiceError> {
+    if line.qty <= 0 {
+        return Err(ServiceError::ValidationError(format!(
+            "Line {line_no}: quantity must be greater than zero"
+        )));
+    }
+    let Some(&price) = prices.get(&line.product_id) else {
+        return Err(ServiceError::ValidationError(format!(
+            "Line {line_no}: product {} not found",
+            line.product_id
+        )));
+    };
+    let overridden = line.base_price != price;
+    if overridden && price_override_by.is_none() {
+        return Err(ServiceError::ValidationError(format!(
+            "Line {line_no}: base price {} does not match the current price {price} of product {}",
+            line.base_price, line.product_id
+        )));
+    }
+
+    let discount = match apply_line_discount(
+        line.base_price,
+        line.discount_type.as_deref(),
+        line.discount_value,
+        decimals,
+    ) {
+        Ok(discount) => discount,
+        Err(ServiceError::ValidationError(msg)) => {
+            return Err(ServiceError::ValidationError(format!("Line {line_no}: {msg}")));
+        }
+        Err(e) => return Err(e),
+    };
+    if let Some(charged) = line.sale_price {
+        if charged != discount.sale_price {
+            return Err(ServiceError::ValidationError(format!(
+                "Line {line_no}: sale price {charged} does not match {} after discount",
+                discount.sale_price
+            )));
+        }
+    }
+
+    Ok(OrderLine {
+        product_id: line.product_id,
+        qty: line.qty,
+        base_price: discount.base_price,
+        discount_type: discount.discount_type,
+        discount_value: discount.discount_value,
+        discount_amount: discount.discount_amount,
+        sale_price: discount.sale_price,
+        list_price: price,
+        price_override_by: price_override_by.filter(|_| overridden),
+        order_discount_amount: Decimal::ZERO,
+        promotions: Vec::new(),
+        tax_rate: Decimal::ZERO,
+        tax_amount: Decimal::ZERO,
+        service_chargeable: true,
+        service_charge_amount: Decimal::ZERO,
+    })
+}
@@ -0,0 +1,161 @@
+use crate::errors::ServiceError;
+use crate::models::sales::{
+    CreateOrderRequest, OrderPaymentRequest, PaymentMethodTotal, SalesOrderPayment,
+    PAYMENT_METHODS, PAYMENT_METHOD_CASH, PAYMENT_METHOD_OTHER,
+};
+use log::error;
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, Transaction};
+
+// Turn the payment part of an order request into tender lines and refresh the
+// payment_cash / payment_non_cash totals from them. Requests that only send the legacy
+// totals get one cash line and one "other" line.
+pub fn take_payment_lines(
+    order_request: &mut CreateOrderRequest,
+) -> Result<Vec<OrderPaymentRequest>, ServiceError> {
+    if order_request.payment_cash < Decimal::ZERO || order_request.payment_non_cash < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Payment amounts cannot be negative".to_string()));
+    }
+
+    if order_request.payments.is_empty() {
+        let mut lines = Vec::new();
+        if order_request.payment_cash > Decimal::ZERO {
+            lines.push(OrderPaymentRequest {
+                method: PAYMENT_METHOD_CASH.to_string(),
+                amount: order_request.payment_cash,
+                reference_number: None,
+            });
+        }
+        if order_request.payment_non_cash > Decimal::ZERO {
+            lines.push(OrderPaymentRequest {
+                method: PAYMENT_METHOD_OTHER.to_string(),
+                amount: order_request.payment_non_cash,
+                reference_number: None,
+            });
+        }
+        return Ok(lines);
+    }
+
+    if order_request.payment_cash != Decimal::ZERO || order_request.payment_non_cash != Decimal::ZERO {
+        return Err(ServiceError::ValidationError(
+            "Send either payments or payment_cash/payment_non_cash, not both".to_string(),
+        ));
+    }
+
+    let lines = std::mem::take(&mut order_request.payments);
+    let (payment_cash, payment_non_cash) = split_payment_lines(&lines)?;
+    order_request.payment_cash = payment_cash;
+    order_request.payment_non_cash = payment_non_cash;
+    Ok(lines)
+}
+
+// Validate tender lines and total them into (cash, non-cash)
+pub fn split_payment_lines(lines: &[OrderPaymentRequest]) -> Result<(Decimal, Decimal), ServiceError> {
+    let mut payment_cash = Decimal::ZERO;
+    let mut payment_non_cash = Decimal::ZERO;
+    for line in lines {
+        if line.method == PAYMENT_METHOD_OTHER || !PAYMENT_METHODS.contains(&line.method.as_str()) {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown payment method '{}'",
+                line.method
+            )));
+        }
+        if line.amount <= Decimal::ZERO {
+            return Err(ServiceError::ValidationError(
+                "Payment amount must be greater than zero".to_string(),
+            ));
+        }
+
+        if line.method == PAYMENT_METHOD_CASH {
+            payment_cash += line.amount;
+        } else {
+            payment_non_cash += line.amount;
+        }
+    }
+
+    Ok((payment_cash, payment_non_cash))
+}
+
+pub async fn insert_order_payments_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    order_id: i32,
+    lines: &[OrderPaymentRequest],
+) -> Result<Vec<SalesOrderPayment>, ServiceError> {
+    let mut payments = Vec::with_capacity(lines.len());
+    for line in lines {
+        let payment = match sqlx::query_as::<_, SalesOrderPayment>(
+            "INSERT INTO sales_order_payments (order_id, method, amount, reference_number, created_at)
+             VALUES ($1, $2, $3, $4, NOW())
+             RETURNING id, order_id, method, amount, reference_number, created_at"
+        )
+        .bind(order_id)
+        .bind(&line.method)
+        .bind(line.amount)
+        .bind(&line.reference_number)
+        .fetch_one(&mut **transaction)
+        .await {
+            Ok(payment) => payment,
+            Err(e) => {
+                error!("Database error while creating order payment: {}", e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
+            }
+        };
+        payments.push(payment);
+    }
+
+    Ok(payments)
+}
+
+pub async fn get_order_payments(
+    pool: &PgPool,
+    order_id: i32,
+) -> Result<Vec<SalesOrderPayment>, ServiceError> {
+    match sqlx::query_as::<_, SalesOrderPayment>(
+        "SELECT id, order_id, method, amount, reference_number, created_at
+         FROM sales_order_payments
+         WHERE order_id = $1
+         ORDER BY id"
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(payments) => Ok(payments),
+        Err(e) => {
+            error!("Database error while fetching payments for order {}: {}", order_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Tender totals per method for the given orders. Change is always given in cash, so the
+// orders' total change_due is taken off the cash line.
+pub async fn get_payment_method_totals(
+    pool: &PgPool,
+    order_ids: &[i32],
+    change_due: Decimal,
+) -> Result<Vec<PaymentMethodTotal>, ServiceError> {
+    let mut totals = match sqlx::query_as::<_, PaymentMethodTotal>(
+        "SELECT method, COUNT(*)::int4 as total_payments, SUM(amount) as total_amount
+         FROM sales_order_payments
+         WHERE order_id = ANY($1)
+         GROUP BY method
+         ORDER BY method"
+    )
+    .bind(order_ids)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(totals) => totals,
+        Err(e) => {
+            error!("Error fetching payment method totals: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Some(cash) = totals.iter_mut().find(|total| total.method == PAYMENT_METHOD_CASH) {
+        cash.total_amount -= change_due;
+    }
+    Ok(totals)
+}
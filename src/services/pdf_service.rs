@@ -0,0 +1,130 @@
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str, TextStr};
+
+// Courier glyphs are 0.6 em wide, so rows laid out in characters line up like on a printer
+const COURIER_ADVANCE: f32 = 0.6;
+const LINE_SPACING: f32 = 1.2;
+pub const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+const CATALOG_ID: Ref = Ref::new(1);
+const PAGE_TREE_ID: Ref = Ref::new(2);
+const FONT_ID: Ref = Ref::new(3);
+const BOLD_FONT_ID: Ref = Ref::new(4);
+const INFO_ID: Ref = Ref::new(5);
+const FIRST_PAGE_ID: i32 = 6;
+
+// A line of monospaced text
+pub struct PdfRow {
+    pub text: String,
+    pub bold: bool,
+}
+
+// Page geometry in points. Without a height the document is a single page as long as its
+// content, like a receipt roll.
+pub struct PdfPage {
+    pub width: f32,
+    pub height: Option<f32>,
+    pub margin: f32,
+    pub font_size: f32,
+}
+
+impl PdfPage {
+    pub const A4_LANDSCAPE: Self = Self {
+        width: 842.0,
+        height: Some(595.0),
+        margin: 36.0,
+        font_size: 8.0,
+    };
+
+    // A roll of paper the given width, with the font sized so `columns` characters fill it
+    #[must_use]
+    pub fn roll(width_mm: f32, columns: u16) -> Self {
+        let width = width_mm * POINTS_PER_MM;
+        let margin = 8.0;
+        Self {
+            width,
+            height: None,
+            margin,
+            font_size: (width - 2.0 * margin) / (f32::from(columns) * COURIER_ADVANCE),
+        }
+    }
+}
+
+// The built-in fonts use WinAnsiEncoding, which matches Latin-1 outside 0x80-0x9F
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            u8::try_from(c)
+                .ok()
+                .filter(|byte| byte.is_ascii() || *byte >= 0xA0)
+                .unwrap_or(b'?')
+        })
+        .collect()
+}
+
+// Write the rows top to bottom with the standard Courier fonts, starting a new page when one is full
+#[must_use]
+pub fn render_pdf(title: &str, rows: &[PdfRow], page: &PdfPage) -> Vec<u8> {
+    let leading = page.font_size * LINE_SPACING;
+    let height = page
+        .height
+        .unwrap_or_else(|| 2.0 * page.margin + rows.iter().map(|_| leading).sum::<f32>());
+
+    let mut pages: Vec<&[PdfRow]> = Vec::new();
+    let mut start = 0;
+    let mut y = height - page.margin;
+    for (index, _) in rows.iter().enumerate() {
+        if y - leading < page.margin && index > start {
+            pages.push(&rows[start..index]);
+            start = index;
+            y = height - page.margin;
+        }
+        y -= leading;
+    }
+    pages.push(&rows[start..]);
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(CATALOG_ID).pages(PAGE_TREE_ID);
+    pdf.document_info(INFO_ID).title(TextStr(title));
+    pdf.type1_font(FONT_ID)
+        .base_font(Name(b"Courier"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(BOLD_FONT_ID)
+        .base_font(Name(b"Courier-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+    let mut page_ids = Vec::new();
+    let mut next_id = FIRST_PAGE_ID;
+    for page_rows in pages {
+        let page_id = Ref::new(next_id);
+        let content_id = Ref::new(next_id + 1);
+        next_id += 2;
+        page_ids.push(page_id);
+
+        let mut content = Content::new();
+        content.begin_text();
+        content.set_leading(leading);
+        content.next_line(page.margin, height - page.margin - page.font_size);
+        for row in page_rows {
+            let font = if row.bold { Name(b"F2") } else { Name(b"F1") };
+            content.set_font(font, page.font_size);
+            content.show(Str(&win_ansi(&row.text)));
+            content.next_line_using_leading();
+        }
+        content.end_text();
+        pdf.stream(content_id, &content.finish());
+
+        let mut pdf_page = pdf.page(page_id);
+        pdf_page.parent(PAGE_TREE_ID);
+        pdf_page.media_box(Rect::new(0.0, 0.0, page.width, height));
+        pdf_page.contents(content_id);
+        pdf_page
+            .resources()
+            .fonts()
+            .pair(Name(b"F1"), FONT_ID)
+            .pair(Name(b"F2"), BOLD_FONT_ID);
+    }
+
+    let page_count = i32::try_from(page_ids.len()).unwrap_or(i32::MAX);
+    pdf.pages(PAGE_TREE_ID).kids(page_ids).count(page_count);
+    pdf.finish()
+}
@@ -0,0 +1,130 @@
+use crate::errors::ServiceError;
+use crate::models::user::{PERMISSIONS, PERMISSION_MANAGE_PERMISSIONS};
+use crate::services::db_service::DbConnectionManager;
+use log::{error, info};
+use sqlx::postgres::PgPool;
+use sqlx::PgExecutor;
+
+pub async fn has_permission<'e, E>(executor: E, user_id: i32, permission: &str) -> Result<bool, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    match sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM user_permissions WHERE user_id = $1 AND permission = $2)"
+    )
+    .bind(user_id)
+    .bind(permission)
+    .fetch_one(executor)
+    .await
+    {
+        Ok(granted) => Ok(granted),
+        Err(e) => {
+            error!("Database error while checking permission {} for user {}: {}", permission, user_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// The granting user must hold manage_permissions and the target user must be in the same company
+async fn check_permission_change(
+    pool: &PgPool,
+    granter_id: i32,
+    company_id: i32,
+    user_id: i32,
+    permission: &str,
+) -> Result<(), ServiceError> {
+    if !PERMISSIONS.contains(&permission) {
+        return Err(ServiceError::ValidationError(format!(
+            "Unknown permission '{permission}', expected one of {}",
+            PERMISSIONS.join(", ")
+        )));
+    }
+    if !has_permission(pool, granter_id, PERMISSION_MANAGE_PERMISSIONS).await? {
+        info!("User {} is not allowed to manage permissions", granter_id);
+        return Err(ServiceError::Unauthorized);
+    }
+
+    match sqlx::query_scalar::<_, i32>("SELECT id FROM users WHERE id = $1 AND company_id = $2")
+        .bind(user_id)
+        .bind(company_id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            info!("User {} not found for company_id {}", user_id, company_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while fetching user {}: {}", user_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn grant_permission(
+    db_manager: &DbConnectionManager,
+    granter_id: i32, // User ID from authentication
+    company_id: i32,
+    user_id: i32,
+    permission: &str,
+) -> Result<(), ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    check_permission_change(&pool, granter_id, company_id, user_id, permission).await?;
+
+    if let Err(e) = sqlx::query(
+        "INSERT INTO user_permissions (user_id, permission, granted_by, granted_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (user_id, permission) DO NOTHING"
+    )
+    .bind(user_id)
+    .bind(permission)
+    .bind(granter_id)
+    .execute(&pool)
+    .await
+    {
+        error!("Database error while granting {} to user {}: {}", permission, user_id, e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("User {} granted {} to user {}", granter_id, permission, user_id);
+    Ok(())
+}
+
+pub async fn revoke_permission(
+    db_manager: &DbConnectionManager,
+    granter_id: i32, // User ID from authentication
+    company_id: i32,
+    user_id: i32,
+    permission: &str,
+) -> Result<(), ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    check_permission_change(&pool, granter_id, company_id, user_id, permission).await?;
+
+    if let Err(e) = sqlx::query("DELETE FROM user_permissions WHERE user_id = $1 AND permission = $2")
+        .bind(user_id)
+        .bind(permission)
+        .execute(&pool)
+        .await
+    {
+        error!("Database error while revoking {} from user {}: {}", permission, user_id, e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("User {} revoked {} from user {}", granter_id, permission, user_id);
+    Ok(())
+}
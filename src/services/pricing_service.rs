@@ -0,0 +1,132 @@
+use crate::errors::ServiceError;
+use crate::models::settings::CompanySettings;
+use crate::models::user::PERMISSION_PRICE_OVERRIDE;
+use crate::services::permission_service::has_permission;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::{Postgres, Transaction};
+
+// Unit price of a line before discounts
+pub struct ResolvedPrice {
+    // Catalog price of the product
+    pub list_price: Decimal,
+    // Price charged, the list price unless it was overridden
+    pub base_price: Decimal,
+    // User who overrode the price
+    pub price_override_by: Option<i32>,
+}
+
+async fn catalog_price_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    company_id: i32,
+    product_id: i32,
+) -> Result<Decimal, ServiceError> {
+    match sqlx::query_scalar::<_, Decimal>(
+        "SELECT sale_price FROM products WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL"
+    )
+    .bind(product_id)
+    .bind(company_id)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(Some(price)) => Ok(price),
+        Ok(None) => {
+            info!("Product {} not found for company_id {}", product_id, company_id);
+            Err(ServiceError::ValidationError(format!("Product {product_id} not found")))
+        }
+        Err(e) => {
+            error!("Database error while fetching price of product {}: {}", product_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Apply a requested unit price against the list price. A price that differs from the list
+// price is an override and needs the price_override permission.
+pub async fn apply_price_override_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    list_price: Decimal,
+    requested: Option<Decimal>,
+) -> Result<ResolvedPrice, ServiceError> {
+    let Some(requested) = requested.filter(|price| *price != list_price) else {
+        return Ok(ResolvedPrice { list_price, base_price: list_price, price_override_by: None });
+    };
+
+    if requested < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Price cannot be negative".to_string()));
+    }
+    if !has_permission(&mut **transaction, user_id, PERMISSION_PRICE_OVERRIDE).await? {
+        info!("User {} is not allowed to override prices", user_id);
+        return Err(ServiceError::Unauthorized);
+    }
+
+    info!("User {} overrode list price {} with {}", user_id, list_price, requested);
+    Ok(ResolvedPrice { list_price, base_price: requested, price_override_by: Some(user_id) })
+}
+
+// Resolve the unit price of a product from the catalog, with an optional manual override
+pub async fn resolve_price_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    company_id: i32,
+    product_id: i32,
+    requested: Option<Decimal>,
+) -> Result<ResolvedPrice, ServiceError> {
+    let list_price = catalog_price_tx(transaction, company_id, product_id).await?;
+    apply_price_override_tx(transaction, user_id, list_price, requested).await
+}
+
+// Whether a manual discount off an amount stays within what the company lets any user give
+#[must_use]
+pub fn within_discount_limit(settings: &CompanySettings, amount: Decimal, discount_amount: Decimal) -> bool {
+    discount_amount <= Decimal::ZERO
+        || discount_amount * Decimal::ONE_HUNDRED <= amount * settings.max_discount_percent
+}
+
+// Check a manual line or order discount. Discounts above the company's max_discount_percent
+// need the price_override permission, like manual prices.
+pub async fn authorize_discount_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    settings: &CompanySettings,
+    amount: Decimal,
+    discount_amount: Decimal,
+) -> Result<(), ServiceError> {
+    if within_discount_limit(settings, amount, discount_amount) {
+        return Ok(());
+    }
+    if !has_permission(&mut **transaction, user_id, PERMISSION_PRICE_OVERRIDE).await? {
+        info!("User {} is not allowed to discount {} off {}", user_id, discount_amount, amount);
+        return Err(ServiceError::Unauthorized);
+    }
+
+    info!("User {} discounted {} off {}", user_id, discount_amount, amount);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn default_limit_lets_any_cashier_discount() {
+        let settings = CompanySettings::defaults(1);
+        assert!(within_discount_limit(&settings, dec("15.99"), dec("1.60")));
+        assert!(within_discount_limit(&settings, dec("15.99"), dec("15.99")));
+    }
+
+    #[test]
+    fn discounts_up_to_the_limit_need_no_permission() {
+        let mut settings = CompanySettings::defaults(1);
+        settings.max_discount_percent = dec("10");
+        assert!(within_discount_limit(&settings, dec("50.00"), Decimal::ZERO));
+        assert!(within_discount_limit(&settings, dec("50.00"), dec("5.00")));
+        assert!(!within_discount_limit(&settings, dec("50.00"), dec("5.01")));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::errors::ServiceError;
-use crate::models::product::{ProductCategory, PaginatedResponse, NewProduct, Product};
+use crate::models::product::{ProductCategory, PaginatedResponse, NewProduct, Product, TAX_CATEGORY_EXEMPT, TAX_CATEGORY_STANDARD};
 use crate::services::db_service::DbConnectionManager;
 use sqlx::postgres::PgPool;
 use sqlx::Row;
@@ -86,6 +86,13 @@ pub async fn create_product(
     new_product: NewProduct,
     company_id: i32,
 ) -> Result<Product, ServiceError> {
+    let tax_category = new_product.tax_category.as_deref().unwrap_or(TAX_CATEGORY_STANDARD);
+    if tax_category != TAX_CATEGORY_STANDARD && tax_category != TAX_CATEGORY_EXEMPT {
+        return Err(ServiceError::ValidationError(format!(
+            "Unknown tax_category '{tax_category}', expected standard or exempt"
+        )));
+    }
+
     let pool = match db_manager.get_pool().await {
         Ok(pool) => pool,
         Err(e) => {
@@ -97,9 +104,10 @@ pub async fn create_product(
     // Execute query to insert new product
     let product = match sqlx::query(
         "INSERT INTO products (
-            sku, name, purchase_price, sale_price, company_id, unit_name, category_id, created_at, updated_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
-        RETURNING id, sku, name, purchase_price, sale_price, company_id, unit_name, deleted_at, created_at, updated_at, category_id"
+            sku, name, purchase_price, sale_price, company_id, unit_name, category_id, tax_category, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
+        RETURNING id, sku, name, purchase_price, sale_price, company_id, unit_name, deleted_at, created_at, updated_at, category_id,
+                  tax_category"
     )
     .bind(&new_product.sku)
     .bind(&new_product.name)
@@ -108,6 +116,7 @@ pub async fn create_product(
     .bind(company_id)
     .bind(&new_product.unit_name)
     .bind(&new_product.category_id)
+    .bind(tax_category)
     .map(|row: sqlx::postgres::PgRow| {
         Product {
             id: row.get("id"),
@@ -121,6 +130,7 @@ pub async fn create_product(
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
             category_id: row.get("category_id"),
+            tax_category: row.get("tax_category"),
         }
     })
     .fetch_one(&pool)
@@ -160,7 +170,7 @@ pub async fn get_products(
     let mut count_query = String::from("SELECT COUNT(*) FROM products WHERE company_id = $1 AND deleted_at IS NULL");
     let mut query = String::from(
         "SELECT id, sku, name, purchase_price, sale_price, company_id, unit_name, 
-        deleted_at, created_at, updated_at, category_id 
+        deleted_at, created_at, updated_at, category_id, tax_category 
         FROM products WHERE company_id = $1 AND deleted_at IS NULL"
     );
 
@@ -219,6 +229,7 @@ pub async fn get_products(
                 created_at: row.try_get("created_at")?,
                 updated_at: row.try_get("updated_at")?,
                 category_id: row.try_get("category_id")?,
+                tax_category: row.try_get("tax_category")?,
             })
         })
         .fetch_all(&pool)
@@ -253,7 +264,7 @@ pub async fn get_product_by_id(
     // Query for product with both product_id and company_id to ensure proper access control
     let query = "
         SELECT id, sku, name, purchase_price, sale_price, company_id, unit_name, 
-        deleted_at, created_at, updated_at, category_id 
+        deleted_at, created_at, updated_at, category_id, tax_category 
         FROM products 
         WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL";
 
@@ -273,6 +284,7 @@ pub async fn get_product_by_id(
                 created_at: row.try_get("created_at")?,
                 updated_at: row.try_get("updated_at")?,
                 category_id: row.try_get("category_id")?,
+                tax_category: row.try_get("tax_category")?,
             })
         })
         .fetch_optional(&pool)
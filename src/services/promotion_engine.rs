@@ -0,0 +1,353 @@
+use crate::models::promotion::{AppliedPromotion, BundleItem, Promotion, PromotionRule};
+use crate::services::discount_engine::{prorate_amount, round_currency};
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+
+// A cart or order line as seen by the promotion rules
+pub struct PromotionLine {
+    pub product_id: i32,
+    pub category_id: Option<i32>,
+    pub qty: i32,
+    // Unit price after the line's own discount
+    pub unit_price: Decimal,
+    // Lines the cashier discounted by hand are left out of promotions
+    pub eligible: bool,
+}
+
+impl PromotionLine {
+    fn total(&self) -> Decimal {
+        self.unit_price * Decimal::from(self.qty)
+    }
+}
+
+// Whether a promotion is switched on, runs in the store and is inside its date and time windows
+#[must_use]
+pub fn is_promotion_running(promotion: &Promotion, store_id: i32, now: NaiveDateTime) -> bool {
+    if !promotion.active {
+        return false;
+    }
+    if let Some(store_ids) = &promotion.store_ids {
+        if !store_ids.is_empty() && !store_ids.contains(&store_id) {
+            return false;
+        }
+    }
+    if promotion.starts_at.is_some_and(|starts_at| now < starts_at)
+        || promotion.ends_at.is_some_and(|ends_at| now > ends_at)
+    {
+        return false;
+    }
+
+    match (promotion.daily_start, promotion.daily_end) {
+        (Some(start), Some(end)) if start <= end => (start..end).contains(&now.time()),
+        // The window wraps past midnight, e.g. 22:00 to 02:00
+        (Some(start), Some(end)) => now.time() >= start || now.time() < end,
+        (Some(start), None) => now.time() >= start,
+        (None, Some(end)) => now.time() < end,
+        (None, None) => true,
+    }
+}
+
+// Apply promotions to the lines, in the order given. A line takes part in at most one
+// promotion; the first promotion that matches it claims it. Returns the promotions applied
+// to each line, in line order.
+#[must_use]
+pub fn evaluate_promotions(
+    promotions: &[Promotion],
+    lines: &[PromotionLine],
+    decimals: u32,
+) -> Vec<Vec<AppliedPromotion>> {
+    let mut applied: Vec<Vec<AppliedPromotion>> = vec![Vec::new(); lines.len()];
+    let mut claimed = vec![false; lines.len()];
+
+    for promotion in promotions {
+        let available = |index: &usize| lines[*index].eligible && !claimed[*index];
+        let discounts: Vec<(usize, Decimal)> = match &promotion.rule {
+            PromotionRule::BuyXGetY { product_id, buy_qty, get_qty } => {
+                let matched: Vec<usize> = (0..lines.len())
+                    .filter(available)
+                    .filter(|index| lines[*index].product_id == *product_id)
+                    .collect();
+                buy_x_get_y(lines, &matched, *buy_qty, *get_qty, decimals)
+            }
+            PromotionRule::BundlePrice { items, price } => {
+                let matched: Vec<usize> = (0..lines.len())
+                    .filter(available)
+                    .filter(|index| items.iter().any(|item| item.product_id == lines[*index].product_id))
+                    .collect();
+                bundle_price(lines, &matched, items, *price, decimals)
+            }
+            PromotionRule::QuantityTier { product_id, tiers } => {
+                let matched: Vec<usize> = (0..lines.len())
+                    .filter(available)
+                    .filter(|index| lines[*index].product_id == *product_id)
+                    .collect();
+                let total_qty: i32 = matched.iter().map(|index| lines[*index].qty).sum();
+                match tiers.iter().filter(|tier| tier.min_qty <= total_qty).max_by_key(|tier| tier.min_qty) {
+                    Some(tier) => matched
+                        .iter()
+                        .map(|index| {
+                            let line = &lines[*index];
+                            let saving = (line.unit_price - tier.unit_price).max(Decimal::ZERO);
+                            (*index, saving * Decimal::from(line.qty))
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                }
+            }
+            PromotionRule::CategoryPercentage { category_id, percentage } => (0..lines.len())
+                .filter(available)
+                .filter(|index| lines[*index].category_id == Some(*category_id))
+                .map(|index| {
+                    let discount = round_currency(lines[index].total() * *percentage / Decimal::ONE_HUNDRED, decimals);
+                    (index, discount)
+                })
+                .collect(),
+        };
+
+        for (index, discount) in discounts {
+            let discount = discount.min(lines[index].total());
+            if discount <= Decimal::ZERO {
+                continue;
+            }
+            claimed[index] = true;
+            applied[index].push(AppliedPromotion {
+                promotion_id: promotion.id,
+                discount_amount: discount,
+            });
+        }
+    }
+
+    applied
+}
+
+// Free units are valued at the cheapest matching line and spread over the lines by their totals
+fn buy_x_get_y(
+    lines: &[PromotionLine],
+    matched: &[usize],
+    buy_qty: i32,
+    get_qty: i32,
+    decimals: u32,
+) -> Vec<(usize, Decimal)> {
+    if buy_qty <= 0 || get_qty <= 0 {
+        return Vec::new();
+    }
+    let total_qty: i32 = matched.iter().map(|index| lines[*index].qty).sum();
+    let free_qty = total_qty / (buy_qty + get_qty) * get_qty;
+    let Some(unit_price) = matched.iter().map(|index| lines[*index].unit_price).min() else {
+        return Vec::new();
+    };
+    if free_qty == 0 {
+        return Vec::new();
+    }
+
+    spread(lines, matched, unit_price * Decimal::from(free_qty), decimals)
+}
+
+// The saving of each complete bundle is spread over every line holding bundle products
+fn bundle_price(
+    lines: &[PromotionLine],
+    matched: &[usize],
+    items: &[BundleItem],
+    price: Decimal,
+    decimals: u32,
+) -> Vec<(usize, Decimal)> {
+    let mut bundles = i32::MAX;
+    let mut regular_price = Decimal::ZERO;
+    for item in items {
+        let item_lines: Vec<&PromotionLine> = matched
+            .iter()
+            .map(|index| &lines[*index])
+            .filter(|line| line.product_id == item.product_id)
+            .collect();
+        let Some(unit_price) = item_lines.iter().map(|line| line.unit_price).min() else {
+            return Vec::new();
+        };
+        if item.qty <= 0 {
+            return Vec::new();
+        }
+        let item_qty: i32 = item_lines.iter().map(|line| line.qty).sum();
+        bundles = bundles.min(item_qty / item.qty);
+        regular_price += unit_price * Decimal::from(item.qty);
+    }
+    if items.is_empty() || bundles == 0 || regular_price <= price {
+        return Vec::new();
+    }
+
+    spread(lines, matched, (regular_price - price) * Decimal::from(bundles), decimals)
+}
+
+fn spread(lines: &[PromotionLine], matched: &[usize], amount: Decimal, decimals: u32) -> Vec<(usize, Decimal)> {
+    let totals: Vec<Decimal> = matched.iter().map(|index| lines[*index].total()).collect();
+    matched
+        .iter()
+        .copied()
+        .zip(prorate_amount(round_currency(amount, decimals), &totals, decimals))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::promotion::PriceTier;
+    use chrono::{NaiveDate, NaiveTime};
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 7, 9).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn promotion(id: i32, rule: PromotionRule) -> Promotion {
+        Promotion {
+            id,
+            company_id: 1,
+            name: format!("Promotion {id}"),
+            rule,
+            store_ids: None,
+            starts_at: None,
+            ends_at: None,
+            daily_start: None,
+            daily_end: None,
+            priority: 0,
+            active: true,
+            created_at: at(0, 0),
+            updated_at: at(0, 0),
+        }
+    }
+
+    fn line(product_id: i32, qty: i32, unit_price: &str) -> PromotionLine {
+        PromotionLine {
+            product_id,
+            category_id: Some(7),
+            qty,
+            unit_price: dec(unit_price),
+            eligible: true,
+        }
+    }
+
+    fn discounts(applied: &[Vec<AppliedPromotion>]) -> Vec<Decimal> {
+        applied
+            .iter()
+            .map(|promotions| promotions.iter().map(|promotion| promotion.discount_amount).sum())
+            .collect()
+    }
+
+    #[test]
+    fn first_matching_promotion_claims_the_line() {
+        let promotions = [
+            promotion(1, PromotionRule::BuyXGetY { product_id: 1, buy_qty: 1, get_qty: 1 }),
+            promotion(2, PromotionRule::CategoryPercentage { category_id: 7, percentage: dec("10") }),
+        ];
+        let lines = [line(1, 2, "10.00"), line(2, 1, "20.00")];
+
+        let applied = evaluate_promotions(&promotions, &lines, 2);
+        assert_eq!(applied[0].len(), 1);
+        assert_eq!(applied[0][0].promotion_id, 1);
+        assert_eq!(applied[0][0].discount_amount, dec("10.00"));
+        assert_eq!(applied[1].len(), 1);
+        assert_eq!(applied[1][0].promotion_id, 2);
+        assert_eq!(applied[1][0].discount_amount, dec("2.00"));
+    }
+
+    #[test]
+    fn promotion_that_gives_nothing_leaves_the_line_unclaimed() {
+        let promotions = [
+            promotion(1, PromotionRule::BuyXGetY { product_id: 1, buy_qty: 2, get_qty: 1 }),
+            promotion(2, PromotionRule::CategoryPercentage { category_id: 7, percentage: dec("10") }),
+        ];
+        let lines = [line(1, 2, "10.00")];
+
+        let applied = evaluate_promotions(&promotions, &lines, 2);
+        assert_eq!(applied[0].len(), 1);
+        assert_eq!(applied[0][0].promotion_id, 2);
+        assert_eq!(applied[0][0].discount_amount, dec("2.00"));
+    }
+
+    #[test]
+    fn manually_discounted_lines_are_left_out() {
+        let promotions = [promotion(1, PromotionRule::CategoryPercentage { category_id: 7, percentage: dec("50") })];
+        let mut discounted = line(1, 1, "10.00");
+        discounted.eligible = false;
+
+        let applied = evaluate_promotions(&promotions, &[discounted, line(2, 1, "4.00")], 2);
+        assert_eq!(discounts(&applied), vec![Decimal::ZERO, dec("2.00")]);
+    }
+
+    #[test]
+    fn mixed_price_bogo_frees_the_cheapest_unit_and_spreads_it() {
+        let promotions = [promotion(1, PromotionRule::BuyXGetY { product_id: 1, buy_qty: 1, get_qty: 1 })];
+        // One unit rung up at 10.00 and one at a manual 8.00: the free unit is worth 8.00
+        let lines = [line(1, 1, "10.00"), line(1, 1, "8.00")];
+
+        let applied = evaluate_promotions(&promotions, &lines, 2);
+        assert_eq!(discounts(&applied), vec![dec("4.44"), dec("3.56")]);
+    }
+
+    #[test]
+    fn bogo_counts_only_complete_groups() {
+        let promotions = [promotion(1, PromotionRule::BuyXGetY { product_id: 1, buy_qty: 2, get_qty: 1 })];
+
+        let applied = evaluate_promotions(&promotions, &[line(1, 5, "3.00")], 2);
+        assert_eq!(discounts(&applied), vec![dec("3.00")]);
+    }
+
+    #[test]
+    fn bundle_applies_once_per_complete_set() {
+        let items = vec![BundleItem { product_id: 1, qty: 1 }, BundleItem { product_id: 2, qty: 1 }];
+        let promotions = [promotion(1, PromotionRule::BundlePrice { items, price: dec("15.00") })];
+        // Two of product 1 but only one of product 2 make a single bundle
+        let lines = [line(1, 2, "10.00"), line(2, 1, "8.00")];
+
+        let applied = evaluate_promotions(&promotions, &lines, 2);
+        assert_eq!(discounts(&applied), vec![dec("2.14"), dec("0.86")]);
+    }
+
+    #[test]
+    fn incomplete_bundle_gives_nothing() {
+        let items = vec![BundleItem { product_id: 1, qty: 2 }, BundleItem { product_id: 2, qty: 1 }];
+        let promotions = [promotion(1, PromotionRule::BundlePrice { items, price: dec("15.00") })];
+
+        let applied = evaluate_promotions(&promotions, &[line(1, 1, "10.00"), line(2, 3, "8.00")], 2);
+        assert_eq!(discounts(&applied), vec![Decimal::ZERO, Decimal::ZERO]);
+        assert!(applied.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn quantity_tier_uses_the_highest_reached_tier() {
+        let tiers = vec![
+            PriceTier { min_qty: 5, unit_price: dec("9.00") },
+            PriceTier { min_qty: 10, unit_price: dec("8.00") },
+        ];
+        let promotions = [promotion(1, PromotionRule::QuantityTier { product_id: 1, tiers })];
+
+        let applied = evaluate_promotions(&promotions, &[line(1, 6, "10.00"), line(1, 4, "10.00")], 2);
+        assert_eq!(discounts(&applied), vec![dec("12.00"), dec("8.00")]);
+    }
+
+    #[test]
+    fn daily_window_wraps_past_midnight() {
+        let mut late_night = promotion(1, PromotionRule::CategoryPercentage { category_id: 7, percentage: dec("10") });
+        late_night.daily_start = NaiveTime::from_hms_opt(22, 0, 0);
+        late_night.daily_end = NaiveTime::from_hms_opt(2, 0, 0);
+
+        assert!(is_promotion_running(&late_night, 1, at(22, 0)));
+        assert!(is_promotion_running(&late_night, 1, at(23, 30)));
+        assert!(is_promotion_running(&late_night, 1, at(1, 59)));
+        assert!(!is_promotion_running(&late_night, 1, at(2, 0)));
+        assert!(!is_promotion_running(&late_night, 1, at(12, 0)));
+    }
+
+    #[test]
+    fn store_and_date_limits_are_checked() {
+        let mut promotion = promotion(1, PromotionRule::CategoryPercentage { category_id: 7, percentage: dec("10") });
+        promotion.store_ids = Some(vec![2]);
+        promotion.ends_at = Some(at(18, 0));
+
+        assert!(is_promotion_running(&promotion, 2, at(12, 0)));
+        assert!(!is_promotion_running(&promotion, 1, at(12, 0)));
+        assert!(!is_promotion_running(&promotion, 2, at(18, 1)));
+    }
+}
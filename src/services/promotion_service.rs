@@ -0,0 +1,365 @@
+use crate::errors::ServiceError;
+use crate::models::promotion::{
+    AppliedPromotion, NewPromotion, Promotion, PromotionQueryParams, PromotionRule, UpdatePromotion,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::promotion_engine::{evaluate_promotions, is_promotion_running, PromotionLine};
+use chrono::NaiveDateTime;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgExecutor, Postgres, Transaction};
+use std::collections::HashMap;
+
+fn validate_rule(rule: &PromotionRule) -> Result<(), ServiceError> {
+    let invalid = |msg: &str| Err(ServiceError::ValidationError(msg.to_string()));
+    match rule {
+        PromotionRule::BuyXGetY { buy_qty, get_qty, .. } => {
+            if *buy_qty <= 0 || *get_qty <= 0 {
+                return invalid("buy_qty and get_qty must be greater than zero");
+            }
+        }
+        PromotionRule::BundlePrice { items, price } => {
+            if items.len() < 2 {
+                return invalid("A bundle needs at least two products");
+            }
+            if items.iter().any(|item| item.qty <= 0) {
+                return invalid("Bundle quantities must be greater than zero");
+            }
+            if *price < Decimal::ZERO {
+                return invalid("Bundle price cannot be negative");
+            }
+        }
+        PromotionRule::QuantityTier { tiers, .. } => {
+            if tiers.is_empty() {
+                return invalid("At least one quantity tier is required");
+            }
+            if tiers.iter().any(|tier| tier.min_qty <= 0 || tier.unit_price < Decimal::ZERO) {
+                return invalid("Tiers need a positive min_qty and a unit_price of zero or more");
+            }
+        }
+        PromotionRule::CategoryPercentage { percentage, .. } => {
+            if *percentage <= Decimal::ZERO || *percentage > Decimal::ONE_HUNDRED {
+                return invalid("Percentage must be greater than 0 and at most 100");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_promotion(
+    name: &str,
+    rule: &PromotionRule,
+    starts_at: Option<NaiveDateTime>,
+    ends_at: Option<NaiveDateTime>,
+) -> Result<(), ServiceError> {
+    if name.trim().is_empty() {
+        return Err(ServiceError::ValidationError("Promotion name is required".to_string()));
+    }
+    if let (Some(starts_at), Some(ends_at)) = (starts_at, ends_at) {
+        if ends_at <= starts_at {
+            return Err(ServiceError::ValidationError("ends_at must be after starts_at".to_string()));
+        }
+    }
+    validate_rule(rule)
+}
+
+// Check that every listed store belongs to the company
+async fn ensure_stores_in_company<'e, E>(
+    executor: E,
+    store_ids: Option<&[i32]>,
+    company_id: i32,
+) -> Result<(), ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    let Some(store_ids) = store_ids else {
+        return Ok(());
+    };
+    let found = match sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(DISTINCT id) FROM stores WHERE id = ANY($1) AND company_id = $2"
+    )
+    .bind(store_ids)
+    .bind(company_id)
+    .fetch_one(executor)
+    .await
+    {
+        Ok(found) => found,
+        Err(e) => {
+            error!("Database error while checking promotion stores: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let mut distinct = store_ids.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if usize::try_from(found).unwrap_or(0) != distinct.len() {
+        return Err(ServiceError::ValidationError("Unknown store in store_ids".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn create_promotion(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    new_promotion: NewPromotion,
+) -> Result<Promotion, ServiceError> {
+    validate_promotion(&new_promotion.name, &new_promotion.rule, new_promotion.starts_at, new_promotion.ends_at)?;
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    ensure_stores_in_company(&pool, new_promotion.store_ids.as_deref(), company_id).await?;
+
+    let promotion = match sqlx::query_as::<_, Promotion>(
+        "INSERT INTO promotions (
+            company_id, name, rule, store_ids, starts_at, ends_at, daily_start, daily_end,
+            priority, active, created_at, updated_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, TRUE, NOW(), NOW())
+        RETURNING id, company_id, name, rule, store_ids, starts_at, ends_at, daily_start, daily_end,
+                  priority, active, created_at, updated_at"
+    )
+    .bind(company_id)
+    .bind(new_promotion.name.trim())
+    .bind(Json(&new_promotion.rule))
+    .bind(&new_promotion.store_ids)
+    .bind(new_promotion.starts_at)
+    .bind(new_promotion.ends_at)
+    .bind(new_promotion.daily_start)
+    .bind(new_promotion.daily_end)
+    .bind(new_promotion.priority)
+    .fetch_one(&pool)
+    .await
+    {
+        Ok(promotion) => promotion,
+        Err(e) => {
+            error!("Database error while creating promotion: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Promotion created successfully with ID: {}", promotion.id);
+    Ok(promotion)
+}
+
+pub async fn get_promotions(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    params: PromotionQueryParams,
+) -> Result<Vec<Promotion>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    match sqlx::query_as::<_, Promotion>(
+        "SELECT id, company_id, name, rule, store_ids, starts_at, ends_at, daily_start, daily_end,
+                priority, active, created_at, updated_at
+         FROM promotions
+         WHERE company_id = $1 AND deleted_at IS NULL AND (NOT $2 OR active)
+         ORDER BY priority DESC, id"
+    )
+    .bind(company_id)
+    .bind(params.active_only.unwrap_or(false))
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(promotions) => {
+            info!("Retrieved {} promotions for company_id {}", promotions.len(), company_id);
+            Ok(promotions)
+        },
+        Err(e) => {
+            error!("Database error while fetching promotions: {}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn update_promotion(
+    db_manager: &DbConnectionManager,
+    promotion_id: i32,
+    company_id: i32,
+    update_data: UpdatePromotion,
+) -> Result<Promotion, ServiceError> {
+    validate_promotion(&update_data.name, &update_data.rule, update_data.starts_at, update_data.ends_at)?;
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    ensure_stores_in_company(&pool, update_data.store_ids.as_deref(), company_id).await?;
+
+    match sqlx::query_as::<_, Promotion>(
+        "UPDATE promotions
+         SET name = $1, rule = $2, store_ids = $3, starts_at = $4, ends_at = $5,
+             daily_start = $6, daily_end = $7, priority = $8, active = COALESCE($9, active),
+             updated_at = NOW()
+         WHERE id = $10 AND company_id = $11 AND deleted_at IS NULL
+         RETURNING id, company_id, name, rule, store_ids, starts_at, ends_at, daily_start, daily_end,
+                   priority, active, created_at, updated_at"
+    )
+    .bind(update_data.name.trim())
+    .bind(Json(&update_data.rule))
+    .bind(&update_data.store_ids)
+    .bind(update_data.starts_at)
+    .bind(update_data.ends_at)
+    .bind(update_data.daily_start)
+    .bind(update_data.daily_end)
+    .bind(update_data.priority)
+    .bind(update_data.active)
+    .bind(promotion_id)
+    .bind(company_id)
+    .fetch_optional(&pool)
+    .await
+    {
+        Ok(Some(promotion)) => {
+            info!("Successfully updated promotion with ID: {}", promotion_id);
+            Ok(promotion)
+        },
+        Ok(None) => {
+            info!("Promotion with ID {} not found for company_id {}", promotion_id, company_id);
+            Err(ServiceError::NotFound)
+        },
+        Err(e) => {
+            error!("Database error while updating promotion {}: {}", promotion_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn delete_promotion(
+    db_manager: &DbConnectionManager,
+    promotion_id: i32,
+    company_id: i32,
+) -> Result<bool, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Soft delete so orders keep the promotions they were sold under
+    let result = match sqlx::query(
+        "UPDATE promotions SET deleted_at = NOW(), active = FALSE, updated_at = NOW()
+         WHERE id = $1 AND company_id = $2 AND deleted_at IS NULL"
+    )
+    .bind(promotion_id)
+    .bind(company_id)
+    .execute(&pool)
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Database error while deleting promotion {}: {}", promotion_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let deleted = result.rows_affected() > 0;
+    if deleted {
+        info!("Successfully deleted promotion with ID: {}", promotion_id);
+    } else {
+        info!("No promotion found with ID: {} for company_id: {}", promotion_id, company_id);
+    }
+
+    Ok(deleted)
+}
+
+// Evaluate the promotions running in the store at the time of the sale against a set of lines.
+// category_id of the lines is filled in here. Returns the promotions applied to each line, in
+// line order.
+pub async fn apply_promotions(
+    conn: &mut PgConnection,
+    company_id: i32,
+    store_id: i32,
+    sold_at: NaiveDateTime,
+    mut lines: Vec<PromotionLine>,
+    decimals: u32,
+) -> Result<Vec<Vec<AppliedPromotion>>, ServiceError> {
+    let promotions = match sqlx::query_as::<_, Promotion>(
+        "SELECT id, company_id, name, rule, store_ids, starts_at, ends_at, daily_start, daily_end,
+                priority, active, created_at, updated_at
+         FROM promotions
+         WHERE company_id = $1 AND deleted_at IS NULL AND active
+         ORDER BY priority DESC, id"
+    )
+    .bind(company_id)
+    .fetch_all(&mut *conn)
+    .await
+    {
+        Ok(promotions) => promotions,
+        Err(e) => {
+            error!("Database error while fetching promotions: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let running: Vec<Promotion> = promotions
+        .into_iter()
+        .filter(|promotion| is_promotion_running(promotion, store_id, sold_at))
+        .collect();
+    if running.is_empty() || lines.is_empty() {
+        return Ok(vec![Vec::new(); lines.len()]);
+    }
+
+    let product_ids: Vec<i32> = lines.iter().map(|line| line.product_id).collect();
+    let categories: HashMap<i32, Option<i32>> = match sqlx::query_as::<_, (i32, Option<i32>)>(
+        "SELECT id, category_id FROM products WHERE id = ANY($1)"
+    )
+    .bind(&product_ids)
+    .fetch_all(&mut *conn)
+    .await
+    {
+        Ok(rows) => rows.into_iter().collect(),
+        Err(e) => {
+            error!("Database error while fetching product categories: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+    for line in &mut lines {
+        line.category_id = categories.get(&line.product_id).copied().flatten();
+    }
+
+    Ok(evaluate_promotions(&running, &lines, decimals))
+}
+
+// Record the promotions applied to an order line
+pub async fn insert_order_promotions_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    order_id: i32,
+    order_detail_id: i32,
+    promotions: &[AppliedPromotion],
+) -> Result<(), ServiceError> {
+    for promotion in promotions {
+        if let Err(e) = sqlx::query(
+            "INSERT INTO sales_order_promotions (order_id, order_detail_id, promotion_id, discount_amount)
+             VALUES ($1, $2, $3, $4)"
+        )
+        .bind(order_id)
+        .bind(order_detail_id)
+        .bind(promotion.promotion_id)
+        .bind(promotion.discount_amount)
+        .execute(&mut **transaction)
+        .await
+        {
+            error!("Database error while recording promotion {} on order {}: {}", promotion.promotion_id, order_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,443 @@
+use crate::errors::ServiceError;
+use crate::models::receipt::{
+    ReceiptQuery, RECEIPT_COLUMNS_58MM, RECEIPT_COLUMNS_80MM, RECEIPT_FORMAT_ESCPOS, RECEIPT_FORMAT_PDF,
+    RECEIPT_FORMAT_TEXT, RECEIPT_WIDTH_58MM, RECEIPT_WIDTH_80MM,
+};
+use crate::models::sales::{
+    DetailedOrderResponse, ORDER_STATUS_VOIDED, PAYMENT_METHOD_BANK_TRANSFER, PAYMENT_METHOD_CASH,
+    PAYMENT_METHOD_CREDIT_CARD, PAYMENT_METHOD_DEBIT_CARD, PAYMENT_METHOD_E_WALLET, PAYMENT_METHOD_QRIS,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::discount_engine::{format_currency, DISCOUNT_TYPE_NONE, DISCOUNT_TYPE_PERCENTAGE};
+use crate::services::pdf_service::{render_pdf, PdfPage, PdfRow};
+use crate::services::sales_service::get_sales_order_by_id;
+use crate::services::settings_service::{load_company_settings, load_store_settings};
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::FromRow;
+
+// ESC/POS control bytes
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+const LF: u8 = 0x0A;
+
+#[derive(FromRow)]
+struct ReceiptStore {
+    name: String,
+    address: Option<String>,
+}
+
+// One element of a receipt, laid out to the paper width when rendered
+enum ReceiptLine {
+    // Centered and bold, e.g. the store name
+    Title(String),
+    // Centered and wrapped to the width
+    Centered(String),
+    // Left-aligned and wrapped to the width
+    Text(String),
+    // Label on the left, amount against the right edge
+    Amount { label: String, amount: String, bold: bool },
+    Separator,
+}
+
+// A line of printer output, already padded to its position
+struct PrintedRow {
+    text: String,
+    bold: bool,
+}
+
+pub enum RenderedReceipt {
+    Text(String),
+    EscPos(Vec<u8>),
+    Pdf(Vec<u8>),
+}
+
+fn payment_label(method: &str) -> &'static str {
+    match method {
+        PAYMENT_METHOD_CASH => "Cash",
+        PAYMENT_METHOD_DEBIT_CARD => "Debit card",
+        PAYMENT_METHOD_CREDIT_CARD => "Credit card",
+        PAYMENT_METHOD_QRIS => "QRIS",
+        PAYMENT_METHOD_BANK_TRANSFER => "Bank transfer",
+        PAYMENT_METHOD_E_WALLET => "E-wallet",
+        _ => "Other",
+    }
+}
+
+fn discount_label(label: &str, discount_type: &str, discount_value: Decimal) -> String {
+    if discount_type == DISCOUNT_TYPE_PERCENTAGE {
+        format!("{label} {}%", discount_value.normalize())
+    } else {
+        label.to_string()
+    }
+}
+
+fn build_receipt(
+    order_response: &DetailedOrderResponse,
+    store: &ReceiptStore,
+    footer: Option<&str>,
+    decimals: u32,
+) -> Vec<ReceiptLine> {
+    let order = &order_response.order;
+    let amount = |label: &str, value: Decimal| ReceiptLine::Amount {
+        label: label.to_string(),
+        amount: format_currency(value, decimals),
+        bold: false,
+    };
+
+    let mut lines = vec![ReceiptLine::Title(store.name.clone())];
+    if let Some(address) = &store.address {
+        lines.push(ReceiptLine::Centered(address.clone()));
+    }
+    lines.push(ReceiptLine::Separator);
+    lines.push(ReceiptLine::Text(format!("Order   : {}", order.order_number)));
+    lines.push(ReceiptLine::Text(format!("Date    : {}", order.created_at.format("%Y-%m-%d %H:%M"))));
+    lines.push(ReceiptLine::Text(format!("Cashier : {}", order.user_initial)));
+    if order.status == ORDER_STATUS_VOIDED {
+        lines.push(ReceiptLine::Centered("*** VOIDED ***".to_string()));
+    }
+    lines.push(ReceiptLine::Separator);
+
+    // Every amount under an item is for the whole line, so they add up to the subtotal.
+    // discount_amount is stored per unit, promotion_discount_amount for the whole line.
+    for detail in &order_response.details {
+        let qty = Decimal::from(detail.qty);
+        let line_discount = detail.discount_amount * qty;
+        lines.push(ReceiptLine::Text(detail.product_name.clone()));
+        lines.push(amount(
+            &format!("  {} x {}", detail.qty, format_currency(detail.base_price, decimals)),
+            detail.base_price * qty,
+        ));
+        if detail.discount_type != DISCOUNT_TYPE_NONE && line_discount > Decimal::ZERO {
+            lines.push(amount(
+                &discount_label("  Discount", &detail.discount_type, detail.discount_value),
+                -line_discount,
+            ));
+        }
+        if detail.promotion_discount_amount > Decimal::ZERO {
+            lines.push(amount("  Promotion", -detail.promotion_discount_amount));
+        }
+    }
+    lines.push(ReceiptLine::Separator);
+
+    lines.push(amount("Subtotal", order.subtotal));
+    if order.order_discount_amount > Decimal::ZERO {
+        lines.push(amount(
+            &discount_label("Order discount", &order.order_discount_type, order.order_discount_value),
+            -order.order_discount_amount,
+        ));
+    }
+    if order.service_charge_amount > Decimal::ZERO {
+        lines.push(amount(
+            &format!("Service charge {}%", order.service_charge_rate.normalize()),
+            order.service_charge_amount,
+        ));
+    }
+    if order.tax_amount > Decimal::ZERO {
+        let label = if order.tax_inclusive { "Tax (included)" } else { "Tax" };
+        lines.push(amount(label, order.tax_amount));
+    }
+    if !order.rounding_amount.is_zero() {
+        lines.push(amount("Rounding", order.rounding_amount));
+    }
+    lines.push(ReceiptLine::Amount {
+        label: "TOTAL".to_string(),
+        amount: format_currency(order.grand_total, decimals),
+        bold: true,
+    });
+    lines.push(ReceiptLine::Separator);
+
+    for payment in &order_response.payments {
+        lines.push(amount(payment_label(&payment.method), payment.amount));
+    }
+    if order.change_due > Decimal::ZERO {
+        lines.push(amount("Change", order.change_due));
+    }
+    if order.receivable > Decimal::ZERO {
+        lines.push(amount("Balance due", order.receivable));
+    }
+
+    if let Some(footer) = footer {
+        lines.push(ReceiptLine::Separator);
+        lines.push(ReceiptLine::Centered(footer.to_string()));
+    }
+    lines
+}
+
+// Word-wrap text to the width, breaking words that are longer than a line
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    for paragraph in text.lines() {
+        let mut row = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > columns {
+                if !row.is_empty() {
+                    rows.push(std::mem::take(&mut row));
+                }
+                rows.push(word.drain(..columns).collect());
+            }
+            let row_len = row.chars().count();
+            if row_len > 0 && row_len + 1 + word.len() > columns {
+                rows.push(std::mem::take(&mut row));
+            }
+            if !row.is_empty() {
+                row.push(' ');
+            }
+            row.extend(word);
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+fn center(text: &str, columns: usize) -> String {
+    let padding = columns.saturating_sub(text.chars().count()) / 2;
+    format!("{}{text}", " ".repeat(padding))
+}
+
+fn layout(lines: &[ReceiptLine], columns: usize) -> Vec<PrintedRow> {
+    let mut rows = Vec::new();
+    for line in lines {
+        match line {
+            ReceiptLine::Title(text) => rows.extend(wrap(text, columns).iter().map(|row| PrintedRow {
+                text: center(row, columns),
+                bold: true,
+            })),
+            ReceiptLine::Centered(text) => rows.extend(wrap(text, columns).iter().map(|row| PrintedRow {
+                text: center(row, columns),
+                bold: false,
+            })),
+            ReceiptLine::Text(text) => rows.extend(wrap(text, columns).into_iter().map(|row| PrintedRow {
+                text: row,
+                bold: false,
+            })),
+            ReceiptLine::Amount { label, amount, bold } => {
+                let amount_len = amount.chars().count();
+                let label_len = label.chars().count();
+                // A label too long to share the line with its amount goes on lines of its own
+                let label_room = columns.saturating_sub(amount_len + 1);
+                let text = if label_len <= label_room {
+                    format!("{label}{}{amount}", " ".repeat(columns - label_len - amount_len))
+                } else {
+                    rows.extend(wrap(label, columns).into_iter().map(|row| PrintedRow { text: row, bold: *bold }));
+                    format!("{amount:>columns$}")
+                };
+                rows.push(PrintedRow { text, bold: *bold });
+            }
+            ReceiptLine::Separator => rows.push(PrintedRow {
+                text: "-".repeat(columns),
+                bold: false,
+            }),
+        }
+    }
+    rows
+}
+
+fn to_text(rows: &[PrintedRow]) -> String {
+    let mut text = String::new();
+    for row in rows {
+        text.push_str(&row.text);
+        text.push('\n');
+    }
+    text
+}
+
+// Printer commands for the receipt: initialise, print each row, feed and cut. Characters outside
+// ASCII are printed as '?' since the printer's code page is unknown.
+fn to_escpos(rows: &[PrintedRow]) -> Vec<u8> {
+    let mut bytes = vec![ESC, b'@'];
+    for row in rows {
+        if row.bold {
+            bytes.extend([ESC, b'E', 1]);
+        }
+        bytes.extend(row.text.chars().map(|c| u8::try_from(c).ok().filter(u8::is_ascii).unwrap_or(b'?')));
+        if row.bold {
+            bytes.extend([ESC, b'E', 0]);
+        }
+        bytes.push(LF);
+    }
+    // Feed past the cutter, then a partial cut
+    bytes.extend([ESC, b'd', 4, GS, b'V', 1]);
+    bytes
+}
+
+pub async fn render_receipt(
+    db_manager: &DbConnectionManager,
+    order_id: i32,
+    company_id: i32,
+    query: ReceiptQuery,
+) -> Result<RenderedReceipt, ServiceError> {
+    let (columns, paper_mm) = match query.width.as_deref() {
+        None | Some(RECEIPT_WIDTH_80MM) => (RECEIPT_COLUMNS_80MM, 80.0),
+        Some(RECEIPT_WIDTH_58MM) => (RECEIPT_COLUMNS_58MM, 58.0),
+        Some(width) => {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown width '{width}', expected 58mm or 80mm"
+            )));
+        }
+    };
+    let format = query.format.unwrap_or_else(|| RECEIPT_FORMAT_TEXT.to_string());
+    if ![RECEIPT_FORMAT_TEXT, RECEIPT_FORMAT_ESCPOS, RECEIPT_FORMAT_PDF].contains(&format.as_str()) {
+        return Err(ServiceError::ValidationError(format!(
+            "Unknown format '{format}', expected text, escpos or pdf"
+        )));
+    }
+
+    // Also checks that the order belongs to the company
+    let order_response = get_sales_order_by_id(db_manager, order_id, company_id).await?;
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let store = match sqlx::query_as::<_, ReceiptStore>("SELECT name, address FROM stores WHERE id = $1")
+        .bind(order_response.order.store_id)
+        .fetch_one(&pool)
+        .await
+    {
+        Ok(store) => store,
+        Err(e) => {
+            error!("Database error while fetching store for receipt: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+    let decimals = load_company_settings(&pool, company_id).await?.currency_scale();
+    let store_settings = load_store_settings(&pool, order_response.order.store_id).await?;
+
+    let lines = build_receipt(&order_response, &store, store_settings.receipt_footer.as_deref(), decimals);
+    let rows = layout(&lines, columns);
+    info!("Rendered {} receipt for order {} at {} columns", format, order_id, columns);
+    match format.as_str() {
+        RECEIPT_FORMAT_ESCPOS => Ok(RenderedReceipt::EscPos(to_escpos(&rows))),
+        RECEIPT_FORMAT_PDF => {
+            let pdf_rows: Vec<PdfRow> = rows
+                .into_iter()
+                .map(|row| PdfRow { text: row.text, bold: row.bold })
+                .collect();
+            let page = PdfPage::roll(paper_mm, u16::try_from(columns).unwrap_or(u16::MAX));
+            let title = format!("Receipt {}", order_response.order.order_number);
+            Ok(RenderedReceipt::Pdf(render_pdf(&title, &pdf_rows, &page)))
+        }
+        _ => Ok(RenderedReceipt::Text(to_text(&rows))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::sales::{DetailedSalesOrder, DetailedSalesOrderDetail, ORDER_STATUS_COMPLETED};
+    use crate::services::discount_engine::DISCOUNT_TYPE_FIXED;
+    use chrono::NaiveDate;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn detail(name: &str, qty: i32, base_price: &str, discount_type: &str, discount_amount: &str, promotion: &str) -> DetailedSalesOrderDetail {
+        let qty_dec = Decimal::from(qty);
+        let sale_price = dec(base_price) - dec(discount_amount);
+        DetailedSalesOrderDetail {
+            id: 1,
+            order_id: 1,
+            product_id: 1,
+            product_name: name.to_string(),
+            sku: String::new(),
+            qty,
+            base_price: dec(base_price),
+            discount_type: discount_type.to_string(),
+            discount_value: dec(discount_amount),
+            discount_amount: dec(discount_amount),
+            sale_price,
+            promotion_discount_amount: dec(promotion),
+            order_discount_amount: Decimal::ZERO,
+            total_price: sale_price * qty_dec - dec(promotion),
+            tax_rate: Decimal::ZERO,
+            tax_amount: Decimal::ZERO,
+            service_charge_amount: Decimal::ZERO,
+            list_price: dec(base_price),
+            price_override_by: None,
+            promotion_ids: Vec::new(),
+        }
+    }
+
+    fn order(subtotal: &str, order_discount: &str, rounding: &str, grand_total: &str) -> DetailedSalesOrder {
+        let created_at = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        DetailedSalesOrder {
+            id: 1,
+            order_number: "A-20240501-0001".to_string(),
+            user_id: 1,
+            user_initial: "JD".to_string(),
+            store_id: 1,
+            store_initial: "A".to_string(),
+            date: created_at.date(),
+            subtotal: dec(subtotal),
+            order_discount_type: DISCOUNT_TYPE_FIXED.to_string(),
+            order_discount_value: dec(order_discount),
+            order_discount_amount: dec(order_discount),
+            rounding_amount: dec(rounding),
+            tax_amount: Decimal::ZERO,
+            tax_inclusive: false,
+            service_charge_rate: Decimal::ZERO,
+            service_charge_amount: Decimal::ZERO,
+            grand_total: dec(grand_total),
+            payment_cash: dec(grand_total),
+            payment_non_cash: Decimal::ZERO,
+            receivable: Decimal::ZERO,
+            change_due: Decimal::ZERO,
+            created_at,
+            customer_id: None,
+            status: ORDER_STATUS_COMPLETED.to_string(),
+            voided_at: None,
+            voided_by: None,
+            void_reason: None,
+            shift_id: None,
+            external_reference: None,
+        }
+    }
+
+    // Amount at the end of a printed row, if it has one
+    fn row_amount(row: &PrintedRow) -> Option<Decimal> {
+        row.text.split_whitespace().last().and_then(|word| Decimal::from_str(word).ok())
+    }
+
+    #[test]
+    fn printed_amounts_add_up_to_the_total() {
+        // 3 x 2.50 less 0.25 a unit = 6.75, 2 x 4.00 less a 1.50 promotion = 6.50
+        let order_response = DetailedOrderResponse {
+            order: order("13.25", "1.00", "-0.25", "12.00"),
+            details: vec![
+                detail("Coffee", 3, "2.50", DISCOUNT_TYPE_FIXED, "0.25", "0"),
+                detail("Bagel", 2, "4.00", DISCOUNT_TYPE_NONE, "0", "1.50"),
+            ],
+            payments: Vec::new(),
+        };
+        let store = ReceiptStore { name: "Corner Shop".to_string(), address: None };
+        let rows = layout(&build_receipt(&order_response, &store, None, 2), RECEIPT_COLUMNS_80MM);
+
+        let separators: Vec<usize> = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.text.starts_with("---"))
+            .map(|(index, _)| index)
+            .collect();
+        let item_total: Decimal = rows[separators[1]..separators[2]].iter().filter_map(row_amount).sum();
+        assert_eq!(item_total, dec("13.25"));
+
+        let total_row = rows.iter().position(|row| row.text.starts_with("TOTAL")).unwrap();
+        let adjustments: Decimal = rows[separators[2]..total_row]
+            .iter()
+            .filter(|row| !row.text.starts_with("Subtotal"))
+            .filter_map(row_amount)
+            .sum();
+        assert_eq!(item_total + adjustments, row_amount(&rows[total_row]).unwrap());
+        assert_eq!(item_total + adjustments, dec("12.00"));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::errors::ServiceError;
+use crate::models::receivable::{
+    AgingReport, AgingReportQuery, AgingRow, CustomerReceivables, NewReceivablePayment,
+    OrderReceivableResponse, ReceivableOrder, ReceivablePayment,
+};
+use crate::models::sales::ORDER_STATUS_VOIDED;
+use crate::services::db_service::DbConnectionManager;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPool;
+use sqlx::PgExecutor;
+
+// Receivable position of one order, scoped to the company of the requester
+async fn fetch_receivable_order<'e, E>(
+    executor: E,
+    order_id: i32,
+    company_id: i32,
+) -> Result<Option<ReceivableOrder>, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    match sqlx::query_as::<_, ReceivableOrder>(
+        "SELECT so.id as order_id, so.order_number, so.customer_id, so.store_id, so.date,
+                so.grand_total, so.receivable,
+                COALESCE(SUM(rp.amount), 0) as paid_amount,
+                so.receivable - COALESCE(SUM(rp.amount), 0) as outstanding
+         FROM sales_orders so
+         JOIN users u ON so.user_id = u.id
+         LEFT JOIN receivable_payments rp ON rp.order_id = so.id
+         WHERE so.id = $1 AND u.company_id = $2
+         GROUP BY so.id"
+    )
+    .bind(order_id)
+    .bind(company_id)
+    .fetch_optional(executor)
+    .await
+    {
+        Ok(order) => Ok(order),
+        Err(e) => {
+            error!("Database error while fetching receivable for order {}: {}", order_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+async fn fetch_order_payments(
+    pool: &PgPool,
+    order_id: i32,
+) -> Result<Vec<ReceivablePayment>, ServiceError> {
+    match sqlx::query_as::<_, ReceivablePayment>(
+        "SELECT id, order_id, customer_id, user_id, store_id, date,
+                payment_cash, payment_non_cash, amount, note, created_at
+         FROM receivable_payments
+         WHERE order_id = $1
+         ORDER BY date, id"
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(payments) => Ok(payments),
+        Err(e) => {
+            error!("Database error while fetching payments for order {}: {}", order_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn create_receivable_payment(
+    db_manager: &DbConnectionManager,
+    order_id: i32,
+    user_id: i32, // User ID from authentication
+    company_id: i32,
+    new_payment: NewReceivablePayment,
+) -> Result<ReceivablePayment, ServiceError> {
+    if new_payment.payment_cash < Decimal::ZERO || new_payment.payment_non_cash < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Payment amounts cannot be negative".to_string()));
+    }
+    let amount = new_payment.payment_cash + new_payment.payment_non_cash;
+    if amount <= Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Payment amount must be greater than zero".to_string()));
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Lock the order so two settlements cannot both pay off the same balance
+    let status = match sqlx::query_scalar::<_, String>(
+        "SELECT so.status FROM sales_orders so
+         JOIN users u ON so.user_id = u.id
+         WHERE so.id = $1 AND u.company_id = $2
+         FOR UPDATE OF so"
+    )
+    .bind(order_id)
+    .bind(company_id)
+    .fetch_optional(&mut *transaction)
+    .await {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            info!("Sales order ID {} not found for company_id {}", order_id, company_id);
+            return Err(ServiceError::NotFound);
+        },
+        Err(e) => {
+            error!("Database error while locking sales order {}: {}", order_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if status == ORDER_STATUS_VOIDED {
+        return Err(ServiceError::ValidationError("Cannot settle a voided order".to_string()));
+    }
+
+    let Some(order) = fetch_receivable_order(&mut *transaction, order_id, company_id).await? else {
+        return Err(ServiceError::NotFound);
+    };
+
+    if amount > order.outstanding {
+        return Err(ServiceError::ValidationError(format!(
+            "Payment of {} exceeds the outstanding balance of {}",
+            amount, order.outstanding
+        )));
+    }
+
+    let date = new_payment.date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let payment = match sqlx::query_as::<_, ReceivablePayment>(
+        "INSERT INTO receivable_payments (
+            order_id, customer_id, user_id, store_id, date,
+            payment_cash, payment_non_cash, amount, note, created_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+        RETURNING id, order_id, customer_id, user_id, store_id, date,
+                 payment_cash, payment_non_cash, amount, note, created_at"
+    )
+    .bind(order.order_id)
+    .bind(order.customer_id)
+    .bind(user_id)
+    .bind(order.store_id)
+    .bind(date)
+    .bind(new_payment.payment_cash)
+    .bind(new_payment.payment_non_cash)
+    .bind(amount)
+    .bind(&new_payment.note)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(payment) => payment,
+        Err(e) => {
+            error!("Database error while recording receivable payment: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!(
+        "Recorded receivable payment ID {} of {} for order ID {}",
+        payment.id, payment.amount, order_id
+    );
+    Ok(payment)
+}
+
+pub async fn get_order_receivable(
+    db_manager: &DbConnectionManager,
+    order_id: i32,
+    company_id: i32,
+) -> Result<OrderReceivableResponse, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let Some(order) = fetch_receivable_order(&pool, order_id, company_id).await? else {
+        info!("Sales order ID {} not found for company_id {}", order_id, company_id);
+        return Err(ServiceError::NotFound);
+    };
+    let payments = fetch_order_payments(&pool, order_id).await?;
+
+    Ok(OrderReceivableResponse { order, payments })
+}
+
+pub async fn get_customer_receivables(
+    db_manager: &DbConnectionManager,
+    customer_id: i32,
+    company_id: i32,
+) -> Result<CustomerReceivables, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let customer_name = match sqlx::query_scalar::<_, String>(
+        "SELECT name FROM customers WHERE id = $1 AND company_id = $2"
+    )
+    .bind(customer_id)
+    .bind(company_id)
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(name)) => name,
+        Ok(None) => {
+            info!("Customer with ID {} not found for company_id {}", customer_id, company_id);
+            return Err(ServiceError::NotFound);
+        },
+        Err(e) => {
+            error!("Database error while fetching customer {}: {}", customer_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let orders = match sqlx::query_as::<_, ReceivableOrder>(
+        "SELECT so.id as order_id, so.order_number, so.customer_id, so.store_id, so.date,
+                so.grand_total, so.receivable,
+                COALESCE(SUM(rp.amount), 0) as paid_amount,
+                so.receivable - COALESCE(SUM(rp.amount), 0) as outstanding
+         FROM sales_orders so
+         LEFT JOIN receivable_payments rp ON rp.order_id = so.id
+         WHERE so.customer_id = $1 AND so.status <> $2 AND so.receivable > 0
+         GROUP BY so.id
+         HAVING so.receivable - COALESCE(SUM(rp.amount), 0) > 0
+         ORDER BY so.date, so.id"
+    )
+    .bind(customer_id)
+    .bind(ORDER_STATUS_VOIDED)
+    .fetch_all(&pool)
+    .await {
+        Ok(orders) => orders,
+        Err(e) => {
+            error!("Database error while fetching receivables for customer {}: {}", customer_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let total_outstanding = orders.iter().fold(Decimal::ZERO, |acc, order| acc + order.outstanding);
+    info!(
+        "Customer {} has {} open orders with {} outstanding",
+        customer_id, orders.len(), total_outstanding
+    );
+
+    Ok(CustomerReceivables {
+        customer_id,
+        customer_name,
+        total_outstanding,
+        orders,
+    })
+}
+
+pub async fn generate_aging_report(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    query: AgingReportQuery,
+) -> Result<AgingReport, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let as_of = query.as_of.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let store_id = query.store_id.unwrap_or(0);
+
+    // Balances are computed as of the given date: later orders and payments are ignored
+    let rows = match sqlx::query_as::<_, AgingRow>(
+        "SELECT o.customer_id, c.name as customer_name,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE $2::date - o.date <= 30), 0) as days_0_30,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE $2::date - o.date BETWEEN 31 AND 60), 0) as days_31_60,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE $2::date - o.date BETWEEN 61 AND 90), 0) as days_61_90,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE $2::date - o.date > 90), 0) as days_over_90,
+                SUM(o.outstanding) as total
+         FROM (
+             SELECT so.id, so.customer_id, so.date,
+                    so.receivable - COALESCE(SUM(rp.amount), 0) as outstanding
+             FROM sales_orders so
+             JOIN users u ON so.user_id = u.id
+             LEFT JOIN receivable_payments rp ON rp.order_id = so.id AND rp.date <= $2
+             WHERE u.company_id = $1
+             AND so.status <> $4
+             AND so.receivable > 0
+             AND so.date <= $2
+             AND ($3 = 0 OR so.store_id = $3)
+             GROUP BY so.id
+         ) o
+         LEFT JOIN customers c ON c.id = o.customer_id
+         WHERE o.outstanding > 0
+         GROUP BY o.customer_id, c.name
+         ORDER BY total DESC"
+    )
+    .bind(company_id)
+    .bind(as_of)
+    .bind(store_id)
+    .bind(ORDER_STATUS_VOIDED)
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Error fetching receivable aging: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let totals = AgingRow {
+        customer_id: None,
+        customer_name: None,
+        days_0_30: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.days_0_30),
+        days_31_60: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.days_31_60),
+        days_61_90: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.days_61_90),
+        days_over_90: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.days_over_90),
+        total: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.total),
+    };
+
+    info!("Generated aging report as of {} with {} rows", as_of, rows.len());
+    Ok(AgingReport {
+        as_of,
+        store_id,
+        rows,
+        totals,
+    })
+}
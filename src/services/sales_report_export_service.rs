@@ -0,0 +1,586 @@
+use crate::errors::ServiceError;
+use crate::models::sales::{
+    SalesReport, SalesReportQuery, REPORT_FORMAT_CSV, REPORT_FORMAT_PDF, REPORT_FORMAT_XLSX,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::discount_engine::format_currency;
+use crate::services::pdf_service::{render_pdf, PdfPage, PdfRow};
+use crate::services::settings_service::load_company_settings;
+use chrono::{NaiveDate, NaiveDateTime};
+use log::{error, info};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::{ColNum, Format, RowNum, Workbook, XlsxError};
+use sqlx::PgPool;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+// A rendered report ready to be sent as a download
+pub struct ExportedReport {
+    pub content_type: &'static str,
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+}
+
+// Who and what the report covers, printed in its header
+struct ReportHeader {
+    company_name: String,
+    store_name: String,
+    period: String,
+}
+
+// A value in the CSV and XLSX exports. Amounts keep their exact decimal digits.
+enum Cell {
+    Text(String),
+    Integer(i64),
+    Amount(Decimal),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Empty,
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Integer(i64::from(value))
+    }
+}
+
+impl From<Decimal> for Cell {
+    fn from(amount: Decimal) -> Self {
+        Cell::Amount(amount)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(value: bool) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Cell::Empty, Into::into)
+    }
+}
+
+// Spreadsheets run text starting with one of these as a formula when they open a CSV file
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+impl Cell {
+    fn to_csv(&self) -> String {
+        match self {
+            // Typed text such as product names is kept as text with a leading apostrophe
+            Cell::Text(text) if text.starts_with(FORMULA_PREFIXES) => format!("'{text}"),
+            Cell::Text(text) => text.clone(),
+            Cell::Integer(value) => value.to_string(),
+            Cell::Amount(amount) => amount.to_string(),
+            Cell::Date(date) => date.to_string(),
+            Cell::DateTime(datetime) => datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+// One CSV file or worksheet of the export
+struct Table {
+    name: &'static str,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<Cell>>,
+}
+
+// Orders, their line items and the SKU summary, one row per record
+fn report_tables(report: &SalesReport) -> [Table; 3] {
+    let orders = report
+        .orders
+        .iter()
+        .map(|order| {
+            vec![
+                order.id.into(),
+                order.order_number.as_str().into(),
+                Cell::Date(order.date),
+                order.store_id.into(),
+                order.store_initial.as_str().into(),
+                order.user_id.into(),
+                order.user_initial.as_str().into(),
+                order.customer_id.into(),
+                order.status.as_str().into(),
+                order.subtotal.into(),
+                order.order_discount_type.as_str().into(),
+                order.order_discount_value.into(),
+                order.order_discount_amount.into(),
+                order.tax_amount.into(),
+                order.tax_inclusive.into(),
+                order.service_charge_rate.into(),
+                order.service_charge_amount.into(),
+                order.rounding_amount.into(),
+                order.grand_total.into(),
+                order.payment_cash.into(),
+                order.payment_non_cash.into(),
+                order.receivable.into(),
+                order.change_due.into(),
+                Cell::DateTime(order.created_at),
+                order.voided_at.map_or(Cell::Empty, Cell::DateTime),
+                order.void_reason.as_deref().into(),
+                order.external_reference.as_deref().into(),
+            ]
+        })
+        .collect();
+
+    let items = report
+        .orders
+        .iter()
+        .flat_map(|order| order.items.iter().map(move |item| (order, item)))
+        .map(|(order, item)| {
+            vec![
+                item.order_id.into(),
+                order.order_number.as_str().into(),
+                item.id.into(),
+                item.product_id.into(),
+                item.sku.as_str().into(),
+                item.product_name.as_str().into(),
+                item.qty.into(),
+                item.base_price.into(),
+                item.discount_type.as_str().into(),
+                item.discount_value.into(),
+                item.discount_amount.into(),
+                item.sale_price.into(),
+                item.promotion_discount_amount.into(),
+                item.order_discount_amount.into(),
+                item.total_price.into(),
+                item.tax_rate.into(),
+                item.tax_amount.into(),
+                item.service_charge_amount.into(),
+            ]
+        })
+        .collect();
+
+    let sku_summary = report
+        .sku_summary
+        .iter()
+        .map(|item| {
+            vec![
+                item.product_id.into(),
+                item.sku.as_str().into(),
+                item.product_name.as_str().into(),
+                Cell::Integer(item.total_qty),
+                item.total_price.into(),
+            ]
+        })
+        .collect();
+
+    [
+        Table {
+            name: "orders",
+            headers: &[
+                "order_id", "order_number", "date", "store_id", "store_initial", "user_id", "user_initial",
+                "customer_id", "status", "subtotal", "order_discount_type", "order_discount_value",
+                "order_discount_amount", "tax_amount", "tax_inclusive", "service_charge_rate",
+                "service_charge_amount", "rounding_amount", "grand_total", "payment_cash", "payment_non_cash",
+                "receivable", "change_due", "created_at", "voided_at", "void_reason", "external_reference",
+            ],
+            rows: orders,
+        },
+        Table {
+            name: "line_items",
+            headers: &[
+                "order_id", "order_number", "line_id", "product_id", "sku", "product_name", "qty", "base_price",
+                "discount_type", "discount_value", "discount_amount", "sale_price", "promotion_discount_amount",
+                "order_discount_amount", "total_price", "tax_rate", "tax_amount", "service_charge_amount",
+            ],
+            rows: items,
+        },
+        Table {
+            name: "sku_summary",
+            headers: &["product_id", "sku", "product_name", "total_qty", "total_price"],
+            rows: sku_summary,
+        },
+    ]
+}
+
+// One CSV file per table in a zip archive
+fn csv_archive(tables: &[Table]) -> Result<Vec<u8>, ServiceError> {
+    let export_error = |e: &dyn std::fmt::Display| {
+        error!("Failed to write CSV export: {}", e);
+        ServiceError::InternalServerError
+    };
+
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    for table in tables {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(table.headers).map_err(|e| export_error(&e))?;
+        for row in &table.rows {
+            writer
+                .write_record(row.iter().map(Cell::to_csv))
+                .map_err(|e| export_error(&e))?;
+        }
+        let bytes = writer.into_inner().map_err(|e| export_error(&e))?;
+
+        archive
+            .start_file(format!("{}.csv", table.name), SimpleFileOptions::default())
+            .map_err(|e| export_error(&e))?;
+        archive.write_all(&bytes).map_err(|e| export_error(&e))?;
+    }
+    let cursor = archive.finish().map_err(|e| export_error(&e))?;
+    Ok(cursor.into_inner())
+}
+
+// One worksheet per table. Amounts are written as numbers shown with the currency's number of
+// decimals; NUMERIC(15, 2) values fit in the 15 significant digits a spreadsheet keeps.
+fn xlsx_workbook(tables: &[Table], decimals: u32) -> Result<Vec<u8>, XlsxError> {
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+    let amount_format = match decimals {
+        0 => Format::new().set_num_format("0"),
+        decimals => Format::new().set_num_format(format!("0.{}", "0".repeat(decimals as usize))),
+    };
+
+    let mut workbook = Workbook::new();
+    for table in tables {
+        let worksheet = workbook.add_worksheet().set_name(table.name)?;
+        for (col, header) in table.headers.iter().enumerate() {
+            worksheet.write_string_with_format(0, column(col)?, *header, &header_format)?;
+        }
+        for (index, cells) in table.rows.iter().enumerate() {
+            let row = RowNum::try_from(index + 1).map_err(|_| XlsxError::RowColumnLimitError)?;
+            for (col, cell) in cells.iter().enumerate() {
+                let col = column(col)?;
+                match cell {
+                    Cell::Text(text) => {
+                        worksheet.write_string(row, col, text)?;
+                    }
+                    Cell::Integer(value) => {
+                        worksheet.write_number(row, col, value.to_f64().unwrap_or_default())?;
+                    }
+                    Cell::Amount(amount) => {
+                        let number = amount.to_f64().unwrap_or_default();
+                        worksheet.write_number_with_format(row, col, number, &amount_format)?;
+                    }
+                    Cell::Date(date) => {
+                        worksheet.write_with_format(row, col, date, &date_format)?;
+                    }
+                    Cell::DateTime(datetime) => {
+                        worksheet.write_with_format(row, col, datetime, &datetime_format)?;
+                    }
+                    Cell::Empty => {}
+                }
+            }
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        worksheet.autofit();
+    }
+    workbook.save_to_buffer()
+}
+
+fn column(index: usize) -> Result<ColNum, XlsxError> {
+    ColNum::try_from(index).map_err(|_| XlsxError::RowColumnLimitError)
+}
+
+enum Align {
+    Left,
+    Right,
+}
+
+// Cells cut or padded to their column width, separated by a space
+fn table_row(cells: &[(&str, usize, Align)]) -> String {
+    cells
+        .iter()
+        .map(|(text, width, align)| {
+            let text: String = text.chars().take(*width).collect();
+            match align {
+                Align::Left => format!("{text:<width$}"),
+                Align::Right => format!("{text:>width$}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+async fn load_header(
+    pool: &PgPool,
+    company_id: i32,
+    query: &SalesReportQuery,
+) -> Result<ReportHeader, ServiceError> {
+    let company_name = match sqlx::query_scalar::<_, String>("SELECT name FROM companies WHERE id = $1")
+        .bind(company_id)
+        .fetch_one(pool)
+        .await
+    {
+        Ok(name) => name,
+        Err(e) => {
+            error!("Database error while fetching company {}: {}", company_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let store_name = if query.store_id > 0 {
+        match sqlx::query_scalar::<_, String>("SELECT name FROM stores WHERE id = $1 AND company_id = $2")
+            .bind(query.store_id)
+            .bind(company_id)
+            .fetch_optional(pool)
+            .await
+        {
+            Ok(Some(name)) => name,
+            Ok(None) => return Err(ServiceError::NotFound),
+            Err(e) => {
+                error!("Database error while fetching store {}: {}", query.store_id, e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
+            }
+        }
+    } else {
+        "All stores".to_string()
+    };
+
+    Ok(ReportHeader {
+        company_name,
+        store_name,
+        period: format!("{} to {}", query.start_date, query.end_date),
+    })
+}
+
+fn sales_report_rows(report: &SalesReport, header: &ReportHeader, decimals: u32) -> Vec<PdfRow> {
+    let money = |amount: Decimal| format_currency(amount, decimals);
+    let text = |text: String| PdfRow { text, bold: false };
+    let heading = |text: &str| PdfRow { text: text.to_string(), bold: true };
+
+    let mut rows = vec![
+        heading("SALES REPORT"),
+        text(format!("Company : {}", header.company_name)),
+        text(format!("Store   : {}", header.store_name)),
+        text(format!("Period  : {}", header.period)),
+        text(String::new()),
+        heading("ORDERS"),
+        PdfRow {
+            text: table_row(&[
+                ("Order number", 24, Align::Left),
+                ("Date", 10, Align::Left),
+                ("Store", 6, Align::Left),
+                ("Cashier", 7, Align::Left),
+                ("Status", 9, Align::Left),
+                ("Subtotal", 15, Align::Right),
+                ("Discount", 13, Align::Right),
+                ("Tax", 13, Align::Right),
+                ("Service", 13, Align::Right),
+                ("Rounding", 10, Align::Right),
+                ("Total", 15, Align::Right),
+            ]),
+            bold: true,
+        },
+    ];
+    for order in &report.orders {
+        rows.push(text(table_row(&[
+            (&order.order_number, 24, Align::Left),
+            (&order.date.to_string(), 10, Align::Left),
+            (&order.store_initial, 6, Align::Left),
+            (&order.user_initial, 7, Align::Left),
+            (&order.status, 9, Align::Left),
+            (&money(order.subtotal), 15, Align::Right),
+            (&money(order.order_discount_amount), 13, Align::Right),
+            (&money(order.tax_amount), 13, Align::Right),
+            (&money(order.service_charge_amount), 13, Align::Right),
+            (&money(order.rounding_amount), 10, Align::Right),
+            (&money(order.grand_total), 15, Align::Right),
+        ])));
+    }
+
+    rows.push(text(String::new()));
+    rows.push(heading("SKU SUMMARY"));
+    rows.push(PdfRow {
+        text: table_row(&[
+            ("SKU", 20, Align::Left),
+            ("Product", 60, Align::Left),
+            ("Qty", 10, Align::Right),
+            ("Total", 16, Align::Right),
+        ]),
+        bold: true,
+    });
+    for item in &report.sku_summary {
+        rows.push(text(table_row(&[
+            (&item.sku, 20, Align::Left),
+            (&item.product_name, 60, Align::Left),
+            (&item.total_qty.to_string(), 10, Align::Right),
+            (&money(item.total_price), 16, Align::Right),
+        ])));
+    }
+
+    let summary = &report.summary;
+    let mut totals = vec![
+        ("Orders", summary.total_orders.to_string()),
+        ("Voided orders", summary.total_voided_orders.to_string()),
+        ("Voided amount", money(summary.total_voided_amount)),
+        ("Gross sales", money(summary.gross_sales)),
+        ("Order discounts", money(summary.total_order_discount)),
+        ("Tax", money(summary.total_tax)),
+        ("Service charge", money(summary.total_service_charge)),
+        ("Rounding", money(summary.total_rounding)),
+        ("Returns", summary.total_returns.to_string()),
+        ("Refunds", money(summary.total_refund)),
+        ("Net sales", money(summary.net_sales)),
+        ("Cash", money(summary.total_payment_cash)),
+        ("Non-cash", money(summary.total_payment_non_cash)),
+        ("Receivable", money(summary.total_receivable)),
+        ("Change given", money(summary.total_change_due)),
+    ];
+    totals.extend(
+        summary
+            .payment_methods
+            .iter()
+            .map(|method| (method.method.as_str(), money(method.total_amount))),
+    );
+
+    rows.push(text(String::new()));
+    rows.push(heading("TOTALS"));
+    for (label, value) in totals {
+        rows.push(text(table_row(&[(label, 20, Align::Left), (&value, 16, Align::Right)])));
+    }
+    rows
+}
+
+async fn sales_report_pdf(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    query: &SalesReportQuery,
+    report: &SalesReport,
+) -> Result<Vec<u8>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let header = load_header(&pool, company_id, query).await?;
+    let decimals = load_company_settings(&pool, company_id).await?.currency_scale();
+    let rows = sales_report_rows(report, &header, decimals);
+    Ok(render_pdf("Sales report", &rows, &PdfPage::A4_LANDSCAPE))
+}
+
+async fn sales_report_xlsx(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    report: &SalesReport,
+) -> Result<Vec<u8>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let decimals = load_company_settings(&pool, company_id).await?.currency_scale();
+    xlsx_workbook(&report_tables(report), decimals).map_err(|e| {
+        error!("Failed to write XLSX export: {}", e);
+        ServiceError::InternalServerError
+    })
+}
+
+// Render a generated sales report in the requested download format
+pub async fn export_sales_report(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    query: &SalesReportQuery,
+    report: &SalesReport,
+    format: &str,
+) -> Result<ExportedReport, ServiceError> {
+    let base_name = format!("sales-report-{}-{}", query.start_date, query.end_date);
+    let exported = match format {
+        REPORT_FORMAT_PDF => ExportedReport {
+            content_type: "application/pdf",
+            file_name: format!("{base_name}.pdf"),
+            bytes: sales_report_pdf(db_manager, company_id, query, report).await?,
+        },
+        REPORT_FORMAT_CSV => ExportedReport {
+            content_type: "application/zip",
+            file_name: format!("{base_name}.zip"),
+            bytes: csv_archive(&report_tables(report))?,
+        },
+        REPORT_FORMAT_XLSX => ExportedReport {
+            content_type: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            file_name: format!("{base_name}.xlsx"),
+            bytes: sales_report_xlsx(db_manager, company_id, report).await?,
+        },
+        _ => {
+            return Err(ServiceError::ValidationError(format!("Unknown report format '{format}'")));
+        }
+    };
+
+    info!("Exported sales report as {} ({} bytes)", format, exported.bytes.len());
+    Ok(exported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::str::FromStr;
+    use zip::ZipArchive;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn archive_file(bytes: &[u8], name: &str) -> String {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut text = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+
+    fn amounts_table() -> Table {
+        let amounts = ["0.10", "0.20", "0.70", "1234567.89", "9999999999999.99", "0.01"];
+        Table {
+            name: "orders",
+            headers: &["order_number", "grand_total"],
+            rows: amounts
+                .iter()
+                .enumerate()
+                .map(|(index, amount)| vec![Cell::Text(format!("A-{index}")), dec(amount).into()])
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn csv_and_xlsx_totals_match() {
+        let tables = [amounts_table()];
+        let expected: Decimal = tables[0]
+            .rows
+            .iter()
+            .filter_map(|row| match row[1] {
+                Cell::Amount(amount) => Some(amount),
+                _ => None,
+            })
+            .sum();
+
+        let csv = archive_file(&csv_archive(&tables).unwrap(), "orders.csv");
+        let csv_total: Decimal = csv
+            .lines()
+            .skip(1)
+            .map(|line| dec(line.split(',').nth(1).unwrap()))
+            .sum();
+
+        // Values of column B below the header row
+        let xlsx = xlsx_workbook(&tables, 2).unwrap();
+        let sheet = archive_file(&xlsx, "xl/worksheets/sheet1.xml");
+        let xlsx_total: Decimal = sheet
+            .split("<c r=\"B")
+            .skip(2)
+            .map(|cell| {
+                let value = &cell[cell.find("<v>").unwrap() + 3..cell.find("</v>").unwrap()];
+                dec(value)
+            })
+            .sum();
+
+        assert_eq!(csv_total, expected);
+        assert_eq!(xlsx_total, expected);
+        assert!(archive_file(&xlsx, "xl/styles.xml").contains("formatCode=\"0.00\""));
+    }
+}
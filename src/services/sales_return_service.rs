@@ -0,0 +1,511 @@
+use crate::errors::ServiceError;
+use crate::models::inventory::MOVEMENT_RETURN;
+use crate::models::sales::ORDER_STATUS_VOIDED;
+use crate::models::sales_return::{
+    CreateReturnRequest, ReturnResponse, SalesReturn, SalesReturnDetail,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::discount_engine::round_currency;
+use crate::services::inventory_service::{record_movement_tx, LedgerEntry};
+use crate::services::settings_service::load_company_settings;
+use chrono::NaiveDate;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPool;
+use sqlx::FromRow;
+use std::collections::HashSet;
+
+// Order header fields needed to process a return
+#[derive(FromRow)]
+struct ReturnableOrder {
+    id: i32,
+    store_id: i32,
+    status: String,
+    // Part of the receivable the customer has not paid yet
+    outstanding: Decimal,
+    // What is left to refund: grand_total, cash rounding included, less earlier returns
+    refundable: Decimal,
+}
+
+// An order line together with the quantity already returned against it
+#[derive(FromRow)]
+struct ReturnableLine {
+    id: i32,
+    product_id: i32,
+    qty: i32,
+    sale_price: Decimal,
+    // Promotion and order discounts of the line, taken off the refund in proportion to the quantity
+    line_discount: Decimal,
+    // Tax charged on top of the line when prices were tax-exclusive, and the line's service
+    // charge, refunded the same way
+    added_tax: Decimal,
+    service_charge_amount: Decimal,
+    returned_qty: i32,
+    // Refunded by earlier returns of the line
+    refunded: Decimal,
+}
+
+impl ReturnableLine {
+    // Price paid for the whole line once promotions, the order discount, added tax and the
+    // service charge are taken into account
+    fn paid_amount(&self) -> Decimal {
+        self.sale_price * Decimal::from(self.qty) - self.line_discount
+            + self.added_tax
+            + self.service_charge_amount
+    }
+
+    // Refund for returning qty more units: their share of the amount paid, rounded once. The
+    // return that takes back the last units gets whatever is left, so a line returned in full
+    // refunds exactly what was paid for it.
+    fn refund(&self, qty: i32, decimals: u32) -> Decimal {
+        if self.returned_qty + qty >= self.qty {
+            return self.paid_amount() - self.refunded;
+        }
+        round_currency(
+            self.paid_amount() * Decimal::from(qty) / Decimal::from(self.qty),
+            decimals,
+        )
+    }
+}
+
+// Settles the order's cash rounding on the return that takes back everything left on the order,
+// and keeps the refunds of an order from adding up to more than its grand total. The difference
+// goes on the last returned lines.
+fn settle_order_refund(refunds: &mut [Decimal], closes_order: bool, refundable: Decimal) {
+    let total: Decimal = refunds.iter().sum();
+    let mut adjustment = if closes_order {
+        refundable - total
+    } else {
+        (refundable - total).min(Decimal::ZERO)
+    };
+    for refund in refunds.iter_mut().rev() {
+        if adjustment.is_zero() {
+            break;
+        }
+        let adjusted = (*refund + adjustment).max(Decimal::ZERO);
+        adjustment -= adjusted - *refund;
+        *refund = adjusted;
+    }
+}
+
+// Aggregated returns for a reporting period
+#[derive(FromRow)]
+pub struct ReturnTotals {
+    pub total_returns: i32,
+    pub total_refund: Decimal,
+    pub refund_cash: Decimal,
+    pub refund_non_cash: Decimal,
+}
+
+fn validate_return_request(request: &CreateReturnRequest) -> Result<(), ServiceError> {
+    if request.items.is_empty() {
+        return Err(ServiceError::ValidationError("No items to return".to_string()));
+    }
+
+    let mut seen = HashSet::new();
+    for item in &request.items {
+        if item.qty <= 0 {
+            return Err(ServiceError::ValidationError(
+                "Returned quantity must be greater than zero".to_string(),
+            ));
+        }
+        if !seen.insert(item.order_detail_id) {
+            return Err(ServiceError::ValidationError(format!(
+                "Order line {} is listed more than once",
+                item.order_detail_id
+            )));
+        }
+    }
+
+    if request.refund_cash < Decimal::ZERO || request.refund_non_cash < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Refund amounts cannot be negative".to_string()));
+    }
+
+    Ok(())
+}
+
+pub async fn create_sales_return(
+    db_manager: &DbConnectionManager,
+    order_id: i32,
+    user_id: i32, // User ID from authentication
+    company_id: i32,
+    request: CreateReturnRequest,
+) -> Result<ReturnResponse, ServiceError> {
+    validate_return_request(&request)?;
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // 1. Lock the order so concurrent returns against it are serialized
+    let order = match sqlx::query_as::<_, ReturnableOrder>(
+        "SELECT so.id, so.store_id, so.status,
+                so.receivable - COALESCE((
+                    SELECT SUM(rp.amount) FROM receivable_payments rp WHERE rp.order_id = so.id
+                ), 0) as outstanding,
+                so.grand_total - COALESCE((
+                    SELECT SUM(sr.total_refund) FROM sales_returns sr WHERE sr.order_id = so.id
+                ), 0) as refundable
+         FROM sales_orders so
+         JOIN users u ON so.user_id = u.id
+         WHERE so.id = $1 AND u.company_id = $2
+         FOR UPDATE OF so"
+    )
+    .bind(order_id)
+    .bind(company_id)
+    .fetch_optional(&mut *transaction)
+    .await {
+        Ok(Some(order)) => order,
+        Ok(None) => {
+            info!("Sales order ID {} not found for company_id {}", order_id, company_id);
+            return Err(ServiceError::NotFound);
+        },
+        Err(e) => {
+            error!("Database error while fetching sales order: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if order.status == ORDER_STATUS_VOIDED {
+        return Err(ServiceError::ValidationError("Cannot return items from a voided order".to_string()));
+    }
+
+    // Refunds are paid from money received, so the customer has to settle the order first
+    if order.outstanding > Decimal::ZERO {
+        return Err(ServiceError::ValidationError(format!(
+            "Order has an outstanding receivable of {}; settle it before returning items",
+            order.outstanding
+        )));
+    }
+
+    // 2. Load the order lines with what has already been returned
+    let lines = match sqlx::query_as::<_, ReturnableLine>(
+        "SELECT sod.id, sod.product_id, sod.qty, sod.sale_price,
+                sod.promotion_discount_amount + sod.order_discount_amount as line_discount,
+                CASE WHEN so.tax_inclusive THEN 0 ELSE sod.tax_amount END as added_tax,
+                sod.service_charge_amount,
+                COALESCE((
+                    SELECT SUM(srd.qty) FROM sales_return_details srd
+                    WHERE srd.order_detail_id = sod.id
+                ), 0)::int4 as returned_qty,
+                COALESCE((
+                    SELECT SUM(srd.total_refund) FROM sales_return_details srd
+                    WHERE srd.order_detail_id = sod.id
+                ), 0) as refunded
+         FROM sales_order_details sod
+         JOIN sales_orders so ON sod.order_id = so.id
+         WHERE sod.order_id = $1"
+    )
+    .bind(order.id)
+    .fetch_all(&mut *transaction)
+    .await {
+        Ok(lines) => lines,
+        Err(e) => {
+            error!("Database error while fetching order lines: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // 3. Check each requested line against the remaining returnable quantity
+    let decimals = load_company_settings(&mut *transaction, company_id).await?.currency_scale();
+    let mut matched_lines = Vec::with_capacity(request.items.len());
+    let mut refunds = Vec::with_capacity(request.items.len());
+    for item in &request.items {
+        let Some(line) = lines.iter().find(|line| line.id == item.order_detail_id) else {
+            return Err(ServiceError::ValidationError(format!(
+                "Order line {} does not belong to order {}",
+                item.order_detail_id, order.id
+            )));
+        };
+
+        let returnable_qty = line.qty - line.returned_qty;
+        if item.qty > returnable_qty {
+            return Err(ServiceError::ValidationError(format!(
+                "Cannot return {} of order line {}: only {} left to return",
+                item.qty, line.id, returnable_qty
+            )));
+        }
+
+        refunds.push(line.refund(item.qty, decimals));
+        matched_lines.push((item, line));
+    }
+
+    let closes_order = lines.iter().all(|line| {
+        let qty = request.items.iter()
+            .find(|item| item.order_detail_id == line.id)
+            .map_or(0, |item| item.qty);
+        line.returned_qty + qty >= line.qty
+    });
+    settle_order_refund(&mut refunds, closes_order, order.refundable);
+    let total_refund: Decimal = refunds.iter().sum();
+
+    let total_payment = request.refund_cash + request.refund_non_cash;
+    if total_payment != total_refund {
+        return Err(ServiceError::ValidationError(format!(
+            "Refund split ({total_payment}) must equal the value of the returned items ({total_refund})"
+        )));
+    }
+
+    // 4. Insert the return header
+    let date = request.date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let sales_return = match sqlx::query_as::<_, SalesReturn>(
+        "INSERT INTO sales_returns (
+            order_id, user_id, store_id, date, total_refund,
+            refund_cash, refund_non_cash, reason, created_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())
+        RETURNING id, order_id, user_id, store_id, date, total_refund,
+                 refund_cash, refund_non_cash, reason, created_at"
+    )
+    .bind(order.id)
+    .bind(user_id)
+    .bind(order.store_id)
+    .bind(date)
+    .bind(total_refund)
+    .bind(request.refund_cash)
+    .bind(request.refund_non_cash)
+    .bind(&request.reason)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(sales_return) => sales_return,
+        Err(e) => {
+            error!("Database error while creating sales return: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // 5. Insert the lines and put resellable goods back into stock
+    let mut details = Vec::with_capacity(matched_lines.len());
+    for ((item, line), refund) in matched_lines.into_iter().zip(refunds) {
+        let detail = match sqlx::query_as::<_, SalesReturnDetail>(
+            "INSERT INTO sales_return_details (
+                return_id, order_detail_id, product_id, qty, unit_refund, total_refund, resellable
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, return_id, order_detail_id, product_id, qty, unit_refund, total_refund, resellable"
+        )
+        .bind(sales_return.id)
+        .bind(line.id)
+        .bind(line.product_id)
+        .bind(item.qty)
+        .bind(round_currency(refund / Decimal::from(item.qty), decimals))
+        .bind(refund)
+        .bind(item.resellable)
+        .fetch_one(&mut *transaction)
+        .await {
+            Ok(detail) => detail,
+            Err(e) => {
+                error!("Database error while creating sales return detail: {}", e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
+            }
+        };
+
+        if detail.resellable {
+            let movement = LedgerEntry {
+                store_id: order.store_id,
+                product_id: detail.product_id,
+                movement_type: MOVEMENT_RETURN,
+                qty: detail.qty,
+                reference_type: Some("sales_return"),
+                reference_id: Some(sales_return.id),
+                note: request.reason.as_deref(),
+                user_id,
+            };
+            record_movement_tx(&mut transaction, &movement).await?;
+        }
+
+        details.push(detail);
+    }
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Created sales return ID {} for order ID {}", sales_return.id, order.id);
+    Ok(ReturnResponse {
+        sales_return,
+        details,
+    })
+}
+
+pub async fn get_order_returns(
+    db_manager: &DbConnectionManager,
+    order_id: i32,
+    company_id: i32,
+) -> Result<Vec<ReturnResponse>, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // Make sure the order exists and belongs to the requester's company
+    match sqlx::query_scalar::<_, i32>(
+        "SELECT so.id FROM sales_orders so
+         JOIN users u ON so.user_id = u.id
+         WHERE so.id = $1 AND u.company_id = $2"
+    )
+    .bind(order_id)
+    .bind(company_id)
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(_)) => {},
+        Ok(None) => {
+            info!("Sales order ID {} not found for company_id {}", order_id, company_id);
+            return Err(ServiceError::NotFound);
+        },
+        Err(e) => {
+            error!("Database error while fetching sales order: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    }
+
+    let returns = match sqlx::query_as::<_, SalesReturn>(
+        "SELECT id, order_id, user_id, store_id, date, total_refund,
+                refund_cash, refund_non_cash, reason, created_at
+         FROM sales_returns
+         WHERE order_id = $1
+         ORDER BY id"
+    )
+    .bind(order_id)
+    .fetch_all(&pool)
+    .await {
+        Ok(returns) => returns,
+        Err(e) => {
+            error!("Database error while fetching sales returns: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let mut responses = Vec::with_capacity(returns.len());
+    for sales_return in returns {
+        let details = match sqlx::query_as::<_, SalesReturnDetail>(
+            "SELECT id, return_id, order_detail_id, product_id, qty, unit_refund, total_refund, resellable
+             FROM sales_return_details
+             WHERE return_id = $1
+             ORDER BY id"
+        )
+        .bind(sales_return.id)
+        .fetch_all(&pool)
+        .await {
+            Ok(details) => details,
+            Err(e) => {
+                error!("Error fetching details for sales return {}: {:?}", sales_return.id, e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
+            }
+        };
+        responses.push(ReturnResponse {
+            sales_return,
+            details,
+        });
+    }
+
+    info!("Retrieved {} returns for order ID {}", responses.len(), order_id);
+    Ok(responses)
+}
+
+// Totals of the returns processed in a period, for one store or all stores (store_id 0)
+pub async fn get_return_totals(
+    pool: &PgPool,
+    company_id: i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    store_id: i32,
+) -> Result<ReturnTotals, ServiceError> {
+    match sqlx::query_as::<_, ReturnTotals>(
+        "SELECT COUNT(*)::int4 as total_returns,
+                COALESCE(SUM(sr.total_refund), 0) as total_refund,
+                COALESCE(SUM(sr.refund_cash), 0) as refund_cash,
+                COALESCE(SUM(sr.refund_non_cash), 0) as refund_non_cash
+         FROM sales_returns sr
+         JOIN stores s ON sr.store_id = s.id
+         WHERE sr.date BETWEEN $1 AND $2
+         AND s.company_id = $3
+         AND ($4 = 0 OR sr.store_id = $4)"
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .bind(company_id)
+    .bind(store_id)
+    .fetch_one(pool)
+    .await
+    {
+        Ok(totals) => Ok(totals),
+        Err(e) => {
+            error!("Error fetching return totals: {:?}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn line(qty: i32, sale_price: &str, returned_qty: i32, refunded: &str) -> ReturnableLine {
+        ReturnableLine {
+            id: 1,
+            product_id: 1,
+            qty,
+            sale_price: dec(sale_price),
+            line_discount: Decimal::ZERO,
+            added_tax: Decimal::ZERO,
+            service_charge_amount: Decimal::ZERO,
+            returned_qty,
+            refunded: dec(refunded),
+        }
+    }
+
+    #[test]
+    fn closing_return_settles_cash_rounding() {
+        // 3 x 3.33 = 9.99, rounded up to 10.00 for cash
+        let grand_total = dec("10.00");
+
+        let mut first = vec![line(3, "3.33", 0, "0").refund(1, 2)];
+        settle_order_refund(&mut first, false, grand_total);
+        assert_eq!(first, vec![dec("3.33")]);
+
+        let mut last = vec![line(3, "3.33", 1, "3.33").refund(2, 2)];
+        settle_order_refund(&mut last, true, grand_total - first[0]);
+        assert_eq!(last, vec![dec("6.67")]);
+        assert_eq!(first[0] + last[0], grand_total);
+    }
+
+    #[test]
+    fn refunds_never_exceed_grand_total() {
+        // 9.99 + 0.04 = 10.03, rounded down to 10.00 for cash
+        let grand_total = dec("10.00");
+        let mut refunds = vec![line(1, "9.99", 0, "0").refund(1, 2)];
+        settle_order_refund(&mut refunds, false, grand_total);
+        assert_eq!(refunds, vec![dec("9.99")]);
+
+        let mut refunds = vec![line(1, "0.04", 0, "0").refund(1, 2)];
+        settle_order_refund(&mut refunds, true, grand_total - dec("9.99"));
+        assert_eq!(refunds, vec![dec("0.01")]);
+    }
+
+    #[test]
+    fn partial_return_is_capped_at_what_is_left() {
+        let mut refunds = vec![dec("4.00"), dec("0.50")];
+        settle_order_refund(&mut refunds, false, dec("3.70"));
+        assert_eq!(refunds, vec![dec("3.70"), dec("0")]);
+    }
+}
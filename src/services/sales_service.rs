@@ -1,11 +1,38 @@
 use crate::errors::ServiceError;
 use crate::models::sales::{SalesCart, SalesCartResponse, NewSalesCart, UpdateSalesCart, SalesOrder, SalesOrderDetail, CreateOrderRequest, OrderResponse,
     SalesReport, SalesReportOrder, SalesReportOrderItem, SkuSummaryItem, SalesSummary, SalesReportQuery, 
-    DetailedOrderResponse, DetailedSalesOrder, DetailedSalesOrderDetail};
+    DetailedOrderResponse, DetailedSalesOrder, DetailedSalesOrderDetail, VoidOrderRequest,
+    ORDER_STATUS_COMPLETED, ORDER_STATUS_VOIDED};
+use crate::models::inventory::{MOVEMENT_ADJUSTMENT, MOVEMENT_SALE};
+use crate::models::promotion::AppliedPromotion;
+use crate::models::settings::{CompanySettings, StoreSettings};
+use crate::services::cart_service::{find_cart_id, touch_cart_tx};
+use crate::services::customer_service::customer_exists_tx;
 use crate::services::db_service::DbConnectionManager;
+use crate::services::idempotency_service::{
+    claim_idempotency_key_tx, store_idempotent_response_tx, validate_idempotency_key,
+    IDEMPOTENCY_SCOPE_CREATE_ORDER,
+};
+use crate::services::inventory_service::{
+    check_availability_tx, record_movement_tx, release_reservations_tx, reserve_cart_item_tx,
+    LedgerEntry,
+};
+use crate::services::discount_engine::{
+    apply_line_discount, prorate_amount, round_currency, round_to_cash_unit, LineDiscount, DISCOUNT_TYPE_NONE,
+};
+use crate::services::pricing_service::{authorize_discount_tx, resolve_price_tx, ResolvedPrice};
+use crate::services::promotion_engine::PromotionLine;
+use crate::services::promotion_service::{apply_promotions, insert_order_promotions_tx};
+use crate::services::payment_service::{
+    get_order_payments, get_payment_method_totals, insert_order_payments_tx, take_payment_lines,
+};
+use crate::services::sales_return_service::get_return_totals;
+use crate::services::settings_service::{load_company_settings, load_store_settings};
+use crate::services::shift_service::active_shift_id_tx;
+use crate::services::tax_service::{assign_tax_rates, line_tax};
 use chrono::Utc;
 use log::{error, info};
-use sqlx::{Row, Transaction, Postgres, FromRow};
+use sqlx::{Row, Transaction, Postgres, FromRow, PgConnection};
 use rust_decimal::Decimal;
 
 // Struct for the order query result
@@ -18,20 +45,44 @@ struct OrderQueryResult {
     pub store_id: i32,
     pub store_initial: String,
     pub date: chrono::NaiveDate,
+    pub subtotal: Decimal,
+    pub order_discount_type: String,
+    pub order_discount_value: Decimal,
+    pub order_discount_amount: Decimal,
+    pub rounding_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub tax_inclusive: bool,
+    pub service_charge_rate: Decimal,
+    pub service_charge_amount: Decimal,
     pub grand_total: Decimal,
     pub payment_cash: Decimal,
     pub payment_non_cash: Decimal,
     pub receivable: Decimal,
+    pub change_due: Decimal,
     pub created_at: chrono::NaiveDateTime,
     pub customer_id: Option<i32>,
+    pub status: String,
+    pub voided_at: Option<chrono::NaiveDateTime>,
+    pub voided_by: Option<i32>,
+    pub void_reason: Option<String>,
+    pub shift_id: Option<i32>,
+    pub external_reference: Option<String>,
     pub creator_company_id: i32,
 }
 
+// Add a product to a cart. Unless separate_line is set, a line for the same product at the
+// same price and discount is merged into by raising its quantity.
 pub async fn add_to_cart(
     db_manager: &DbConnectionManager,
     new_cart_item: NewSalesCart,
     user_id: i32, // User ID from authentication
-) -> Result<SalesCart, ServiceError> {
+    company_id: i32,
+    separate_line: bool,
+) -> Result<SalesCartResponse, ServiceError> {
+    if new_cart_item.qty <= 0 {
+        return Err(ServiceError::ValidationError("Quantity must be greater than zero".to_string()));
+    }
+
     let pool = match db_manager.get_pool().await {
         Ok(pool) => pool,
         Err(e) => {
@@ -40,68 +91,226 @@ pub async fn add_to_cart(
         }
     };
 
-    // Calculate sales price if not provided
-    let sale_price = new_cart_item.sale_price.unwrap_or_else(|| {
-        let discount_type = new_cart_item.discount_type.as_deref().unwrap_or("fixed");
-        let discount_value = new_cart_item.discount_value.unwrap_or(0);
-        let discount_amount = new_cart_item.discount_amount.unwrap_or_else(|| {
-            if discount_type == "percentage" && discount_value > 0 {
-                new_cart_item.base_price * rust_decimal::Decimal::new(discount_value as i64, 2)
-            } else {
-                rust_decimal::Decimal::new(discount_value as i64, 0)
+    // Check availability, insert the line and reserve its stock atomically
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let cart_id = touch_cart_tx(&mut transaction, user_id, new_cart_item.store_id, new_cart_item.cart_id).await?;
+
+    // Price the line from the catalog; the client only chooses the discount
+    let price = resolve_price_tx(
+        &mut transaction,
+        user_id,
+        company_id,
+        new_cart_item.product_id,
+        new_cart_item.base_price,
+    ).await?;
+    let settings = load_company_settings(&mut *transaction, company_id).await?;
+    let discount = apply_line_discount(
+        price.base_price,
+        new_cart_item.discount_type.as_deref(),
+        new_cart_item.discount_value,
+        settings.currency_scale(),
+    )?;
+    authorize_discount_tx(&mut transaction, user_id, &settings, discount.base_price, discount.discount_amount).await?;
+
+    let merge_into = if separate_line {
+        None
+    } else {
+        find_mergeable_line_tx(
+            &mut transaction,
+            cart_id,
+            new_cart_item.product_id,
+            &discount,
+            price.price_override_by,
+        ).await?
+    };
+
+    // The merged line's own reservation does not count against the new total
+    let (line_qty, own_lines) = match &merge_into {
+        Some(line) => match line.qty.checked_add(new_cart_item.qty) {
+            Some(qty) => (qty, vec![line.id]),
+            None => return Err(ServiceError::ValidationError("Quantity is too large".to_string())),
+        },
+        None => (new_cart_item.qty, Vec::new()),
+    };
+    let shortages = check_availability_tx(
+        &mut transaction,
+        new_cart_item.store_id,
+        &[(new_cart_item.product_id, line_qty)],
+        &own_lines,
+    ).await?;
+    if !shortages.is_empty() && !settings.allow_negative_stock {
+        return Err(ServiceError::InsufficientStock(shortages));
+    }
+
+    let cart_item = if let Some(line) = merge_into {
+        merge_cart_line_tx(&mut transaction, line.id, line_qty).await?
+    } else {
+        // Execute query to insert new cart item
+        match sqlx::query(
+            "INSERT INTO sales_cart (
+                user_id, store_id, product_id, base_price, qty, 
+                discount_type, discount_value, discount_amount, sale_price, 
+                created_at, updated_at, cart_id, list_price, price_override_by
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), NOW(), $10, $11, $12)
+            RETURNING id, cart_id, user_id, store_id, product_id, base_price, qty, 
+                     discount_type, discount_value, discount_amount, sale_price, 
+                     created_at, updated_at, list_price, price_override_by"
+        )
+        .bind(user_id) // Authenticated user ID
+        .bind(new_cart_item.store_id)
+        .bind(new_cart_item.product_id)
+        .bind(discount.base_price)
+        .bind(new_cart_item.qty)
+        .bind(discount.discount_type)
+        .bind(discount.discount_value)
+        .bind(discount.discount_amount)
+        .bind(discount.sale_price)
+        .bind(cart_id)
+        .bind(price.list_price)
+        .bind(price.price_override_by)
+        .try_map(|row: sqlx::postgres::PgRow| {
+            Ok(SalesCart {
+                id: row.try_get("id")?,
+                cart_id: row.try_get("cart_id")?,
+                user_id: row.try_get("user_id")?,
+                store_id: row.try_get("store_id")?,
+                product_id: row.try_get("product_id")?,
+                base_price: row.try_get("base_price")?,
+                qty: row.try_get("qty")?,
+                discount_type: row.try_get("discount_type")?,
+                discount_value: row.try_get("discount_value")?,
+                discount_amount: row.try_get("discount_amount")?,
+                sale_price: row.try_get("sale_price")?,
+                created_at: row.try_get("created_at")?,
+                updated_at: row.try_get("updated_at")?,
+                list_price: row.try_get("list_price")?,
+                price_override_by: row.try_get("price_override_by")?,
+            })
+        })
+        .fetch_one(&mut *transaction)
+        .await {
+            Ok(cart_item) => cart_item,
+            Err(e) => {
+                error!("Database error while adding to cart: {}", e);
+                return Err(ServiceError::DatabaseError(e.to_string()));
             }
-        });
-        
-        new_cart_item.base_price - discount_amount
-    });
-
-    // Execute query to insert new cart item
-    let cart_item = match sqlx::query(
-        "INSERT INTO sales_cart (
-            user_id, store_id, product_id, base_price, qty, 
-            discount_type, discount_value, discount_amount, sale_price, 
-            created_at, updated_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), NOW())
-        RETURNING id, user_id, store_id, product_id, base_price, qty, 
-                 discount_type, discount_value, discount_amount, sale_price, 
-                 created_at, updated_at"
+        }
+    };
+
+    reserve_cart_item_tx(
+        &mut transaction,
+        cart_item.id,
+        cart_item.store_id,
+        cart_item.product_id,
+        cart_item.qty,
+        settings.reservation_ttl_minutes,
+    ).await?;
+
+    let response = fetch_cart_line_tx(&mut transaction, cart_item.id).await?;
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Item added to cart successfully with ID: {} (qty {})", response.id, response.qty);
+    Ok(response)
+}
+
+// A single cart line with its product name and reservation
+async fn fetch_cart_line_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    cart_item_id: i32,
+) -> Result<SalesCartResponse, ServiceError> {
+    match sqlx::query_as::<_, SalesCartResponse>(
+        "SELECT sc.id, sc.cart_id, sc.user_id, sc.store_id, sc.product_id, p.name as product_name, p.unit_name,
+                sc.base_price, sc.qty, sc.discount_type, sc.discount_value,
+                sc.discount_amount, sc.sale_price, sc.created_at, sc.updated_at,
+                sc.list_price, sc.price_override_by, r.expires_at as reserved_until
+         FROM sales_cart sc
+         INNER JOIN products p ON sc.product_id = p.id
+         LEFT JOIN stock_reservations r ON r.cart_item_id = sc.id
+         WHERE sc.id = $1"
     )
-    .bind(user_id) // Authenticated user ID
-    .bind(new_cart_item.store_id)
-    .bind(new_cart_item.product_id)
-    .bind(&new_cart_item.base_price)
-    .bind(new_cart_item.qty)
-    .bind(new_cart_item.discount_type.unwrap_or_else(|| "fixed".to_string()))
-    .bind(new_cart_item.discount_value.unwrap_or(0))
-    .bind(new_cart_item.discount_amount.unwrap_or_else(|| rust_decimal::Decimal::new(0, 0)))
-    .bind(sale_price)
-    .try_map(|row: sqlx::postgres::PgRow| {
-        Ok(SalesCart {
-            id: row.try_get("id")?,
-            user_id: row.try_get("user_id")?,
-            store_id: row.try_get("store_id")?,
-            product_id: row.try_get("product_id")?,
-            base_price: row.try_get("base_price")?,
-            qty: row.try_get("qty")?,
-            discount_type: row.try_get("discount_type")?,
-            discount_value: row.try_get("discount_value")?,
-            discount_amount: row.try_get("discount_amount")?,
-            sale_price: row.try_get("sale_price")?,
-            created_at: row.try_get("created_at")?,
-            updated_at: row.try_get("updated_at")?,
-        })
-    })
-    .fetch_one(&pool)
+    .bind(cart_item_id)
+    .fetch_one(&mut **transaction)
     .await {
-        Ok(cart_item) => cart_item,
+        Ok(line) => Ok(line),
         Err(e) => {
-            error!("Database error while adding to cart: {}", e);
-            return Err(ServiceError::DatabaseError(e.to_string()));
+            error!("Database error while fetching cart item {}: {}", cart_item_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
         }
-    };
+    }
+}
+
+// Line of the cart that a new item with the same product, price and discount can be merged into
+async fn find_mergeable_line_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    cart_id: i32,
+    product_id: i32,
+    discount: &LineDiscount,
+    price_override_by: Option<i32>,
+) -> Result<Option<SalesCart>, ServiceError> {
+    match sqlx::query_as::<_, SalesCart>(
+        "SELECT id, cart_id, user_id, store_id, product_id, base_price, qty,
+                discount_type, discount_value, discount_amount, sale_price,
+                created_at, updated_at, list_price, price_override_by
+         FROM sales_cart
+         WHERE cart_id = $1 AND product_id = $2 AND base_price = $3
+         AND discount_type = $4 AND discount_value = $5
+         AND price_override_by IS NOT DISTINCT FROM $6
+         ORDER BY id
+         LIMIT 1
+         FOR UPDATE"
+    )
+    .bind(cart_id)
+    .bind(product_id)
+    .bind(discount.base_price)
+    .bind(discount.discount_type)
+    .bind(discount.discount_value)
+    .bind(price_override_by)
+    .fetch_optional(&mut **transaction)
+    .await {
+        Ok(line) => Ok(line),
+        Err(e) => {
+            error!("Database error while looking up cart line to merge: {}", e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
 
-    info!("Item added to cart successfully with ID: {}", cart_item.id);
-    Ok(cart_item)
+async fn merge_cart_line_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    cart_item_id: i32,
+    qty: i32,
+) -> Result<SalesCart, ServiceError> {
+    match sqlx::query_as::<_, SalesCart>(
+        "UPDATE sales_cart SET qty = $2, updated_at = NOW()
+         WHERE id = $1
+         RETURNING id, cart_id, user_id, store_id, product_id, base_price, qty,
+                 discount_type, discount_value, discount_amount, sale_price,
+                 created_at, updated_at, list_price, price_override_by"
+    )
+    .bind(cart_item_id)
+    .bind(qty)
+    .fetch_one(&mut **transaction)
+    .await {
+        Ok(line) => {
+            info!("Merged into cart item with ID: {}", cart_item_id);
+            Ok(line)
+        },
+        Err(e) => {
+            error!("Database error while merging cart item {}: {}", cart_item_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
 }
 
 pub async fn delete_from_cart(
@@ -117,7 +326,8 @@ pub async fn delete_from_cart(
         }
     };
 
-    // Execute query to delete the cart item, ensuring it belongs to the authenticated user
+    // Execute query to delete the cart item, ensuring it belongs to the authenticated user.
+    // Its stock reservation is released by the ON DELETE CASCADE on stock_reservations.
     let result = match sqlx::query(
         "DELETE FROM sales_cart 
          WHERE id = $1 AND user_id = $2"
@@ -148,7 +358,9 @@ pub async fn delete_from_cart(
 pub async fn get_cart_items(
     db_manager: &DbConnectionManager,
     user_id: i32,
+    company_id: i32,
     store_id: i32,
+    cart_id: Option<i32>,
 ) -> Result<Vec<SalesCartResponse>, ServiceError> {
     let pool = match db_manager.get_pool().await {
         Ok(pool) => pool,
@@ -158,21 +370,28 @@ pub async fn get_cart_items(
         }
     };
 
-    // Execute query to get all cart items for the user and store with product names
+    let Some(cart_id) = find_cart_id(&pool, user_id, store_id, cart_id).await? else {
+        info!("No active cart for user {} in store {}", user_id, store_id);
+        return Ok(Vec::new());
+    };
+
+    // Execute query to get all items of the cart with product names
     let cart_items = match sqlx::query(
-        "SELECT sc.id, sc.user_id, sc.store_id, sc.product_id, p.name as product_name, p.unit_name,
+        "SELECT sc.id, sc.cart_id, sc.user_id, sc.store_id, sc.product_id, p.name as product_name, p.unit_name,
                 sc.base_price, sc.qty, sc.discount_type, sc.discount_value, 
-                sc.discount_amount, sc.sale_price, sc.created_at, sc.updated_at 
+                sc.discount_amount, sc.sale_price, sc.created_at, sc.updated_at,
+                sc.list_price, sc.price_override_by, r.expires_at as reserved_until
          FROM sales_cart sc
          INNER JOIN products p ON sc.product_id = p.id
-         WHERE sc.user_id = $1 AND sc.store_id = $2 
+         LEFT JOIN stock_reservations r ON r.cart_item_id = sc.id
+         WHERE sc.cart_id = $1
          ORDER BY sc.created_at DESC"
     )
-    .bind(user_id)
-    .bind(store_id)
+    .bind(cart_id)
     .try_map(|row: sqlx::postgres::PgRow| {
         Ok(SalesCartResponse {
             id: row.try_get("id")?,
+            cart_id: row.try_get("cart_id")?,
             user_id: row.try_get("user_id")?,
             store_id: row.try_get("store_id")?,
             product_id: row.try_get("product_id")?,
@@ -186,6 +405,11 @@ pub async fn get_cart_items(
             sale_price: row.try_get("sale_price")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
+            list_price: row.try_get("list_price")?,
+            price_override_by: row.try_get("price_override_by")?,
+            reserved_until: row.try_get("reserved_until")?,
+            promotion_ids: Vec::new(),
+            promotion_discount: Decimal::ZERO,
         })
     })
     .fetch_all(&pool)
@@ -197,7 +421,28 @@ pub async fn get_cart_items(
         }
     };
 
-    info!("Retrieved {} cart items for user {} in store {}", cart_items.len(), user_id, store_id);
+    // Show the promotions checkout would apply to the cart as it stands
+    let mut conn = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to acquire database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+    let decimals = load_company_settings(&mut *conn, company_id).await?.currency_scale();
+    let promotion_lines = cart_items
+        .iter()
+        .map(|item| promotion_line(item.product_id, item.qty, item.sale_price, &item.discount_type))
+        .collect();
+    let now = chrono::Local::now().naive_local();
+    let applied = apply_promotions(&mut conn, company_id, store_id, now, promotion_lines, decimals).await?;
+    let mut cart_items = cart_items;
+    for (item, promotions) in cart_items.iter_mut().zip(applied) {
+        item.promotion_discount = promotions.iter().map(|promotion| promotion.discount_amount).sum();
+        item.promotion_ids = promotions.iter().map(|promotion| promotion.promotion_id).collect();
+    }
+
+    info!("Retrieved {} cart items from cart {} for user {}", cart_items.len(), cart_id, user_id);
     Ok(cart_items)
 }
 
@@ -205,8 +450,13 @@ pub async fn update_cart_item(
     db_manager: &DbConnectionManager,
     cart_item_id: i32,
     user_id: i32, // User ID from authentication
+    company_id: i32,
     update_data: UpdateSalesCart,
 ) -> Result<SalesCart, ServiceError> {
+    if update_data.qty.is_some_and(|qty| qty <= 0) {
+        return Err(ServiceError::ValidationError("Quantity must be greater than zero".to_string()));
+    }
+
     let pool = match db_manager.get_pool().await {
         Ok(pool) => pool,
         Err(e) => {
@@ -215,13 +465,21 @@ pub async fn update_cart_item(
         }
     };
 
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
     // First, get the current cart item to make calculations
     let current_item = match sqlx::query_as::<_, SalesCart>(
-        "SELECT * FROM sales_cart WHERE id = $1 AND user_id = $2"
+        "SELECT * FROM sales_cart WHERE id = $1 AND user_id = $2 FOR UPDATE"
     )
     .bind(cart_item_id)
     .bind(user_id)
-    .fetch_optional(&pool)
+    .fetch_optional(&mut *transaction)
     .await {
         Ok(Some(item)) => item,
         Ok(None) => {
@@ -234,40 +492,70 @@ pub async fn update_cart_item(
         }
     };
 
+    // A new price is checked against the current catalog price; otherwise the line keeps its price
+    let price = match update_data.base_price {
+        Some(requested) => resolve_price_tx(
+            &mut transaction,
+            user_id,
+            company_id,
+            current_item.product_id,
+            Some(requested),
+        ).await?,
+        None => ResolvedPrice {
+            list_price: current_item.list_price,
+            base_price: current_item.base_price,
+            price_override_by: current_item.price_override_by,
+        },
+    };
+
     // Prepare update values, using current values if new ones aren't provided
-    let base_price = update_data.base_price.unwrap_or(current_item.base_price);
     let qty = update_data.qty.unwrap_or(current_item.qty);
     let discount_type = update_data.discount_type.unwrap_or(current_item.discount_type);
     let discount_value = update_data.discount_value.unwrap_or(current_item.discount_value);
 
     // Calculate discount_amount and sale_price based on the updated values
-    let discount_amount = if discount_type == "percentage" && discount_value > 0 {
-        base_price * rust_decimal::Decimal::new(discount_value as i64, 2)
-    } else {
-        rust_decimal::Decimal::new(discount_value as i64, 0)
-    };
+    let settings = load_company_settings(&mut *transaction, company_id).await?;
+    let discount = apply_line_discount(
+        price.base_price,
+        Some(&discount_type),
+        Some(discount_value),
+        settings.currency_scale(),
+    )?;
+    authorize_discount_tx(&mut transaction, user_id, &settings, discount.base_price, discount.discount_amount).await?;
 
-    let sale_price = base_price - discount_amount;
+    // Re-check availability for the new quantity, ignoring this line's own reservation
+    let shortages = check_availability_tx(
+        &mut transaction,
+        current_item.store_id,
+        &[(current_item.product_id, qty)],
+        &[cart_item_id],
+    ).await?;
+    if !shortages.is_empty() && !settings.allow_negative_stock {
+        return Err(ServiceError::InsufficientStock(shortages));
+    }
 
     // Execute query to update the cart item
     let updated_item = match sqlx::query_as::<_, SalesCart>(
         "UPDATE sales_cart
          SET base_price = $1, qty = $2, discount_type = $3, discount_value = $4,
-             discount_amount = $5, sale_price = $6, updated_at = NOW()
+             discount_amount = $5, sale_price = $6, list_price = $9, price_override_by = $10,
+             updated_at = NOW()
          WHERE id = $7 AND user_id = $8
-         RETURNING id, user_id, store_id, product_id, base_price, qty,
+         RETURNING id, cart_id, user_id, store_id, product_id, base_price, qty,
                  discount_type, discount_value, discount_amount, sale_price,
-                 created_at, updated_at"
+                 created_at, updated_at, list_price, price_override_by"
     )
-    .bind(base_price)
+    .bind(discount.base_price)
     .bind(qty)
-    .bind(discount_type)
-    .bind(discount_value)
-    .bind(discount_amount)
-    .bind(sale_price)
+    .bind(discount.discount_type)
+    .bind(discount.discount_value)
+    .bind(discount.discount_amount)
+    .bind(discount.sale_price)
     .bind(cart_item_id)
     .bind(user_id)
-    .fetch_one(&pool)
+    .bind(price.list_price)
+    .bind(price.price_override_by)
+    .fetch_one(&mut *transaction)
     .await {
         Ok(item) => item,
         Err(e) => {
@@ -276,6 +564,21 @@ pub async fn update_cart_item(
         }
     };
 
+    // Refresh the reservation with the new quantity and a new expiry
+    reserve_cart_item_tx(
+        &mut transaction,
+        updated_item.id,
+        updated_item.store_id,
+        updated_item.product_id,
+        updated_item.qty,
+        settings.reservation_ttl_minutes,
+    ).await?;
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
     info!("Successfully updated cart item with ID: {} for user: {}", cart_item_id, user_id);
     Ok(updated_item)
 }
@@ -284,6 +587,7 @@ pub async fn clear_cart(
     db_manager: &DbConnectionManager,
     user_id: i32, // User ID from authentication
     store_id: i32,
+    cart_id: Option<i32>,
 ) -> Result<bool, ServiceError> {
     let pool = match db_manager.get_pool().await {
         Ok(pool) => pool,
@@ -293,13 +597,17 @@ pub async fn clear_cart(
         }
     };
 
-    // Execute query to delete all cart items for this user and store
+    let Some(cart_id) = find_cart_id(&pool, user_id, store_id, cart_id).await? else {
+        info!("No active cart for user {} in store {}", user_id, store_id);
+        return Ok(false);
+    };
+
+    // Execute query to delete all items of the cart (reservations cascade); the cart itself stays
     let result = match sqlx::query(
         "DELETE FROM sales_cart 
-         WHERE user_id = $1 AND store_id = $2"
+         WHERE cart_id = $1"
     )
-    .bind(user_id)
-    .bind(store_id)
+    .bind(cart_id)
     .execute(&pool)
     .await {
         Ok(result) => result,
@@ -313,9 +621,9 @@ pub async fn clear_cart(
     let deleted = result.rows_affected() > 0;
     
     if deleted {
-        info!("Successfully cleared cart for user: {} in store: {}", user_id, store_id);
+        info!("Successfully cleared cart {} for user: {}", cart_id, user_id);
     } else {
-        info!("No cart items found for user: {} in store: {}", user_id, store_id);
+        info!("No cart items found in cart {} for user: {}", cart_id, user_id);
     }
     
     Ok(deleted)
@@ -324,8 +632,17 @@ pub async fn clear_cart(
 pub async fn create_sales_order(
     db_manager: &DbConnectionManager,
     user_id: i32, // User ID from authentication
-    order_request: CreateOrderRequest,
+    company_id: i32,
+    mut order_request: CreateOrderRequest,
+    idempotency_key: Option<&str>,
 ) -> Result<OrderResponse, ServiceError> {
+    if let Some(key) = idempotency_key {
+        validate_idempotency_key(key)?;
+    }
+
+    // Validate the tender lines up front; this also fills payment_cash / payment_non_cash
+    let payment_lines = take_payment_lines(&mut order_request)?;
+
     let pool = match db_manager.get_pool().await {
         Ok(pool) => pool,
         Err(e) => {
@@ -343,8 +660,34 @@ pub async fn create_sales_order(
         }
     };
 
-    // 1. Get cart items for the user and store
-    let cart_items = match get_cart_items_tx(&mut transaction, user_id, order_request.store_id).await {
+    // A retried request with the same key gets the original order back instead of a second
+    // checkout, which would otherwise fail on the cart the first attempt already emptied
+    if let Some(key) = idempotency_key {
+        let previous = claim_idempotency_key_tx::<OrderResponse>(
+            &mut transaction,
+            user_id,
+            IDEMPOTENCY_SCOPE_CREATE_ORDER,
+            key,
+        ).await?;
+        if let Some(previous) = previous {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            if previous.order.store_id != order_request.store_id {
+                return Err(ServiceError::ValidationError(
+                    "Idempotency-Key was already used for an order in another store".to_string(),
+                ));
+            }
+            info!("Replaying sales order ID {} for idempotency key {}", previous.order.id, key);
+            return Ok(previous);
+        }
+    }
+
+    // 1. Get the items of the cart being checked out
+    let Some(cart_id) = find_cart_id(&mut *transaction, user_id, order_request.store_id, order_request.cart_id).await? else {
+        return Err(ServiceError::ValidationError("No items in cart".to_string()));
+    };
+    let cart_items = match get_cart_items_tx(&mut transaction, cart_id).await {
         Ok(items) => {
             if items.is_empty() {
                 return Err(ServiceError::ValidationError("No items in cart".to_string()));
@@ -354,26 +697,98 @@ pub async fn create_sales_order(
         Err(e) => return Err(e),
     };
 
-    // 2. Calculate grand total from cart items
-    let grand_total = cart_items.iter().fold(Decimal::new(0, 0), |acc, item| {
-        acc + (item.sale_price * Decimal::new(item.qty as i64, 0))
-    });
+    // Make sure the store can still cover every line. Reservations held by this cart do not count
+    // against it, so expired reservations are simply re-checked against current stock.
+    let settings = load_company_settings(&mut *transaction, company_id).await?;
+    let requested: Vec<(i32, i32)> = cart_items.iter().map(|item| (item.product_id, item.qty)).collect();
+    let cart_item_ids: Vec<i32> = cart_items.iter().map(|item| item.id).collect();
+    let shortages = check_availability_tx(
+        &mut transaction,
+        order_request.store_id,
+        &requested,
+        &cart_item_ids,
+    ).await?;
+    if !shortages.is_empty() && !settings.allow_negative_stock {
+        if let Err(rollback_err) = transaction.rollback().await {
+            error!("Failed to rollback transaction: {:?}", rollback_err);
+        }
+        return Err(ServiceError::InsufficientStock(shortages));
+    }
 
-    // 3. Calculate receivable (grand_total - payment_cash - payment_non_cash)
-    let total_payment = order_request.payment_cash + order_request.payment_non_cash;
-    let receivable = if grand_total > total_payment {
-        grand_total - total_payment
-    } else {
-        Decimal::new(0, 0)
+    // Attach the cashier's open shift so the drawer can be reconciled at close
+    let shift_id = active_shift_id_tx(&mut transaction, user_id, order_request.store_id).await?;
+    if shift_id.is_none() && settings.require_open_shift {
+        if let Err(rollback_err) = transaction.rollback().await {
+            error!("Failed to rollback transaction: {:?}", rollback_err);
+        }
+        return Err(ServiceError::ValidationError(
+            "Open a shift in this store before checking out".to_string(),
+        ));
+    }
+
+    // 2. Apply running promotions, then calculate the totals from the cart items, spreading
+    // the order discount over the lines
+    let promotion_lines = cart_items
+        .iter()
+        .map(|item| promotion_line(item.product_id, item.qty, item.sale_price, &item.discount_type))
+        .collect();
+    let applied = apply_promotions(
+        &mut transaction,
+        company_id,
+        order_request.store_id,
+        chrono::Local::now().naive_local(),
+        promotion_lines,
+        settings.currency_scale(),
+    ).await?;
+    let mut lines: Vec<OrderLine> = cart_items.iter().map(OrderLine::from_cart).collect();
+    for (line, promotions) in lines.iter_mut().zip(applied) {
+        line.promotions = promotions;
+    }
+    assign_tax_rates(&mut transaction, &settings, &mut lines).await?;
+    let store_settings = load_store_settings(&mut *transaction, order_request.store_id).await?;
+    mark_service_charge_exempt(&mut transaction, &store_settings, &mut lines).await?;
+    let mut totals = match price_order(
+        &mut lines,
+        order_request.order_discount_type.as_deref(),
+        order_request.order_discount_value,
+        &settings,
+        &store_settings,
+        is_cash_sale(order_request.payment_cash, order_request.payment_non_cash),
+    ) {
+        Ok(totals) => totals,
+        Err(e) => {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            return Err(e);
+        }
+    };
+    authorize_discount_tx(&mut transaction, user_id, &settings, totals.subtotal, totals.order_discount_amount).await?;
+
+    // 3. Calculate receivable or change. Card/QR payments cannot give change, so any overpayment
+    // must be covered by the cash part of the payment.
+    (totals.receivable, totals.change_due) = match settle_payment(
+        totals.grand_total,
+        order_request.payment_cash,
+        order_request.payment_non_cash,
+    ) {
+        Ok(amounts) => amounts,
+        Err(e) => {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            return Err(e);
+        }
     };
 
     // 4. Insert into sales_orders
     let order = match insert_sales_order(
         &mut transaction, 
         user_id, 
+        company_id,
         &order_request, 
-        grand_total, 
-        receivable
+        &totals,
+        shift_id
     ).await {
         Ok(order) => order,
         Err(e) => {
@@ -385,17 +800,17 @@ pub async fn create_sales_order(
         }
     };
 
-    // 5. Insert order details
+    // 5. Convert the reservations into sales: release them, insert details and record stock movements
+    if let Err(e) = release_reservations_tx(&mut transaction, &cart_item_ids).await {
+        if let Err(rollback_err) = transaction.rollback().await {
+            error!("Failed to rollback transaction: {:?}", rollback_err);
+        }
+        return Err(e);
+    }
+
     let mut order_details = Vec::new();
-    for cart_item in &cart_items {
-        let total_price = cart_item.sale_price * Decimal::new(cart_item.qty as i64, 0);
-        
-        let detail = match insert_sales_order_detail(
-            &mut transaction,
-            order.id,
-            cart_item,
-            total_price
-        ).await {
+    for line in &lines {
+        let detail = match insert_sales_order_detail(&mut transaction, order.id, line).await {
             Ok(detail) => detail,
             Err(e) => {
                 // If there's an error, rollback and return
@@ -406,11 +821,40 @@ pub async fn create_sales_order(
             }
         };
         
+        // Take the sold quantity out of the store's stock
+        let movement = LedgerEntry {
+            store_id: order.store_id,
+            product_id: detail.product_id,
+            movement_type: MOVEMENT_SALE,
+            qty: -detail.qty,
+            reference_type: Some("sales_order"),
+            reference_id: Some(order.id),
+            note: None,
+            user_id,
+        };
+        if let Err(e) = record_movement_tx(&mut transaction, &movement).await {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            return Err(e);
+        }
+
         order_details.push(detail);
     }
 
-    // 6. Clear the cart
-    if let Err(e) = clear_cart_tx(&mut transaction, user_id, order_request.store_id).await {
+    // 6. Record the tender lines
+    let payments = match insert_order_payments_tx(&mut transaction, order.id, &payment_lines).await {
+        Ok(payments) => payments,
+        Err(e) => {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            return Err(e);
+        }
+    };
+
+    // 7. Remove the checked-out cart
+    if let Err(e) = delete_cart_tx(&mut transaction, cart_id).await {
         // If there's an error, rollback and return
         if let Err(rollback_err) = transaction.rollback().await {
             error!("Failed to rollback transaction: {:?}", rollback_err);
@@ -418,36 +862,53 @@ pub async fn create_sales_order(
         return Err(e);
     }
 
+    let response = OrderResponse {
+        order,
+        details: order_details,
+        payments,
+    };
+
+    // 8. Keep the response for retries carrying the same idempotency key
+    if let Some(key) = idempotency_key {
+        if let Err(e) = store_idempotent_response_tx(
+            &mut transaction,
+            user_id,
+            IDEMPOTENCY_SCOPE_CREATE_ORDER,
+            key,
+            &response,
+        ).await {
+            if let Err(rollback_err) = transaction.rollback().await {
+                error!("Failed to rollback transaction: {:?}", rollback_err);
+            }
+            return Err(e);
+        }
+    }
+
     // Commit the transaction
     if let Err(e) = transaction.commit().await {
         error!("Failed to commit transaction: {:?}", e);
         return Err(ServiceError::DatabaseError(e.to_string()));
     }
 
-    info!("Successfully created sales order with ID: {}", order.id);
+    info!("Successfully created sales order with ID: {}", response.order.id);
     
-    Ok(OrderResponse {
-        order,
-        details: order_details,
-    })
+    Ok(response)
 }
 
 // Helper function to get cart items within a transaction
 async fn get_cart_items_tx(
     transaction: &mut Transaction<'_, Postgres>, 
-    user_id: i32, 
-    store_id: i32
+    cart_id: i32
 ) -> Result<Vec<SalesCart>, ServiceError> {
     let cart_items = match sqlx::query_as::<_, SalesCart>(
-        "SELECT id, user_id, store_id, product_id, base_price, qty, 
+        "SELECT id, cart_id, user_id, store_id, product_id, base_price, qty, 
                 discount_type, discount_value, discount_amount, sale_price, 
-                created_at, updated_at 
+                created_at, updated_at, list_price, price_override_by 
          FROM sales_cart 
-         WHERE user_id = $1 AND store_id = $2 
+         WHERE cart_id = $1 
          ORDER BY created_at DESC"
     )
-    .bind(user_id)
-    .bind(store_id)
+    .bind(cart_id)
     .fetch_all(&mut **transaction)
     .await {
         Ok(items) => items,
@@ -457,38 +918,349 @@ async fn get_cart_items_tx(
         }
     };
 
-    info!("Retrieved {} cart items for user {} in store {}", cart_items.len(), user_id, store_id);
+    info!("Retrieved {} cart items from cart {}", cart_items.len(), cart_id);
     Ok(cart_items)
 }
 
+// Split the payment against the grand total into (receivable, change_due)
+pub fn settle_payment(
+    grand_total: Decimal,
+    payment_cash: Decimal,
+    payment_non_cash: Decimal,
+) -> Result<(Decimal, Decimal), ServiceError> {
+    if payment_cash < Decimal::ZERO || payment_non_cash < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Payment amounts cannot be negative".to_string()));
+    }
+    if payment_non_cash > grand_total {
+        return Err(ServiceError::ValidationError(format!(
+            "Non-cash payment ({payment_non_cash}) cannot exceed the grand total ({grand_total})"
+        )));
+    }
+
+    let total_payment = payment_cash + payment_non_cash;
+    if total_payment > grand_total {
+        Ok((Decimal::ZERO, total_payment - grand_total))
+    } else {
+        Ok((grand_total - total_payment, Decimal::ZERO))
+    }
+}
+
+// Allocate the next order number of a store for a business date, formatted as
+// {store_initial}-{YYYYMMDD}-{seq}. The sequence row stays locked until the checkout
+// transaction ends, so concurrent tills wait for each other and a rollback hands the
+// number back, which keeps the sequence gap-free.
+pub async fn allocate_order_number_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    store_id: i32,
+    company_id: i32,
+    date: chrono::NaiveDate,
+) -> Result<String, ServiceError> {
+    let store_initial = match sqlx::query_scalar::<_, String>(
+        "SELECT initial FROM stores WHERE id = $1 AND company_id = $2"
+    )
+    .bind(store_id)
+    .bind(company_id)
+    .fetch_optional(&mut **transaction)
+    .await {
+        Ok(Some(initial)) => initial,
+        Ok(None) => {
+            info!("Store {} not found for company_id {}", store_id, company_id);
+            return Err(ServiceError::ValidationError(format!("Store {store_id} not found")));
+        },
+        Err(e) => {
+            error!("Database error while fetching store {}: {}", store_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let seq = match sqlx::query_scalar::<_, i32>(
+        "INSERT INTO order_number_sequences (store_id, business_date, last_seq)
+         VALUES ($1, $2, 1)
+         ON CONFLICT (store_id, business_date)
+         DO UPDATE SET last_seq = order_number_sequences.last_seq + 1
+         RETURNING last_seq"
+    )
+    .bind(store_id)
+    .bind(date)
+    .fetch_one(&mut **transaction)
+    .await {
+        Ok(seq) => seq,
+        Err(e) => {
+            error!("Database error while allocating order number for store {}: {}", store_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    Ok(format!("{store_initial}-{}-{seq:04}", date.format("%Y%m%d")))
+}
+
+// Amounts computed at checkout and stored on the order header
+pub struct OrderTotals {
+    pub subtotal: Decimal,
+    pub order_discount_type: &'static str,
+    pub order_discount_value: Decimal,
+    pub order_discount_amount: Decimal,
+    pub rounding_amount: Decimal,
+    pub tax_amount: Decimal,
+    pub tax_inclusive: bool,
+    pub service_charge_rate: Decimal,
+    pub service_charge_amount: Decimal,
+    pub grand_total: Decimal,
+    pub receivable: Decimal,
+    pub change_due: Decimal,
+}
+
+// Cash rounding only applies when the whole payment is made in cash
+#[must_use]
+pub fn is_cash_sale(payment_cash: Decimal, payment_non_cash: Decimal) -> bool {
+    payment_cash > Decimal::ZERO && payment_non_cash.is_zero()
+}
+
+// Work out the order header from its priced lines, after their promotions: apply the order
+// discount to the subtotal, give each line its share of it, tax each line at its tax_rate,
+// add the store's service charge, then round cash sales to the company's cash rounding unit.
+// receivable and change_due are left for settle_payment.
+pub fn price_order(
+    lines: &mut [OrderLine<'_>],
+    discount_type: Option<&str>,
+    discount_value: Option<Decimal>,
+    settings: &CompanySettings,
+    store_settings: &StoreSettings,
+    cash_sale: bool,
+) -> Result<OrderTotals, ServiceError> {
+    let decimals = settings.currency_scale();
+    let line_totals: Vec<Decimal> = lines
+        .iter()
+        .map(|line| line.sale_price * Decimal::from(line.qty) - line.promotion_discount())
+        .collect();
+    let subtotal: Decimal = line_totals.iter().copied().sum();
+
+    let order_discount = match apply_line_discount(subtotal, discount_type, discount_value, decimals) {
+        Ok(discount) => discount,
+        Err(ServiceError::ValidationError(msg)) => {
+            return Err(ServiceError::ValidationError(format!("Order discount: {msg}")));
+        }
+        Err(e) => return Err(e),
+    };
+    let shares = prorate_amount(order_discount.discount_amount, &line_totals, decimals);
+    for (line, share) in lines.iter_mut().zip(shares) {
+        line.order_discount_amount = share;
+        line.tax_amount = line_tax(line.total_price(), line.tax_rate, settings.tax_inclusive, decimals);
+    }
+    let tax_amount: Decimal = lines.iter().map(|line| line.tax_amount).sum();
+
+    // The service charge is not taxed itself; before tax it is taken on the lines without their
+    // tax, after tax on the lines including it
+    let charge_bases: Vec<Decimal> = lines
+        .iter()
+        .map(|line| match (line.service_chargeable, store_settings.service_charge_after_tax, settings.tax_inclusive) {
+            (false, _, _) => Decimal::ZERO,
+            (true, false, true) => line.total_price() - line.tax_amount,
+            (true, true, false) => line.total_price() + line.tax_amount,
+            (true, _, _) => line.total_price(),
+        })
+        .collect();
+    let charge_base: Decimal = charge_bases.iter().copied().sum();
+    let service_charge_amount = round_currency(
+        charge_base * store_settings.service_charge_rate / Decimal::ONE_HUNDRED,
+        decimals,
+    );
+    let charge_shares = prorate_amount(service_charge_amount, &charge_bases, decimals);
+    for (line, share) in lines.iter_mut().zip(charge_shares) {
+        line.service_charge_amount = share;
+    }
+
+    let mut net_total = order_discount.sale_price + service_charge_amount;
+    if !settings.tax_inclusive {
+        net_total += tax_amount;
+    }
+    let grand_total = if cash_sale {
+        round_to_cash_unit(net_total, settings.cash_rounding_unit)
+    } else {
+        net_total
+    };
+
+    Ok(OrderTotals {
+        subtotal,
+        order_discount_type: order_discount.discount_type,
+        order_discount_value: order_discount.discount_value,
+        order_discount_amount: order_discount.discount_amount,
+        rounding_amount: grand_total - net_total,
+        tax_amount,
+        tax_inclusive: settings.tax_inclusive,
+        service_charge_rate: store_settings.service_charge_rate,
+        service_charge_amount,
+        grand_total,
+        receivable: grand_total,
+        change_due: Decimal::ZERO,
+    })
+}
+
+// Priced line as it is written to sales_order_details
+pub struct OrderLine<'a> {
+    pub product_id: i32,
+    pub qty: i32,
+    pub base_price: Decimal,
+    pub discount_type: &'a str,
+    pub discount_value: Decimal,
+    // Discount per unit
+    pub discount_amount: Decimal,
+    pub sale_price: Decimal,
+    pub list_price: Decimal,
+    pub price_override_by: Option<i32>,
+    // Share of the order discount for the whole line, set by price_order
+    pub order_discount_amount: Decimal,
+    pub promotions: Vec<AppliedPromotion>,
+    // Set by assign_tax_rates and price_order
+    pub tax_rate: Decimal,
+    pub tax_amount: Decimal,
+    // Set by mark_service_charge_exempt and price_order
+    pub service_chargeable: bool,
+    pub service_charge_amount: Decimal,
+}
+
+// A cart line as input to the promotion rules; lines with a manual discount do not take part
+#[must_use]
+pub fn promotion_line(product_id: i32, qty: i32, sale_price: Decimal, discount_type: &str) -> PromotionLine {
+    PromotionLine {
+        product_id,
+        category_id: None,
+        qty,
+        unit_price: sale_price,
+        eligible: discount_type == DISCOUNT_TYPE_NONE,
+    }
+}
+
+impl<'a> OrderLine<'a> {
+    // Promotion discount for the whole line
+    #[must_use]
+    pub fn promotion_discount(&self) -> Decimal {
+        self.promotions.iter().map(|promotion| promotion.discount_amount).sum()
+    }
+
+    // Line total after its own discount, its promotions and its share of the order discount
+    #[must_use]
+    pub fn total_price(&self) -> Decimal {
+        self.sale_price * Decimal::from(self.qty) - self.promotion_discount() - self.order_discount_amount
+    }
+
+    fn from_cart(cart_item: &'a SalesCart) -> Self {
+        Self {
+            product_id: cart_item.product_id,
+            qty: cart_item.qty,
+            base_price: cart_item.base_price,
+            discount_type: &cart_item.discount_type,
+            discount_value: cart_item.discount_value,
+            discount_amount: cart_item.discount_amount,
+            sale_price: cart_item.sale_price,
+            list_price: cart_item.list_price,
+            price_override_by: cart_item.price_override_by,
+            order_discount_amount: Decimal::ZERO,
+            promotions: Vec::new(),
+            tax_rate: Decimal::ZERO,
+            tax_amount: Decimal::ZERO,
+            service_chargeable: true,
+            service_charge_amount: Decimal::ZERO,
+        }
+    }
+}
+
+// Leave lines whose product category the store exempts out of the service charge
+pub async fn mark_service_charge_exempt(
+    conn: &mut PgConnection,
+    store_settings: &StoreSettings,
+    lines: &mut [OrderLine<'_>],
+) -> Result<(), ServiceError> {
+    if store_settings.service_charge_rate <= Decimal::ZERO
+        || store_settings.service_charge_exempt_category_ids.is_empty()
+    {
+        return Ok(());
+    }
+
+    let product_ids: Vec<i32> = lines.iter().map(|line| line.product_id).collect();
+    let exempt_ids: Vec<i32> = match sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM products WHERE id = ANY($1) AND category_id = ANY($2)"
+    )
+    .bind(&product_ids)
+    .bind(&store_settings.service_charge_exempt_category_ids)
+    .fetch_all(&mut *conn)
+    .await
+    {
+        Ok(ids) => ids,
+        Err(e) => {
+            error!("Database error while fetching service charge exempt products: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    for line in lines {
+        line.service_chargeable = !exempt_ids.contains(&line.product_id);
+    }
+    Ok(())
+}
+
 // Helper function to insert into sales_orders within a transaction
-async fn insert_sales_order(
+pub async fn insert_sales_order(
     transaction: &mut Transaction<'_, Postgres>,
     user_id: i32,
+    company_id: i32,
     order_request: &CreateOrderRequest,
-    grand_total: Decimal,
-    receivable: Decimal,
+    totals: &OrderTotals,
+    shift_id: Option<i32>,
 ) -> Result<SalesOrder, ServiceError> {
-    // Use the provided date or default to today
-    let date = order_request.date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    // The customer, if any, must belong to the same company as the cashier
+    if let Some(customer_id) = order_request.customer_id {
+        if !customer_exists_tx(transaction, customer_id, company_id).await? {
+            info!("Customer {} not found for company_id {}", customer_id, company_id);
+            return Err(ServiceError::ValidationError(format!("Customer {customer_id} not found")));
+        }
+    }
+
+    // Use the provided date or default to today. Order numbers follow the server's business
+    // date instead, so a till with a wrong clock cannot reuse or skip another day's sequence.
+    let business_date = chrono::Local::now().date_naive();
+    let date = order_request.date.unwrap_or(business_date);
+    let order_number = allocate_order_number_tx(transaction, order_request.store_id, company_id, business_date).await?;
 
     let order = match sqlx::query_as::<_, SalesOrder>(
         "INSERT INTO sales_orders (
             order_number, user_id, store_id, date, grand_total, 
-            payment_cash, payment_non_cash, receivable, created_at, customer_id
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), $9)
-        RETURNING id, order_number, user_id, store_id, date, grand_total, 
-                 payment_cash, payment_non_cash, receivable, created_at, customer_id"
+            payment_cash, payment_non_cash, receivable, change_due, created_at, customer_id, status,
+            shift_id, external_reference, subtotal, order_discount_type, order_discount_value,
+            order_discount_amount, rounding_amount, tax_amount, tax_inclusive, service_charge_rate,
+            service_charge_amount
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
+            $21, $22
+        )
+        RETURNING id, order_number, user_id, store_id, date, subtotal, order_discount_type,
+                 order_discount_value, order_discount_amount, rounding_amount, tax_amount, tax_inclusive,
+                 service_charge_rate, service_charge_amount, grand_total, 
+                 payment_cash, payment_non_cash, receivable, change_due, created_at, customer_id,
+                 status, voided_at, voided_by, void_reason, shift_id, external_reference"
     )
-    .bind(&order_request.order_number)
+    .bind(&order_number)
     .bind(user_id)
     .bind(order_request.store_id)
     .bind(date)
-    .bind(grand_total)
+    .bind(totals.grand_total)
     .bind(order_request.payment_cash)
     .bind(order_request.payment_non_cash)
-    .bind(receivable)
+    .bind(totals.receivable)
+    .bind(totals.change_due)
     .bind(order_request.customer_id)
+    .bind(ORDER_STATUS_COMPLETED)
+    .bind(shift_id)
+    .bind(&order_request.external_reference)
+    .bind(totals.subtotal)
+    .bind(totals.order_discount_type)
+    .bind(totals.order_discount_value)
+    .bind(totals.order_discount_amount)
+    .bind(totals.rounding_amount)
+    .bind(totals.tax_amount)
+    .bind(totals.tax_inclusive)
+    .bind(totals.service_charge_rate)
+    .bind(totals.service_charge_amount)
     .fetch_one(&mut **transaction)
     .await {
         Ok(order) => order,
@@ -503,29 +1275,44 @@ async fn insert_sales_order(
 }
 
 // Helper function to insert into sales_order_details within a transaction
-async fn insert_sales_order_detail(
+pub async fn insert_sales_order_detail(
     transaction: &mut Transaction<'_, Postgres>,
     order_id: i32,
-    cart_item: &SalesCart,
-    total_price: Decimal,
+    line: &OrderLine<'_>,
 ) -> Result<SalesOrderDetail, ServiceError> {
+    // unit_cost snapshots the product's purchase price so later price changes do not rewrite past margins
     let detail = match sqlx::query_as::<_, SalesOrderDetail>(
         "INSERT INTO sales_order_details (
             order_id, product_id, qty, base_price, 
-            discount_type, discount_value, discount_amount, sale_price, total_price
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            discount_type, discount_value, discount_amount, sale_price, total_price, unit_cost,
+            list_price, price_override_by, order_discount_amount, promotion_discount_amount,
+            tax_rate, tax_amount, service_charge_amount
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9,
+            COALESCE((SELECT purchase_price FROM products WHERE id = $2), 0),
+            $10, $11, $12, $13, $14, $15, $16
+        )
         RETURNING id, order_id, product_id, qty, base_price, 
-                 discount_type, discount_value, discount_amount, sale_price, total_price"
+                 discount_type, discount_value, discount_amount, sale_price, promotion_discount_amount,
+                 order_discount_amount, total_price, tax_rate, tax_amount, service_charge_amount, unit_cost,
+                 list_price, price_override_by"
     )
     .bind(order_id)
-    .bind(cart_item.product_id)
-    .bind(cart_item.qty)
-    .bind(cart_item.base_price)
-    .bind(&cart_item.discount_type)
-    .bind(Decimal::new(cart_item.discount_value as i64, 0)) // Convert i32 to Decimal
-    .bind(cart_item.discount_amount)
-    .bind(cart_item.sale_price)
-    .bind(total_price)
+    .bind(line.product_id)
+    .bind(line.qty)
+    .bind(line.base_price)
+    .bind(line.discount_type)
+    .bind(line.discount_value)
+    .bind(line.discount_amount)
+    .bind(line.sale_price)
+    .bind(line.total_price())
+    .bind(line.list_price)
+    .bind(line.price_override_by)
+    .bind(line.order_discount_amount)
+    .bind(line.promotion_discount())
+    .bind(line.tax_rate)
+    .bind(line.tax_amount)
+    .bind(line.service_charge_amount)
     .fetch_one(&mut **transaction)
     .await {
         Ok(detail) => detail,
@@ -534,36 +1321,35 @@ async fn insert_sales_order_detail(
             return Err(ServiceError::DatabaseError(e.to_string()));
         }
     };
+    insert_order_promotions_tx(transaction, order_id, detail.id, &line.promotions).await?;
 
     info!("Created sales order detail with ID: {}", detail.id);
     Ok(detail)
 }
 
-// Helper function to clear cart within a transaction
-async fn clear_cart_tx(
+// Helper function to delete a cart and its lines within a transaction
+async fn delete_cart_tx(
     transaction: &mut Transaction<'_, Postgres>,
-    user_id: i32,
-    store_id: i32,
+    cart_id: i32,
 ) -> Result<bool, ServiceError> {
     let result = match sqlx::query(
-        "DELETE FROM sales_cart WHERE user_id = $1 AND store_id = $2"
+        "DELETE FROM carts WHERE id = $1"
     )
-    .bind(user_id)
-    .bind(store_id)
+    .bind(cart_id)
     .execute(&mut **transaction)
     .await {
         Ok(result) => result,
         Err(e) => {
-            error!("Database error while clearing cart: {}", e);
+            error!("Database error while deleting cart: {}", e);
             return Err(ServiceError::DatabaseError(e.to_string()));
         }
     };
 
     let deleted = result.rows_affected() > 0;
     if deleted {
-        info!("Successfully cleared cart for user: {} in store: {}", user_id, store_id);
+        info!("Successfully deleted cart {}", cart_id);
     } else {
-        info!("No cart items found for user: {} in store: {}", user_id, store_id);
+        info!("No cart found with ID: {}", cart_id);
     }
     
     Ok(deleted)
@@ -586,8 +1372,11 @@ pub async fn generate_sales_report(
     // 1. Get orders based on date range and store_id
     let mut orders_query_builder = String::from(
         "SELECT so.id, so.order_number, so.user_id, u.initial as user_initial, 
-        so.store_id, s.initial as store_initial, so.date, so.grand_total, 
-        so.payment_cash, so.payment_non_cash, so.receivable, so.created_at, so.customer_id
+        so.store_id, s.initial as store_initial, so.date, so.subtotal, so.order_discount_type,
+        so.order_discount_value, so.order_discount_amount, so.rounding_amount, so.tax_amount,
+        so.tax_inclusive, so.service_charge_rate, so.service_charge_amount, so.grand_total, 
+        so.payment_cash, so.payment_non_cash, so.receivable, so.change_due, so.created_at, so.customer_id,
+        so.status, so.voided_at, so.voided_by, so.void_reason, so.shift_id, so.external_reference
         FROM sales_orders so
         JOIN users u ON so.user_id = u.id
         JOIN stores s ON so.store_id = s.id
@@ -622,7 +1411,9 @@ pub async fn generate_sales_report(
                 let items = match sqlx::query_as::<_, SalesReportOrderItem>(
                     "SELECT sod.id, sod.order_id, sod.product_id, p.name as product_name, p.sku,
                             sod.qty, sod.base_price, sod.discount_type, sod.discount_value,
-                            sod.discount_amount, sod.sale_price, sod.total_price
+                            sod.discount_amount, sod.sale_price, sod.promotion_discount_amount,
+                            sod.order_discount_amount, sod.total_price, sod.tax_rate, sod.tax_amount,
+                            sod.service_charge_amount
                      FROM sales_order_details sod
                      JOIN products p ON sod.product_id = p.id
                      WHERE sod.order_id = $1
@@ -647,22 +1438,12 @@ pub async fn generate_sales_report(
         }
     };
 
-    // Get the order IDs for SKU summary
-    let order_ids: Vec<i32> = orders.iter().map(|o| o.id).collect();
-    
-    if order_ids.is_empty() {
-        // If no orders found, return an empty report
-        return Ok(SalesReport {
-            orders: vec![],
-            sku_summary: vec![],
-            summary: SalesSummary {
-                total_payment_cash: Decimal::new(0, 0),
-                total_payment_non_cash: Decimal::new(0, 0),
-                total_receivable: Decimal::new(0, 0),
-                total_orders: 0,
-            },
-        });
-    }
+    // Get the order IDs for SKU summary; voided orders are listed but do not count as sales
+    let order_ids: Vec<i32> = orders
+        .iter()
+        .filter(|o| o.status != ORDER_STATUS_VOIDED)
+        .map(|o| o.id)
+        .collect();
 
     // 2. Get SKU summary for the selected orders
     let sku_summary = match sqlx::query_as::<_, SkuSummaryItem>(
@@ -687,12 +1468,44 @@ pub async fn generate_sales_report(
         }
     };
 
-    // 3. Calculate the total summary
+    // 3. Get the returns processed in the same period; they may belong to orders from earlier dates
+    let return_totals = get_return_totals(
+        &pool,
+        company_id,
+        query.start_date,
+        query.end_date,
+        query.store_id,
+    ).await?;
+
+    // 4. Calculate the total summary
+    let (voided_orders, completed_orders): (Vec<&SalesReportOrder>, Vec<&SalesReportOrder>) =
+        orders.iter().partition(|order| order.status == ORDER_STATUS_VOIDED);
+    let gross_sales = completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.grand_total);
+    let total_change_due = completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.change_due);
+
+    let payment_methods = get_payment_method_totals(&pool, &order_ids, total_change_due).await?;
+
     let summary = SalesSummary {
-        total_payment_cash: orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.payment_cash),
-        total_payment_non_cash: orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.payment_non_cash),
-        total_receivable: orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.receivable),
-        total_orders: orders.len() as i32,
+        total_payment_cash: completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.payment_cash - order.change_due),
+        total_payment_non_cash: completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.payment_non_cash),
+        total_receivable: completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.receivable),
+        total_change_due,
+        total_orders: i32::try_from(completed_orders.len()).unwrap_or(i32::MAX),
+        total_voided_orders: i32::try_from(voided_orders.len()).unwrap_or(i32::MAX),
+        total_voided_amount: voided_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.grand_total),
+        gross_sales,
+        total_returns: return_totals.total_returns,
+        total_refund: return_totals.total_refund,
+        total_refund_cash: return_totals.refund_cash,
+        total_refund_non_cash: return_totals.refund_non_cash,
+        total_order_discount: completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.order_discount_amount),
+        total_rounding: completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.rounding_amount),
+        total_tax: completed_orders.iter().fold(Decimal::new(0, 0), |acc, order| acc + order.tax_amount),
+        total_service_charge: completed_orders
+            .iter()
+            .fold(Decimal::new(0, 0), |acc, order| acc + order.service_charge_amount),
+        net_sales: gross_sales - return_totals.total_refund,
+        payment_methods,
     };
 
     // Return the complete sales report
@@ -720,8 +1533,12 @@ pub async fn get_sales_order_by_id(
     let order_row = sqlx::query_as::<_, OrderQueryResult>(
         r#"
         SELECT so.id, so.order_number, so.user_id, u.initial as user_initial, 
-               so.store_id, s.initial as store_initial, so.date, so.grand_total, 
-               so.payment_cash, so.payment_non_cash, so.receivable, so.created_at, so.customer_id,
+               so.store_id, s.initial as store_initial, so.date, so.subtotal, so.order_discount_type,
+               so.order_discount_value, so.order_discount_amount, so.rounding_amount, so.tax_amount,
+               so.tax_inclusive, so.service_charge_rate, so.service_charge_amount, so.grand_total, 
+               so.payment_cash, so.payment_non_cash, so.receivable, so.change_due, so.created_at, so.customer_id,
+               so.status, so.voided_at, so.voided_by, so.void_reason, so.shift_id,
+               so.external_reference,
                u.company_id as creator_company_id
         FROM sales_orders so
         JOIN users u ON so.user_id = u.id
@@ -760,19 +1577,41 @@ pub async fn get_sales_order_by_id(
         store_id: order_row.store_id,
         store_initial: order_row.store_initial,
         date: order_row.date,
+        subtotal: order_row.subtotal,
+        order_discount_type: order_row.order_discount_type,
+        order_discount_value: order_row.order_discount_value,
+        order_discount_amount: order_row.order_discount_amount,
+        rounding_amount: order_row.rounding_amount,
+        tax_amount: order_row.tax_amount,
+        tax_inclusive: order_row.tax_inclusive,
+        service_charge_rate: order_row.service_charge_rate,
+        service_charge_amount: order_row.service_charge_amount,
         grand_total: order_row.grand_total,
         payment_cash: order_row.payment_cash,
         payment_non_cash: order_row.payment_non_cash,
         receivable: order_row.receivable,
+        change_due: order_row.change_due,
         created_at: order_row.created_at,
         customer_id: order_row.customer_id,
+        status: order_row.status,
+        voided_at: order_row.voided_at,
+        voided_by: order_row.voided_by,
+        void_reason: order_row.void_reason,
+        shift_id: order_row.shift_id,
+        external_reference: order_row.external_reference,
     };
 
     // Now, get all the details with product information
     let details = match sqlx::query_as::<_, DetailedSalesOrderDetail>(
         "SELECT sod.id, sod.order_id, sod.product_id, p.name as product_name, p.sku, 
                 sod.qty, sod.base_price, sod.discount_type, sod.discount_value, 
-                sod.discount_amount, sod.sale_price, sod.total_price
+                sod.discount_amount, sod.sale_price, sod.promotion_discount_amount,
+                sod.order_discount_amount, sod.total_price, sod.tax_rate, sod.tax_amount,
+                sod.service_charge_amount, sod.list_price, sod.price_override_by,
+                ARRAY(
+                    SELECT sop.promotion_id FROM sales_order_promotions sop
+                    WHERE sop.order_detail_id = sod.id ORDER BY sop.id
+                ) as promotion_ids
          FROM sales_order_details sod
          JOIN products p ON sod.product_id = p.id
          WHERE sod.order_id = $1
@@ -788,10 +1627,173 @@ pub async fn get_sales_order_by_id(
         }
     };
 
+    let payments = get_order_payments(&pool, order_id).await?;
+
     info!("Successfully retrieved sales order ID: {} with {} detail items", order_id, details.len());
     
     Ok(DetailedOrderResponse {
         order,
         details,
+        payments,
     })
 }
+
+pub async fn void_sales_order(
+    db_manager: &DbConnectionManager,
+    order_id: i32,
+    user_id: i32, // User ID of the person voiding the order
+    requester_company_id: i32,
+    void_request: VoidOrderRequest,
+) -> Result<SalesOrder, ServiceError> {
+    let reason = void_request.reason.trim();
+    if reason.is_empty() {
+        return Err(ServiceError::ValidationError("A reason is required to void an order".to_string()));
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Lock the order and make sure it belongs to the requester's company
+    let current_status = match sqlx::query_scalar::<_, String>(
+        "SELECT so.status
+         FROM sales_orders so
+         JOIN users u ON so.user_id = u.id
+         WHERE so.id = $1 AND u.company_id = $2
+         FOR UPDATE OF so"
+    )
+    .bind(order_id)
+    .bind(requester_company_id)
+    .fetch_optional(&mut *transaction)
+    .await {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            info!("Sales order ID {} not found for company_id {}", order_id, requester_company_id);
+            return Err(ServiceError::NotFound);
+        },
+        Err(e) => {
+            error!("Database error while fetching sales order: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if current_status == ORDER_STATUS_VOIDED {
+        return Err(ServiceError::ValidationError("Order is already voided".to_string()));
+    }
+
+    // Returned units were already restocked and refunded, so voiding would count them twice
+    let has_returns = match sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM sales_returns WHERE order_id = $1)"
+    )
+    .bind(order_id)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(has_returns) => has_returns,
+        Err(e) => {
+            error!("Database error while checking returns of sales order: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if has_returns {
+        return Err(ServiceError::ValidationError(
+            "Cannot void an order that has returns".to_string(),
+        ));
+    }
+
+    // Collected receivable payments stay in the shift's cash, so they must be undone first
+    let has_payments = match sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM receivable_payments WHERE order_id = $1)"
+    )
+    .bind(order_id)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(has_payments) => has_payments,
+        Err(e) => {
+            error!("Database error while checking receivable payments of sales order: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if has_payments {
+        return Err(ServiceError::ValidationError(
+            "Cannot void an order that has receivable payments".to_string(),
+        ));
+    }
+
+    let order = match sqlx::query_as::<_, SalesOrder>(
+        "UPDATE sales_orders
+         SET status = $1, voided_at = NOW(), voided_by = $2, void_reason = $3
+         WHERE id = $4
+         RETURNING id, order_number, user_id, store_id, date, subtotal, order_discount_type,
+                 order_discount_value, order_discount_amount, rounding_amount, tax_amount, tax_inclusive,
+                 service_charge_rate, service_charge_amount, grand_total, payment_cash, payment_non_cash, receivable, change_due, created_at, customer_id,
+                 status, voided_at, voided_by, void_reason, shift_id, external_reference"
+    )
+    .bind(ORDER_STATUS_VOIDED)
+    .bind(user_id)
+    .bind(reason)
+    .bind(order_id)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(order) => order,
+        Err(e) => {
+            error!("Database error while voiding sales order: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Put the sold quantities back into the store's stock
+    let details = match sqlx::query_as::<_, SalesOrderDetail>(
+        "SELECT id, order_id, product_id, qty, base_price,
+                discount_type, discount_value, discount_amount, sale_price, promotion_discount_amount,
+                order_discount_amount, total_price, tax_rate, tax_amount, service_charge_amount, unit_cost,
+                list_price, price_override_by
+         FROM sales_order_details
+         WHERE order_id = $1
+         ORDER BY id"
+    )
+    .bind(order_id)
+    .fetch_all(&mut *transaction)
+    .await {
+        Ok(details) => details,
+        Err(e) => {
+            error!("Database error while fetching order details: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    for detail in &details {
+        let movement = LedgerEntry {
+            store_id: order.store_id,
+            product_id: detail.product_id,
+            movement_type: MOVEMENT_ADJUSTMENT,
+            qty: detail.qty,
+            reference_type: Some("sales_order_void"),
+            reference_id: Some(order.id),
+            note: Some(reason),
+            user_id,
+        };
+        record_movement_tx(&mut transaction, &movement).await?;
+    }
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Sales order ID {} voided by user {}", order_id, user_id);
+    Ok(order)
+}
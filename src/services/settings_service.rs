@@ -0,0 +1,309 @@
+use crate::errors::ServiceError;
+use crate::models::settings::{CompanySettings, StoreSettings, UpdateCompanySettings, UpdateStoreSettings};
+use crate::services::db_service::DbConnectionManager;
+use crate::models::user::PERMISSION_MANAGE_SETTINGS;
+use crate::services::inventory_service::ensure_store_in_company;
+use crate::services::permission_service::has_permission;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::PgExecutor;
+
+// Load the company's settings, falling back to defaults when none have been saved.
+// Accepts a pool or a transaction so checkout can read settings inside its own transaction.
+pub async fn load_company_settings<'e, E>(
+    executor: E,
+    company_id: i32,
+) -> Result<CompanySettings, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    match sqlx::query_as::<_, CompanySettings>(
+        "SELECT company_id, allow_negative_stock, reservation_ttl_minutes, require_open_shift, currency_decimals,
+                cash_rounding_unit, tax_rate, tax_inclusive, max_discount_percent, updated_at
+         FROM company_settings
+         WHERE company_id = $1"
+    )
+    .bind(company_id)
+    .fetch_optional(executor)
+    .await
+    {
+        Ok(Some(settings)) => Ok(settings),
+        Ok(None) => Ok(CompanySettings::defaults(company_id)),
+        Err(e) => {
+            error!("Database error while fetching settings for company_id {}: {}", company_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn get_company_settings(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+) -> Result<CompanySettings, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    load_company_settings(&pool, company_id).await
+}
+
+pub async fn update_company_settings(
+    db_manager: &DbConnectionManager,
+    user_id: i32, // User ID from authentication
+    company_id: i32,
+    update_data: UpdateCompanySettings,
+) -> Result<CompanySettings, ServiceError> {
+    if let Some(ttl) = update_data.reservation_ttl_minutes {
+        if ttl <= 0 {
+            return Err(ServiceError::ValidationError(
+                "reservation_ttl_minutes must be greater than zero".to_string(),
+            ));
+        }
+    }
+
+    if let Some(decimals) = update_data.currency_decimals {
+        // Amounts are stored with two decimals
+        if !(0..=2).contains(&decimals) {
+            return Err(ServiceError::ValidationError(
+                "currency_decimals must be between 0 and 2".to_string(),
+            ));
+        }
+    }
+
+    if let Some(unit) = update_data.cash_rounding_unit {
+        if unit < Decimal::ZERO {
+            return Err(ServiceError::ValidationError(
+                "cash_rounding_unit cannot be negative".to_string(),
+            ));
+        }
+    }
+
+    if let Some(rate) = update_data.tax_rate {
+        if rate < Decimal::ZERO || rate > Decimal::ONE_HUNDRED {
+            return Err(ServiceError::ValidationError(
+                "tax_rate must be between 0 and 100".to_string(),
+            ));
+        }
+    }
+
+    if let Some(percent) = update_data.max_discount_percent {
+        if percent < Decimal::ZERO || percent > Decimal::ONE_HUNDRED {
+            return Err(ServiceError::ValidationError(
+                "max_discount_percent must be between 0 and 100".to_string(),
+            ));
+        }
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    if !has_permission(&pool, user_id, PERMISSION_MANAGE_SETTINGS).await? {
+        info!("User {} is not allowed to change settings", user_id);
+        return Err(ServiceError::Unauthorized);
+    }
+
+    // Start from the current values so omitted fields keep their setting
+    let current = load_company_settings(&pool, company_id).await?;
+    let allow_negative_stock = update_data.allow_negative_stock.unwrap_or(current.allow_negative_stock);
+    let reservation_ttl_minutes = update_data.reservation_ttl_minutes.unwrap_or(current.reservation_ttl_minutes);
+    let require_open_shift = update_data.require_open_shift.unwrap_or(current.require_open_shift);
+    let currency_decimals = update_data.currency_decimals.unwrap_or(current.currency_decimals);
+    let cash_rounding_unit = update_data.cash_rounding_unit.unwrap_or(current.cash_rounding_unit);
+    let tax_rate = update_data.tax_rate.unwrap_or(current.tax_rate);
+    let tax_inclusive = update_data.tax_inclusive.unwrap_or(current.tax_inclusive);
+    let max_discount_percent = update_data.max_discount_percent.unwrap_or(current.max_discount_percent);
+
+    let settings = match sqlx::query_as::<_, CompanySettings>(
+        "INSERT INTO company_settings (
+            company_id, allow_negative_stock, reservation_ttl_minutes, require_open_shift, currency_decimals,
+            cash_rounding_unit, tax_rate, tax_inclusive, max_discount_percent, updated_at
+         ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+         ON CONFLICT (company_id) DO UPDATE
+         SET allow_negative_stock = EXCLUDED.allow_negative_stock,
+             reservation_ttl_minutes = EXCLUDED.reservation_ttl_minutes,
+             require_open_shift = EXCLUDED.require_open_shift,
+             currency_decimals = EXCLUDED.currency_decimals,
+             cash_rounding_unit = EXCLUDED.cash_rounding_unit,
+             tax_rate = EXCLUDED.tax_rate,
+             tax_inclusive = EXCLUDED.tax_inclusive,
+             max_discount_percent = EXCLUDED.max_discount_percent,
+             updated_at = NOW()
+         RETURNING company_id, allow_negative_stock, reservation_ttl_minutes, require_open_shift, currency_decimals,
+                cash_rounding_unit, tax_rate, tax_inclusive, max_discount_percent, updated_at"
+    )
+    .bind(company_id)
+    .bind(allow_negative_stock)
+    .bind(reservation_ttl_minutes)
+    .bind(require_open_shift)
+    .bind(currency_decimals)
+    .bind(cash_rounding_unit)
+    .bind(tax_rate)
+    .bind(tax_inclusive)
+    .bind(max_discount_percent)
+    .fetch_one(&pool)
+    .await
+    {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!("Database error while updating settings for company_id {}: {}", company_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Updated settings for company_id {}", company_id);
+    Ok(settings)
+}
+
+// Load the store's settings, falling back to defaults when none have been saved
+pub async fn load_store_settings<'e, E>(
+    executor: E,
+    store_id: i32,
+) -> Result<StoreSettings, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    match sqlx::query_as::<_, StoreSettings>(
+        "SELECT store_id, service_charge_rate, service_charge_after_tax, service_charge_exempt_category_ids,
+                receipt_footer, updated_at
+         FROM store_settings
+         WHERE store_id = $1"
+    )
+    .bind(store_id)
+    .fetch_optional(executor)
+    .await
+    {
+        Ok(Some(settings)) => Ok(settings),
+        Ok(None) => Ok(StoreSettings::defaults(store_id)),
+        Err(e) => {
+            error!("Database error while fetching settings for store_id {}: {}", store_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn get_store_settings(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    store_id: i32,
+) -> Result<StoreSettings, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            error!("Failed to begin transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+    ensure_store_in_company(&mut transaction, store_id, company_id).await?;
+    let settings = load_store_settings(&mut *transaction, store_id).await?;
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    Ok(settings)
+}
+
+pub async fn update_store_settings(
+    db_manager: &DbConnectionManager,
+    user_id: i32, // User ID from authentication
+    company_id: i32,
+    store_id: i32,
+    update_data: UpdateStoreSettings,
+) -> Result<StoreSettings, ServiceError> {
+    if let Some(rate) = update_data.service_charge_rate {
+        if rate < Decimal::ZERO || rate > Decimal::ONE_HUNDRED {
+            return Err(ServiceError::ValidationError(
+                "service_charge_rate must be between 0 and 100".to_string(),
+            ));
+        }
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    if !has_permission(&pool, user_id, PERMISSION_MANAGE_SETTINGS).await? {
+        info!("User {} is not allowed to change settings", user_id);
+        return Err(ServiceError::Unauthorized);
+    }
+
+    let mut transaction = match pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            error!("Failed to begin transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+    ensure_store_in_company(&mut transaction, store_id, company_id).await?;
+
+    // Start from the current values so omitted fields keep their setting
+    let current = load_store_settings(&mut *transaction, store_id).await?;
+    let service_charge_rate = update_data.service_charge_rate.unwrap_or(current.service_charge_rate);
+    let service_charge_after_tax = update_data.service_charge_after_tax.unwrap_or(current.service_charge_after_tax);
+    let service_charge_exempt_category_ids = update_data
+        .service_charge_exempt_category_ids
+        .unwrap_or(current.service_charge_exempt_category_ids);
+    let receipt_footer = match update_data.receipt_footer {
+        Some(footer) if footer.trim().is_empty() => None,
+        Some(footer) => Some(footer),
+        None => current.receipt_footer,
+    };
+
+    let settings = match sqlx::query_as::<_, StoreSettings>(
+        "INSERT INTO store_settings (
+            store_id, service_charge_rate, service_charge_after_tax, service_charge_exempt_category_ids,
+            receipt_footer, updated_at
+         ) VALUES ($1, $2, $3, $4, $5, NOW())
+         ON CONFLICT (store_id) DO UPDATE
+         SET service_charge_rate = EXCLUDED.service_charge_rate,
+             service_charge_after_tax = EXCLUDED.service_charge_after_tax,
+             service_charge_exempt_category_ids = EXCLUDED.service_charge_exempt_category_ids,
+             receipt_footer = EXCLUDED.receipt_footer,
+             updated_at = NOW()
+         RETURNING store_id, service_charge_rate, service_charge_after_tax, service_charge_exempt_category_ids,
+                receipt_footer, updated_at"
+    )
+    .bind(store_id)
+    .bind(service_charge_rate)
+    .bind(service_charge_after_tax)
+    .bind(&service_charge_exempt_category_ids)
+    .bind(&receipt_footer)
+    .fetch_one(&mut *transaction)
+    .await
+    {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!("Database error while updating settings for store_id {}: {}", store_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Updated settings for store_id {}", store_id);
+    Ok(settings)
+}
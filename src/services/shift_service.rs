@@ -0,0 +1,426 @@
+use crate::errors::ServiceError;
+use crate::models::sales::ORDER_STATUS_VOIDED;
+use crate::models::shift::{
+    CashDrawerEvent, CashShift, CloseShiftRequest, NewCashDrawerEvent, OpenShiftRequest,
+    ShiftCashSummary, ShiftResponse, DRAWER_EVENT_CASH_IN, DRAWER_EVENT_CASH_OUT,
+    SHIFT_STATUS_CLOSED, SHIFT_STATUS_OPEN,
+};
+use crate::models::user::PERMISSION_SUPERVISE_SHIFTS;
+use crate::services::db_service::DbConnectionManager;
+use crate::services::inventory_service::ensure_store_in_company;
+use crate::services::permission_service::has_permission;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::{PgExecutor, Postgres, Transaction};
+
+// Open shift of a cashier in a store, if any. The row is share-locked until the transaction
+// ends, so a sale cannot be booked on a shift that is being closed.
+pub async fn active_shift_id_tx(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    store_id: i32,
+) -> Result<Option<i32>, ServiceError> {
+    match sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM cash_shifts WHERE user_id = $1 AND store_id = $2 AND status = $3 FOR SHARE"
+    )
+    .bind(user_id)
+    .bind(store_id)
+    .bind(SHIFT_STATUS_OPEN)
+    .fetch_optional(&mut **transaction)
+    .await
+    {
+        Ok(shift_id) => Ok(shift_id),
+        Err(e) => {
+            error!("Database error while looking up open shift for user {}: {}", user_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Fetch a shift scoped to the company through its store, optionally locking it
+async fn fetch_shift<'e, E>(
+    executor: E,
+    shift_id: i32,
+    company_id: i32,
+    for_update: bool,
+) -> Result<CashShift, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    let sql = if for_update {
+        "SELECT cs.id, cs.user_id, cs.store_id, cs.status, cs.opening_float, cs.opened_at, cs.opening_note,
+                cs.closing_count, cs.expected_cash, cs.variance, cs.closed_at, cs.closed_by, cs.closing_note
+         FROM cash_shifts cs
+         JOIN stores s ON cs.store_id = s.id
+         WHERE cs.id = $1 AND s.company_id = $2
+         FOR UPDATE OF cs"
+    } else {
+        "SELECT cs.id, cs.user_id, cs.store_id, cs.status, cs.opening_float, cs.opened_at, cs.opening_note,
+                cs.closing_count, cs.expected_cash, cs.variance, cs.closed_at, cs.closed_by, cs.closing_note
+         FROM cash_shifts cs
+         JOIN stores s ON cs.store_id = s.id
+         WHERE cs.id = $1 AND s.company_id = $2"
+    };
+
+    match sqlx::query_as::<_, CashShift>(sql)
+        .bind(shift_id)
+        .bind(company_id)
+        .fetch_optional(executor)
+        .await
+    {
+        Ok(Some(shift)) => Ok(shift),
+        Ok(None) => {
+            info!("Shift ID {} not found for company_id {}", shift_id, company_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while fetching shift {}: {}", shift_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Only the cashier who opened a shift, or a supervisor, may move cash on it or close it
+async fn ensure_shift_access<'e, E>(executor: E, shift: &CashShift, user_id: i32) -> Result<(), ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    if shift.user_id == user_id || has_permission(executor, user_id, PERMISSION_SUPERVISE_SHIFTS).await? {
+        return Ok(());
+    }
+    info!("User {} is not allowed to manage shift ID {}", user_id, shift.id);
+    Err(ServiceError::Unauthorized)
+}
+
+async fn fetch_shift_events<'e, E>(executor: E, shift_id: i32) -> Result<Vec<CashDrawerEvent>, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    match sqlx::query_as::<_, CashDrawerEvent>(
+        "SELECT id, shift_id, event_type, amount, reason, user_id, created_at
+         FROM cash_drawer_events
+         WHERE shift_id = $1
+         ORDER BY created_at, id"
+    )
+    .bind(shift_id)
+    .fetch_all(executor)
+    .await
+    {
+        Ok(events) => Ok(events),
+        Err(e) => {
+            error!("Database error while fetching drawer events for shift {}: {}", shift_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+// Expected drawer cash. Orders are linked to the shift directly; refunds and debt
+// settlements are matched on cashier, store and the time the shift was open.
+async fn compute_shift_summary<'e, E>(executor: E, shift_id: i32) -> Result<ShiftCashSummary, ServiceError>
+where
+    E: PgExecutor<'e>,
+{
+    match sqlx::query_as::<_, ShiftCashSummary>(
+        "SELECT t.total_orders, t.cash_sales, t.cash_refunds, t.receivable_cash, t.cash_in, t.cash_out,
+                t.opening_float + t.cash_sales + t.receivable_cash + t.cash_in
+                    - t.cash_refunds - t.cash_out as expected_cash
+         FROM (
+             SELECT cs.opening_float,
+                    (SELECT COUNT(*)::int4 FROM sales_orders so
+                     WHERE so.shift_id = cs.id AND so.status <> $2) as total_orders,
+                    COALESCE((SELECT SUM(so.payment_cash - so.change_due) FROM sales_orders so
+                              WHERE so.shift_id = cs.id AND so.status <> $2), 0) as cash_sales,
+                    COALESCE((SELECT SUM(sr.refund_cash) FROM sales_returns sr
+                              WHERE sr.user_id = cs.user_id AND sr.store_id = cs.store_id
+                              AND sr.created_at BETWEEN cs.opened_at AND COALESCE(cs.closed_at, NOW())), 0) as cash_refunds,
+                    COALESCE((SELECT SUM(rp.payment_cash) FROM receivable_payments rp
+                              WHERE rp.user_id = cs.user_id AND rp.store_id = cs.store_id
+                              AND rp.created_at BETWEEN cs.opened_at AND COALESCE(cs.closed_at, NOW())), 0) as receivable_cash,
+                    COALESCE((SELECT SUM(e.amount) FROM cash_drawer_events e
+                              WHERE e.shift_id = cs.id AND e.event_type = $3), 0) as cash_in,
+                    COALESCE((SELECT SUM(e.amount) FROM cash_drawer_events e
+                              WHERE e.shift_id = cs.id AND e.event_type = $4), 0) as cash_out
+             FROM cash_shifts cs
+             WHERE cs.id = $1
+         ) t"
+    )
+    .bind(shift_id)
+    .bind(ORDER_STATUS_VOIDED)
+    .bind(DRAWER_EVENT_CASH_IN)
+    .bind(DRAWER_EVENT_CASH_OUT)
+    .fetch_one(executor)
+    .await
+    {
+        Ok(summary) => Ok(summary),
+        Err(e) => {
+            error!("Database error while computing summary for shift {}: {}", shift_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+pub async fn open_shift(
+    db_manager: &DbConnectionManager,
+    user_id: i32, // User ID from authentication
+    company_id: i32,
+    request: OpenShiftRequest,
+) -> Result<CashShift, ServiceError> {
+    if request.opening_float < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Opening float cannot be negative".to_string()));
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    ensure_store_in_company(&mut transaction, request.store_id, company_id).await?;
+
+    if let Some(shift_id) = active_shift_id_tx(&mut transaction, user_id, request.store_id).await? {
+        return Err(ServiceError::ValidationError(format!(
+            "Shift {shift_id} is already open for this store"
+        )));
+    }
+
+    let shift = match sqlx::query_as::<_, CashShift>(
+        "INSERT INTO cash_shifts (user_id, store_id, status, opening_float, opened_at, opening_note)
+         VALUES ($1, $2, $3, $4, NOW(), $5)
+         RETURNING id, user_id, store_id, status, opening_float, opened_at, opening_note,
+                   closing_count, expected_cash, variance, closed_at, closed_by, closing_note"
+    )
+    .bind(user_id)
+    .bind(request.store_id)
+    .bind(SHIFT_STATUS_OPEN)
+    .bind(request.opening_float)
+    .bind(&request.note)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(shift) => shift,
+        Err(e) => {
+            error!("Database error while opening shift: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Opened shift ID {} for user {} in store {}", shift.id, user_id, shift.store_id);
+    Ok(shift)
+}
+
+pub async fn get_current_shift(
+    db_manager: &DbConnectionManager,
+    user_id: i32,
+    company_id: i32,
+    store_id: i32,
+) -> Result<ShiftResponse, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let shift_id = match sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM cash_shifts WHERE user_id = $1 AND store_id = $2 AND status = $3"
+    )
+    .bind(user_id)
+    .bind(store_id)
+    .bind(SHIFT_STATUS_OPEN)
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(shift_id)) => shift_id,
+        Ok(None) => {
+            info!("No open shift for user {} in store {}", user_id, store_id);
+            return Err(ServiceError::NotFound);
+        },
+        Err(e) => {
+            error!("Database error while looking up open shift for user {}: {}", user_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let shift = fetch_shift(&pool, shift_id, company_id, false).await?;
+    let events = fetch_shift_events(&pool, shift_id).await?;
+    let summary = compute_shift_summary(&pool, shift_id).await?;
+
+    Ok(ShiftResponse { shift, events, summary })
+}
+
+pub async fn get_shift_by_id(
+    db_manager: &DbConnectionManager,
+    shift_id: i32,
+    company_id: i32,
+) -> Result<ShiftResponse, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let shift = fetch_shift(&pool, shift_id, company_id, false).await?;
+    let events = fetch_shift_events(&pool, shift_id).await?;
+    let summary = compute_shift_summary(&pool, shift_id).await?;
+
+    Ok(ShiftResponse { shift, events, summary })
+}
+
+pub async fn add_drawer_event(
+    db_manager: &DbConnectionManager,
+    shift_id: i32,
+    user_id: i32, // User ID from authentication
+    company_id: i32,
+    new_event: NewCashDrawerEvent,
+) -> Result<CashDrawerEvent, ServiceError> {
+    if new_event.event_type != DRAWER_EVENT_CASH_IN && new_event.event_type != DRAWER_EVENT_CASH_OUT {
+        return Err(ServiceError::ValidationError(format!(
+            "Unknown drawer event type '{}'",
+            new_event.event_type
+        )));
+    }
+    if new_event.amount <= Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Amount must be greater than zero".to_string()));
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Lock the shift so an event cannot slip in while it is being closed
+    let shift = fetch_shift(&mut *transaction, shift_id, company_id, true).await?;
+    ensure_shift_access(&mut *transaction, &shift, user_id).await?;
+    if shift.status != SHIFT_STATUS_OPEN {
+        return Err(ServiceError::ValidationError("Shift is already closed".to_string()));
+    }
+
+    let event = match sqlx::query_as::<_, CashDrawerEvent>(
+        "INSERT INTO cash_drawer_events (shift_id, event_type, amount, reason, user_id, created_at)
+         VALUES ($1, $2, $3, $4, $5, NOW())
+         RETURNING id, shift_id, event_type, amount, reason, user_id, created_at"
+    )
+    .bind(shift.id)
+    .bind(&new_event.event_type)
+    .bind(new_event.amount)
+    .bind(&new_event.reason)
+    .bind(user_id)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(event) => event,
+        Err(e) => {
+            error!("Database error while recording drawer event: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!("Recorded {} of {} on shift ID {}", event.event_type, event.amount, shift_id);
+    Ok(event)
+}
+
+pub async fn close_shift(
+    db_manager: &DbConnectionManager,
+    shift_id: i32,
+    user_id: i32, // User ID of the person closing the shift
+    company_id: i32,
+    request: CloseShiftRequest,
+) -> Result<ShiftResponse, ServiceError> {
+    if request.closing_count < Decimal::ZERO {
+        return Err(ServiceError::ValidationError("Closing count cannot be negative".to_string()));
+    }
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let mut transaction = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            error!("Failed to start transaction: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let shift = fetch_shift(&mut *transaction, shift_id, company_id, true).await?;
+    ensure_shift_access(&mut *transaction, &shift, user_id).await?;
+    if shift.status != SHIFT_STATUS_OPEN {
+        return Err(ServiceError::ValidationError("Shift is already closed".to_string()));
+    }
+
+    // NOW() is fixed for the transaction, so the summary window matches the stored closed_at
+    let summary = compute_shift_summary(&mut *transaction, shift.id).await?;
+    let variance = request.closing_count - summary.expected_cash;
+
+    let shift = match sqlx::query_as::<_, CashShift>(
+        "UPDATE cash_shifts
+         SET status = $1, closing_count = $2, expected_cash = $3, variance = $4,
+             closed_at = NOW(), closed_by = $5, closing_note = $6
+         WHERE id = $7
+         RETURNING id, user_id, store_id, status, opening_float, opened_at, opening_note,
+                   closing_count, expected_cash, variance, closed_at, closed_by, closing_note"
+    )
+    .bind(SHIFT_STATUS_CLOSED)
+    .bind(request.closing_count)
+    .bind(summary.expected_cash)
+    .bind(variance)
+    .bind(user_id)
+    .bind(&request.note)
+    .bind(shift.id)
+    .fetch_one(&mut *transaction)
+    .await {
+        Ok(shift) => shift,
+        Err(e) => {
+            error!("Database error while closing shift {}: {}", shift_id, e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let events = fetch_shift_events(&mut *transaction, shift.id).await?;
+
+    if let Err(e) = transaction.commit().await {
+        error!("Failed to commit transaction: {:?}", e);
+        return Err(ServiceError::DatabaseError(e.to_string()));
+    }
+
+    info!(
+        "Closed shift ID {}: expected {}, counted {}, variance {}",
+        shift.id, summary.expected_cash, request.closing_count, variance
+    );
+    Ok(ShiftResponse { shift, events, summary })
+}
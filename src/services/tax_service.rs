@@ -0,0 +1,222 @@
+use crate::errors::ServiceError;
+use crate::models::product::TAX_CATEGORY_EXEMPT;
+use crate::models::sales::ORDER_STATUS_VOIDED;
+use crate::models::settings::CompanySettings;
+use crate::models::tax_report::{
+    TaxReport, TaxReportQuery, TaxReportRow, TaxReportTotals, TAX_PERIOD_DAY, TAX_PERIOD_MONTH,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::discount_engine::round_currency;
+use crate::services::sales_service::OrderLine;
+use crate::services::settings_service::load_company_settings;
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::PgConnection;
+use std::collections::{BTreeMap, HashMap};
+
+// Tax contained in (inclusive) or owed on top of (exclusive) a line total
+#[must_use]
+pub fn line_tax(amount: Decimal, rate: Decimal, inclusive: bool, decimals: u32) -> Decimal {
+    if rate <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    if inclusive {
+        round_currency(amount * rate / (Decimal::ONE_HUNDRED + rate), decimals)
+    } else {
+        round_currency(amount * rate / Decimal::ONE_HUNDRED, decimals)
+    }
+}
+
+// Give each line the company tax rate, or zero for products in the exempt tax category
+pub async fn assign_tax_rates(
+    conn: &mut PgConnection,
+    settings: &CompanySettings,
+    lines: &mut [OrderLine<'_>],
+) -> Result<(), ServiceError> {
+    if settings.tax_rate <= Decimal::ZERO || lines.is_empty() {
+        return Ok(());
+    }
+
+    let product_ids: Vec<i32> = lines.iter().map(|line| line.product_id).collect();
+    let categories: HashMap<i32, String> = match sqlx::query_as::<_, (i32, String)>(
+        "SELECT id, tax_category FROM products WHERE id = ANY($1)"
+    )
+    .bind(&product_ids)
+    .fetch_all(&mut *conn)
+    .await
+    {
+        Ok(rows) => rows.into_iter().collect(),
+        Err(e) => {
+            error!("Database error while fetching product tax categories: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    for line in lines {
+        let exempt = categories.get(&line.product_id).is_some_and(|category| category == TAX_CATEGORY_EXEMPT);
+        line.tax_rate = if exempt { Decimal::ZERO } else { settings.tax_rate };
+    }
+    Ok(())
+}
+
+pub async fn generate_tax_report(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    query: TaxReportQuery,
+) -> Result<TaxReport, ServiceError> {
+    if query.start_date > query.end_date {
+        return Err(ServiceError::ValidationError("start_date must not be after end_date".to_string()));
+    }
+
+    let period = query.period.unwrap_or_else(|| TAX_PERIOD_MONTH.to_string());
+    let period_format = match period.as_str() {
+        TAX_PERIOD_DAY => "YYYY-MM-DD",
+        TAX_PERIOD_MONTH => "YYYY-MM",
+        _ => {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown period '{period}', expected day or month"
+            )));
+        }
+    };
+    let store_id = query.store_id.unwrap_or(0);
+
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    // The tax base of a line is its total without the tax it contains
+    let sales = match sqlx::query_as::<_, TaxReportRow>(
+        "SELECT to_char(so.date, $6) as period,
+                COUNT(DISTINCT so.id)::int8 as total_orders,
+                COALESCE(SUM(CASE WHEN sod.tax_rate > 0 THEN
+                    sod.total_price - CASE WHEN so.tax_inclusive THEN sod.tax_amount ELSE 0 END
+                END), 0) as taxable_sales,
+                COALESCE(SUM(sod.tax_amount), 0) as tax_amount,
+                COALESCE(SUM(CASE WHEN sod.tax_rate = 0 THEN sod.total_price END), 0) as exempt_sales,
+                0::numeric as returned_taxable_sales,
+                0::numeric as returned_tax_amount,
+                0::numeric as net_tax_amount
+         FROM sales_order_details sod
+         JOIN sales_orders so ON sod.order_id = so.id
+         JOIN users u ON so.user_id = u.id
+         WHERE so.date BETWEEN $1 AND $2
+         AND u.company_id = $3
+         AND so.status <> $4
+         AND ($5 = 0 OR so.store_id = $5)
+         GROUP BY 1"
+    )
+    .bind(query.start_date)
+    .bind(query.end_date)
+    .bind(company_id)
+    .bind(ORDER_STATUS_VOIDED)
+    .bind(store_id)
+    .bind(period_format)
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Error fetching tax report sales: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    // Returned units give back their share of the line's tax base and tax, rounded once per
+    // return like the refund. The return that takes back the last units of a line gets what is
+    // left, so a line returned in full gives back exactly the tax it was charged. Earlier returns
+    // of a line count towards that even when they fall outside the period.
+    let decimals = load_company_settings(&pool, company_id).await?.currency_scale();
+    let returns = match sqlx::query_as::<_, (String, Decimal, Decimal)>(
+        "WITH shares AS (
+             SELECT sr.date, sr.store_id, sod.qty as line_qty, line_base, sod.tax_amount as line_tax,
+                    SUM(srd.qty) OVER line_returns as returned_qty,
+                    ROUND(line_base * srd.qty / sod.qty, $6) as base_share,
+                    ROUND(sod.tax_amount * srd.qty / sod.qty, $6) as tax_share,
+                    COALESCE(SUM(ROUND(line_base * srd.qty / sod.qty, $6)) OVER earlier_returns, 0) as earlier_base,
+                    COALESCE(SUM(ROUND(sod.tax_amount * srd.qty / sod.qty, $6)) OVER earlier_returns, 0) as earlier_tax
+             FROM sales_return_details srd
+             JOIN sales_returns sr ON srd.return_id = sr.id
+             JOIN sales_order_details sod ON srd.order_detail_id = sod.id
+             JOIN sales_orders so ON sod.order_id = so.id
+             JOIN users u ON so.user_id = u.id
+             CROSS JOIN LATERAL (
+                 SELECT sod.total_price - CASE WHEN so.tax_inclusive THEN sod.tax_amount ELSE 0 END as line_base
+             ) base
+             WHERE u.company_id = $3
+             AND sod.tax_rate > 0
+             WINDOW line_returns AS (PARTITION BY srd.order_detail_id ORDER BY srd.id),
+                    earlier_returns AS (
+                        PARTITION BY srd.order_detail_id ORDER BY srd.id
+                        ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING
+                    )
+         )
+         SELECT to_char(date, $4) as period,
+                COALESCE(SUM(CASE WHEN returned_qty >= line_qty THEN line_base - earlier_base
+                                  ELSE base_share END), 0) as returned_taxable_sales,
+                COALESCE(SUM(CASE WHEN returned_qty >= line_qty THEN line_tax - earlier_tax
+                                  ELSE tax_share END), 0) as returned_tax_amount
+         FROM shares
+         WHERE date BETWEEN $1 AND $2
+         AND ($5 = 0 OR store_id = $5)
+         GROUP BY 1"
+    )
+    .bind(query.start_date)
+    .bind(query.end_date)
+    .bind(company_id)
+    .bind(period_format)
+    .bind(store_id)
+    .bind(i32::try_from(decimals).unwrap_or(2))
+    .fetch_all(&pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Error fetching tax report returns: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let mut by_period: BTreeMap<String, TaxReportRow> = sales
+        .into_iter()
+        .map(|row| (row.period.clone(), row))
+        .collect();
+    for (period_key, returned_taxable_sales, returned_tax_amount) in returns {
+        let row = by_period.entry(period_key.clone()).or_insert_with(|| TaxReportRow {
+            period: period_key,
+            ..TaxReportRow::default()
+        });
+        row.returned_taxable_sales = returned_taxable_sales;
+        row.returned_tax_amount = returned_tax_amount;
+    }
+    let rows: Vec<TaxReportRow> = by_period
+        .into_values()
+        .map(|mut row| {
+            row.net_tax_amount = row.tax_amount - row.returned_tax_amount;
+            row
+        })
+        .collect();
+
+    let totals = TaxReportTotals {
+        total_orders: rows.iter().map(|row| row.total_orders).sum(),
+        taxable_sales: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.taxable_sales),
+        tax_amount: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.tax_amount),
+        exempt_sales: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.exempt_sales),
+        returned_taxable_sales: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.returned_taxable_sales),
+        returned_tax_amount: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.returned_tax_amount),
+        net_tax_amount: rows.iter().fold(Decimal::ZERO, |acc, row| acc + row.net_tax_amount),
+    };
+
+    info!("Generated tax report by {} with {} rows", period, rows.len());
+    Ok(TaxReport {
+        start_date: query.start_date,
+        end_date: query.end_date,
+        store_id,
+        period,
+        rows,
+        totals,
+    })
+}
@@ -0,0 +1,314 @@
+use crate::errors::ServiceError;
+use crate::models::sales::ORDER_STATUS_VOIDED;
+use crate::models::z_report::{
+    CashierSubtotal, CloseZReportRequest, ZReport, ZReportClosing, ZReportDiscrepancy,
+    ZReportFigures, ZReportQuery,
+};
+use crate::services::db_service::DbConnectionManager;
+use crate::services::payment_service::get_payment_method_totals;
+use crate::services::sales_return_service::get_return_totals;
+use chrono::{NaiveDate, NaiveDateTime};
+use log::{error, info};
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPool;
+use sqlx::types::Json;
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+// One order of the business date with the discount given on its lines
+#[derive(FromRow)]
+struct ZOrderRow {
+    id: i32,
+    order_number: String,
+    user_id: i32,
+    user_initial: String,
+    grand_total: Decimal,
+    payment_cash: Decimal,
+    change_due: Decimal,
+    status: String,
+    total_discount: Decimal,
+}
+
+#[derive(FromRow)]
+struct ZReportRow {
+    id: i32,
+    closed_at: NaiveDateTime,
+    closed_by: i32,
+    figures: Json<ZReportFigures>,
+}
+
+async fn fetch_store_initial(
+    pool: &PgPool,
+    store_id: i32,
+    company_id: i32,
+) -> Result<String, ServiceError> {
+    match sqlx::query_scalar::<_, String>("SELECT initial FROM stores WHERE id = $1 AND company_id = $2")
+        .bind(store_id)
+        .bind(company_id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(initial)) => Ok(initial),
+        Ok(None) => {
+            info!("Store {} not found for company_id {}", store_id, company_id);
+            Err(ServiceError::NotFound)
+        }
+        Err(e) => {
+            error!("Database error while checking store {}: {}", store_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+async fn fetch_closing(
+    pool: &PgPool,
+    store_id: i32,
+    business_date: NaiveDate,
+) -> Result<Option<ZReportClosing>, ServiceError> {
+    match sqlx::query_as::<_, ZReportRow>(
+        "SELECT id, closed_at, closed_by, figures
+         FROM z_reports
+         WHERE store_id = $1 AND business_date = $2"
+    )
+    .bind(store_id)
+    .bind(business_date)
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(row) => Ok(row.map(|row| ZReportClosing {
+            id: row.id,
+            closed_at: row.closed_at,
+            closed_by: row.closed_by,
+            figures: row.figures.0,
+        })),
+        Err(e) => {
+            error!("Database error while fetching Z report for store {}: {}", store_id, e);
+            Err(ServiceError::DatabaseError(e.to_string()))
+        }
+    }
+}
+
+async fn compute_figures(
+    pool: &PgPool,
+    company_id: i32,
+    store_id: i32,
+    business_date: NaiveDate,
+) -> Result<ZReportFigures, ServiceError> {
+    let orders = match sqlx::query_as::<_, ZOrderRow>(
+        "SELECT so.id, so.order_number, so.user_id, u.initial as user_initial, so.grand_total,
+                so.payment_cash, so.change_due, so.status,
+                COALESCE((
+                    SELECT SUM(sod.discount_amount * sod.qty + sod.promotion_discount_amount) FROM sales_order_details sod
+                    WHERE sod.order_id = so.id
+                ), 0) + so.order_discount_amount as total_discount
+         FROM sales_orders so
+         JOIN users u ON so.user_id = u.id
+         WHERE so.store_id = $1 AND so.date = $2 AND u.company_id = $3
+         ORDER BY so.created_at, so.id"
+    )
+    .bind(store_id)
+    .bind(business_date)
+    .bind(company_id)
+    .fetch_all(pool)
+    .await {
+        Ok(orders) => orders,
+        Err(e) => {
+            error!("Error fetching orders for Z report: {:?}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    let (voided_orders, completed_orders): (Vec<&ZOrderRow>, Vec<&ZOrderRow>) =
+        orders.iter().partition(|order| order.status == ORDER_STATUS_VOIDED);
+
+    let gross_sales = completed_orders.iter().fold(Decimal::ZERO, |acc, order| acc + order.grand_total);
+    let total_discounts = completed_orders.iter().fold(Decimal::ZERO, |acc, order| acc + order.total_discount);
+    let total_change_due = completed_orders.iter().fold(Decimal::ZERO, |acc, order| acc + order.change_due);
+    let total_orders = i32::try_from(completed_orders.len()).unwrap_or(i32::MAX);
+    let average_basket = if total_orders > 0 {
+        (gross_sales / Decimal::from(total_orders)).round_dp(2)
+    } else {
+        Decimal::ZERO
+    };
+
+    // Per-cashier subtotals, ordered by user ID
+    let mut cashiers: BTreeMap<i32, CashierSubtotal> = BTreeMap::new();
+    for order in &completed_orders {
+        let subtotal = cashiers.entry(order.user_id).or_insert_with(|| CashierSubtotal {
+            user_id: order.user_id,
+            user_initial: order.user_initial.clone(),
+            total_orders: 0,
+            gross_sales: Decimal::ZERO,
+            total_discounts: Decimal::ZERO,
+            cash_sales: Decimal::ZERO,
+        });
+        subtotal.total_orders += 1;
+        subtotal.gross_sales += order.grand_total;
+        subtotal.total_discounts += order.total_discount;
+        subtotal.cash_sales += order.payment_cash - order.change_due;
+    }
+
+    let order_ids: Vec<i32> = completed_orders.iter().map(|order| order.id).collect();
+    let tenders = get_payment_method_totals(pool, &order_ids, total_change_due).await?;
+    let return_totals = get_return_totals(pool, company_id, business_date, business_date, store_id).await?;
+
+    Ok(ZReportFigures {
+        gross_sales,
+        total_discounts,
+        total_orders,
+        average_basket,
+        first_order_number: completed_orders.first().map(|order| order.order_number.clone()),
+        last_order_number: completed_orders.last().map(|order| order.order_number.clone()),
+        total_voided_orders: i32::try_from(voided_orders.len()).unwrap_or(i32::MAX),
+        total_voided_amount: voided_orders.iter().fold(Decimal::ZERO, |acc, order| acc + order.grand_total),
+        total_returns: return_totals.total_returns,
+        total_refund: return_totals.total_refund,
+        net_sales: gross_sales - return_totals.total_refund,
+        total_change_due,
+        tenders,
+        cashiers: cashiers.into_values().collect(),
+    })
+}
+
+fn push_if_changed<T: PartialEq + Display>(
+    discrepancies: &mut Vec<ZReportDiscrepancy>,
+    field: &str,
+    closed: &T,
+    current: &T,
+) {
+    if closed != current {
+        discrepancies.push(ZReportDiscrepancy {
+            field: field.to_string(),
+            closed_value: closed.to_string(),
+            current_value: current.to_string(),
+        });
+    }
+}
+
+// Compare the closed figures with the current ones and list what changed since closing
+fn find_discrepancies(closed: &ZReportFigures, current: &ZReportFigures) -> Vec<ZReportDiscrepancy> {
+    let mut discrepancies = Vec::new();
+    push_if_changed(&mut discrepancies, "gross_sales", &closed.gross_sales, &current.gross_sales);
+    push_if_changed(&mut discrepancies, "total_discounts", &closed.total_discounts, &current.total_discounts);
+    push_if_changed(&mut discrepancies, "total_orders", &closed.total_orders, &current.total_orders);
+    push_if_changed(&mut discrepancies, "total_voided_orders", &closed.total_voided_orders, &current.total_voided_orders);
+    push_if_changed(&mut discrepancies, "total_voided_amount", &closed.total_voided_amount, &current.total_voided_amount);
+    push_if_changed(&mut discrepancies, "total_returns", &closed.total_returns, &current.total_returns);
+    push_if_changed(&mut discrepancies, "total_refund", &closed.total_refund, &current.total_refund);
+    push_if_changed(&mut discrepancies, "net_sales", &closed.net_sales, &current.net_sales);
+
+    let mut tenders: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+    for tender in &closed.tenders {
+        tenders.entry(tender.method.as_str()).or_default().0 = tender.total_amount;
+    }
+    for tender in &current.tenders {
+        tenders.entry(tender.method.as_str()).or_default().1 = tender.total_amount;
+    }
+    for (method, (closed_amount, current_amount)) in tenders {
+        push_if_changed(&mut discrepancies, &format!("tender.{method}"), &closed_amount, &current_amount);
+    }
+
+    discrepancies
+}
+
+pub async fn get_z_report(
+    db_manager: &DbConnectionManager,
+    company_id: i32,
+    query: ZReportQuery,
+) -> Result<ZReport, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let store_initial = fetch_store_initial(&pool, query.store_id, company_id).await?;
+    let figures = compute_figures(&pool, company_id, query.store_id, query.date).await?;
+    let closing = fetch_closing(&pool, query.store_id, query.date).await?;
+    let discrepancies = closing
+        .as_ref()
+        .map(|closing| find_discrepancies(&closing.figures, &figures))
+        .unwrap_or_default();
+
+    if !discrepancies.is_empty() {
+        info!(
+            "Z report for store {} on {} has {} discrepancies against its closed figures",
+            query.store_id, query.date, discrepancies.len()
+        );
+    }
+
+    Ok(ZReport {
+        store_id: query.store_id,
+        store_initial,
+        business_date: query.date,
+        figures,
+        closing,
+        discrepancies,
+    })
+}
+
+pub async fn close_z_report(
+    db_manager: &DbConnectionManager,
+    user_id: i32, // User ID of the person closing the day
+    company_id: i32,
+    request: CloseZReportRequest,
+) -> Result<ZReport, ServiceError> {
+    let pool = match db_manager.get_pool().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to get database connection: {:?}", e);
+            return Err(ServiceError::DatabaseConnectionError);
+        }
+    };
+
+    let store_initial = fetch_store_initial(&pool, request.store_id, company_id).await?;
+    let figures = compute_figures(&pool, company_id, request.store_id, request.date).await?;
+
+    // The unique (store_id, business_date) key makes closing a day a one-time operation
+    let row = match sqlx::query_as::<_, ZReportRow>(
+        "INSERT INTO z_reports (
+            store_id, business_date, closed_by, closed_at, gross_sales, net_sales, total_orders, figures
+        ) VALUES ($1, $2, $3, NOW(), $4, $5, $6, $7)
+        ON CONFLICT (store_id, business_date) DO NOTHING
+        RETURNING id, closed_at, closed_by, figures"
+    )
+    .bind(request.store_id)
+    .bind(request.date)
+    .bind(user_id)
+    .bind(figures.gross_sales)
+    .bind(figures.net_sales)
+    .bind(figures.total_orders)
+    .bind(Json(&figures))
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) => row,
+        Ok(None) => {
+            return Err(ServiceError::ValidationError(format!(
+                "Business date {} is already closed for this store",
+                request.date
+            )));
+        },
+        Err(e) => {
+            error!("Database error while closing Z report: {}", e);
+            return Err(ServiceError::DatabaseError(e.to_string()));
+        }
+    };
+
+    info!("Closed Z report ID {} for store {} on {}", row.id, request.store_id, request.date);
+    Ok(ZReport {
+        store_id: request.store_id,
+        store_initial,
+        business_date: request.date,
+        closing: Some(ZReportClosing {
+            id: row.id,
+            closed_at: row.closed_at,
+            closed_by: row.closed_by,
+            figures: row.figures.0,
+        }),
+        figures,
+        discrepancies: Vec::new(),
+    })
+}